@@ -1,5 +1,5 @@
 // beam_search.rs
-use crate::{Instance, Solution, Solver};
+use crate::{Instance, Solution};
 
 #[derive(Debug, Clone, Copy, PartialEq, Eq)]
 enum ReqState {
@@ -47,7 +47,6 @@ impl BeamSearch {
 
     pub fn search(&self) -> Solution {
         let mut beam = vec![self.initial_state()];
-        let dist_matrix = self.instance.compute_distance_matrix();
         let max_depth = self.max_depth.unwrap_or(self.instance.n_reqs() * 4);
 
         for depth in 0..max_depth {
@@ -57,7 +56,7 @@ impl BeamSearch {
 
             let mut all_successors = Vec::new();
             for state in &beam {
-                let successors = self.generate_successors(state, &dist_matrix);
+                let successors = self.generate_successors(state);
                 all_successors.extend(successors);
             }
 
@@ -68,26 +67,26 @@ impl BeamSearch {
             // Keep complete solutions that meet gamma requirement
             let (complete, incomplete): (Vec<_>, Vec<_>) = all_successors
                 .into_iter()
-                .partition(|s| s.served_count >= self.instance.gamma() && self.is_feasible(s));
+                .partition(|s| self.is_covered(s) && self.is_feasible(s));
 
             if !complete.is_empty() {
-                beam = self.select_best_states(complete, &dist_matrix);
+                beam = self.select_best_states(complete);
                 // Continue searching for better complete solutions
                 if depth < max_depth - 1 {
                     let mut continued_beam = beam.clone();
                     for state in &beam {
-                        let more_successors = self.generate_successors(state, &dist_matrix);
+                        let more_successors = self.generate_successors(state);
                         continued_beam.extend(more_successors);
                     }
-                    beam = self.select_best_states(continued_beam, &dist_matrix);
+                    beam = self.select_best_states(continued_beam);
                 }
                 break;
             } else {
-                beam = self.select_best_states(incomplete, &dist_matrix);
+                beam = self.select_best_states(incomplete);
             }
         }
 
-        self.best_complete_solution(&beam, &dist_matrix)
+        self.best_complete_solution(&beam)
             .unwrap_or_else(|| self.fallback_solution())
     }
 
@@ -102,19 +101,17 @@ impl BeamSearch {
         }
     }
 
-    fn generate_successors(&self, state: &PartialSolution, dist_matrix: &[Vec<usize>]) -> Vec<PartialSolution> {
+    fn generate_successors(&self, state: &PartialSolution) -> Vec<PartialSolution> {
         let mut successors = Vec::new();
 
         for vehicle_id in 0..self.instance.n_vehicles() {
-            let route = &state.routes[vehicle_id];
             let current_load = state.current_loads[vehicle_id];
-            let last_loc = route.last().copied().unwrap_or(0);
 
             // 1. Pickup actions
             for req_id in 0..self.instance.n_reqs() {
                 if state.req_states[req_id] == ReqState::Unserved {
                     let demand = self.instance.demands()[req_id];
-                    if current_load + demand <= self.instance.cap() {
+                    if self.instance.within_capacity(vehicle_id, current_load + demand) {
                         if let Some(new_state) = self.apply_pickup(state, vehicle_id, req_id) {
                             successors.push(new_state);
                         }
@@ -124,30 +121,10 @@ impl BeamSearch {
 
             // 2. Dropoff actions (only for requests picked up by this vehicle)
             for req_id in 0..self.instance.n_reqs() {
-                if state.req_states[req_id] == ReqState::PickedUp {
-                    if state.pickup_vehicle[req_id] == Some(vehicle_id) {
-                        if let Some(new_state) = self.apply_dropoff(state, vehicle_id, req_id) {
-                            successors.push(new_state);
-                        }
-                    }
-                }
-            }
-
-            // 3. Return to depot ONLY when necessary (vehicle full or no more feasible actions)
-            if !route.is_empty() && last_loc != 0 {
-                let has_feasible_pickups = (0..self.instance.n_reqs()).any(|req_id| {
-                    state.req_states[req_id] == ReqState::Unserved &&
-                    state.current_loads[vehicle_id] + self.instance.demands()[req_id] <= self.instance.cap()
-                });
-
-                let has_pending_dropoffs = (0..self.instance.n_reqs()).any(|req_id| {
-                    state.req_states[req_id] == ReqState::PickedUp &&
-                    state.pickup_vehicle[req_id] == Some(vehicle_id)
-                });
-
-                // Only return to depot if no more work can be done from current location
-                if !has_feasible_pickups && !has_pending_dropoffs {
-                    if let Some(new_state) = self.apply_depot_return(state, vehicle_id) {
+                if state.req_states[req_id] == ReqState::PickedUp
+                    && state.pickup_vehicle[req_id] == Some(vehicle_id)
+                {
+                    if let Some(new_state) = self.apply_dropoff(state, vehicle_id, req_id) {
                         successors.push(new_state);
                     }
                 }
@@ -162,6 +139,9 @@ impl BeamSearch {
         let pickup_index = 1 + req_id; // pickup locations start at index 1
         
         new_state.routes[vehicle_id].push(pickup_index);
+        if !self.instance.route_constraints_feasible(vehicle_id, &new_state.routes[vehicle_id]) {
+            return None;
+        }
         new_state.req_states[req_id] = ReqState::PickedUp;
         new_state.current_loads[vehicle_id] += self.instance.demands()[req_id];
         new_state.pickup_vehicle[req_id] = Some(vehicle_id);
@@ -174,6 +154,9 @@ impl BeamSearch {
         let dropoff_index = 1 + self.instance.n_reqs() + req_id; // dropoff locations after pickups
         
         new_state.routes[vehicle_id].push(dropoff_index);
+        if !self.instance.route_constraints_feasible(vehicle_id, &new_state.routes[vehicle_id]) {
+            return None;
+        }
         new_state.req_states[req_id] = ReqState::Delivered;
         new_state.current_loads[vehicle_id] -= self.instance.demands()[req_id];
         new_state.served_count += 1;
@@ -181,22 +164,15 @@ impl BeamSearch {
         Some(new_state)
     }
 
-    fn apply_depot_return(&self, state: &PartialSolution, vehicle_id: usize) -> Option<PartialSolution> {
-        let mut new_state = state.clone();
-        new_state.routes[vehicle_id].push(0); // 0 represents depot
-        
-        Some(new_state)
-    }
-
-    fn select_best_states(&self, mut states: Vec<PartialSolution>, dist_matrix: &[Vec<usize>]) -> Vec<PartialSolution> {
+    fn select_best_states(&self, mut states: Vec<PartialSolution>) -> Vec<PartialSolution> {
         if states.len() <= self.beam_width {
             return states;
         }
 
         // Remove duplicates and invalid states
         states.sort_by(|a, b| {
-            let score_a = self.heuristic_score(a, dist_matrix);
-            let score_b = self.heuristic_score(b, dist_matrix);
+            let score_a = self.heuristic_score(a);
+            let score_b = self.heuristic_score(b);
             score_a.partial_cmp(&score_b).unwrap_or(std::cmp::Ordering::Equal)
         });
         states.dedup();
@@ -204,7 +180,7 @@ impl BeamSearch {
         // Score all states and take best ones
         let mut scored_states: Vec<(f64, PartialSolution)> = states
             .into_iter()
-            .map(|state| (self.heuristic_score(&state, dist_matrix), state))
+            .map(|state| (self.heuristic_score(&state), state))
             .collect();
 
         scored_states.sort_by(|a, b| a.0.partial_cmp(&b.0).unwrap_or(std::cmp::Ordering::Equal));
@@ -216,36 +192,29 @@ impl BeamSearch {
             .collect()
     }
 
-    fn heuristic_score(&self, state: &PartialSolution, dist_matrix: &[Vec<usize>]) -> f64 {
+    fn heuristic_score(&self, state: &PartialSolution) -> f64 {
         let route_distances: Vec<f64> = state.routes.iter()
-            .map(|r| self.compute_route_distance(r, dist_matrix))
+            .enumerate()
+            .map(|(vehicle_id, r)| self.instance.route_distance(vehicle_id, r))
             .collect();
 
         let total_distance: f64 = route_distances.iter().sum();
-        let fairness = self.compute_jain_fairness(&route_distances);
 
-        let gamma = self.instance.gamma();
         let rho = self.instance.rho();
 
-        let gamma_penalty = if state.served_count < gamma {
-            (gamma - state.served_count) as f64 * 10000.0
-        } else {
-            0.0
-        };
+        // Requests, or profit, still missing for gamma or the minimum profit
+        let served = self.served_requests(state);
+        let gamma_penalty = self.instance.coverage_shortfall(&served).max(0.0) * 10000.0;
+        let unserved_profit = self.instance.unserved_profit(&served);
 
-        let fairness_penalty = rho * (1.0 - fairness);
-
-        // Heavy penalty for excessive depot returns
-        let depot_return_penalty = state.routes.iter()
-            .map(|route| {
-                route.iter().filter(|&&loc| loc == 0).count() as f64 * 500.0
-            })
-            .sum::<f64>();
+        let fairness_penalty = rho * self.instance.fairness_measure().penalty(&route_distances);
 
         // Penalty for short routes (encourage serving multiple requests per route)
         let route_efficiency_penalty = state.routes.iter()
             .map(|route| {
-                let served_in_route = route.iter().filter(|&&loc| loc > self.instance.n_reqs()).count();
+                let served_in_route = route.iter()
+                    .filter(|&&loc| loc > self.instance.n_reqs() && loc <= 2 * self.instance.n_reqs())
+                    .count();
                 if served_in_route < 2 && !route.is_empty() {
                     200.0 // Penalty for routes serving too few requests
                 } else {
@@ -254,70 +223,42 @@ impl BeamSearch {
             })
             .sum::<f64>();
 
-        total_distance + fairness_penalty + gamma_penalty + depot_return_penalty + route_efficiency_penalty
+        total_distance + fairness_penalty + gamma_penalty + unserved_profit + route_efficiency_penalty
     }
 
-    fn compute_route_distance(&self, route: &[usize], dist_matrix: &[Vec<usize>]) -> f64 {
-        if route.is_empty() {
-            return 0.0;
-        }
-        
-        let mut distance = 0.0;
-        
-        // From depot to first stop (if first stop is not depot)
-        if route[0] != 0 {
-            distance += dist_matrix[0][route[0]] as f64;
-        }
-        
-        // Between consecutive stops
-        for i in 0..route.len() - 1 {
-            distance += dist_matrix[route[i]][route[i + 1]] as f64;
-        }
-        
-        // From last stop back to depot (if last stop is not depot)
-        if let Some(&last_stop) = route.last() {
-            if last_stop != 0 {
-                distance += dist_matrix[last_stop][0] as f64;
-            }
-        }
-        
-        distance
+    fn served_requests(&self, state: &PartialSolution) -> Vec<usize> {
+        (0..self.instance.n_reqs())
+            .filter(|&req_id| state.req_states[req_id] == ReqState::Delivered)
+            .collect()
     }
 
-    fn compute_jain_fairness(&self, route_distances: &[f64]) -> f64 {
-        let sum: f64 = route_distances.iter().sum();
-        let sum_sq: f64 = route_distances.iter().map(|d| d * d).sum();
-        let k = route_distances.len() as f64;
-
-        if sum_sq == 0.0 {
-            1.0
-        } else {
-            (sum * sum) / (k * sum_sq)
-        }
+    /// Whether gamma requests, or the minimum profit, are served
+    fn is_covered(&self, state: &PartialSolution) -> bool {
+        self.instance.coverage_met(&self.served_requests(state))
     }
 
     fn is_feasible(&self, state: &PartialSolution) -> bool {
-        for &load in &state.current_loads {
-            if load > self.instance.cap() {
+        for (vehicle_id, &load) in state.current_loads.iter().enumerate() {
+            if !self.instance.within_capacity(vehicle_id, load) {
                 return false;
             }
         }
 
         // Check that all delivered requests were properly picked up
         for req_id in 0..self.instance.n_reqs() {
-            if state.req_states[req_id] == ReqState::Delivered {
-                if state.pickup_vehicle[req_id].is_none() {
-                    return false;
-                }
+            if state.req_states[req_id] == ReqState::Delivered
+                && state.pickup_vehicle[req_id].is_none()
+            {
+                return false;
             }
         }
 
         true
     }
 
-    fn best_complete_solution(&self, beam: &[PartialSolution], dist_matrix: &[Vec<usize>]) -> Option<Solution> {
+    fn best_complete_solution(&self, beam: &[PartialSolution]) -> Option<Solution> {
         let complete_solutions: Vec<_> = beam.iter()
-            .filter(|state| state.served_count >= self.instance.gamma() && self.is_feasible(state))
+            .filter(|state| self.is_covered(state) && self.is_feasible(state))
             .collect();
 
         if complete_solutions.is_empty() {
@@ -347,4 +288,11 @@ impl crate::Solver for BeamSearch {
     fn solve(&self) -> Solution {
         self.search()
     }
+
+    fn config_description(&self) -> String {
+        match self.max_depth {
+            Some(max_depth) => format!("beam_width={} max_depth={}", self.beam_width, max_depth),
+            None => format!("beam_width={}", self.beam_width),
+        }
+    }
 }
\ No newline at end of file
@@ -0,0 +1,248 @@
+use std::time::Instant;
+
+use crate::compare::ComparisonReport;
+use crate::results::{csv_safe, ResultRow};
+use crate::solver::SearchStats;
+use crate::{Instance, ObjectiveBreakdown, Solution, Solver};
+
+/// Builds a solver for an instance and a seed; deterministic solvers ignore the seed
+pub type SolverFactory = Box<dyn for<'a> Fn(&'a Instance, u64) -> Box<dyn Solver + 'a>>;
+
+/// Runs any number of named solvers on the same instances
+#[derive(Default)]
+pub struct Benchmark {
+    algorithms: Vec<(String, SolverFactory)>,
+}
+
+impl Benchmark {
+    pub fn new() -> Self {
+        Self { algorithms: Vec::new() }
+    }
+
+    /// Add a solver; the factory is called once per instance and seed
+    pub fn with_algorithm<F>(mut self, name: &str, factory: F) -> Self
+    where
+        F: for<'a> Fn(&'a Instance, u64) -> Box<dyn Solver + 'a> + 'static,
+    {
+        self.algorithms.push((name.to_string(), Box::new(factory)));
+        self
+    }
+
+    pub fn algorithm_names(&self) -> Vec<String> {
+        self.algorithms.iter().map(|(name, _)| name.clone()).collect()
+    }
+
+    /// Solve a single instance with one of the algorithms, timing the run
+    pub fn run_one(&self, algorithm: usize, instance: &Instance, seed: u64) -> BenchmarkEntry {
+        let (name, factory) = &self.algorithms[algorithm];
+        let start_time = Instant::now();
+        let solver = factory(instance, seed);
+        let solution = solver.solve();
+        let time_seconds = start_time.elapsed().as_secs_f64();
+
+        let mut stats = solver.stats();
+        // Constructive solvers find their only solution at the end
+        stats.time_to_best_seconds.get_or_insert(time_seconds);
+
+        BenchmarkEntry {
+            instance_name: instance.name().to_string(),
+            algorithm: name.clone(),
+            seed,
+            config: solver.config_description(),
+            time_seconds,
+            stats,
+            solution,
+        }
+    }
+
+    /// Run every algorithm on every instance, once per seed
+    pub fn run(&self, instances: &[Instance], seeds: &[u64]) -> BenchmarkResults {
+        let mut entries = Vec::new();
+        for instance in instances {
+            for &seed in seeds {
+                for algorithm in 0..self.algorithms.len() {
+                    entries.push(self.run_one(algorithm, instance, seed));
+                }
+            }
+        }
+        BenchmarkResults { algorithms: self.algorithm_names(), entries }
+    }
+}
+
+/// Result of one algorithm on one instance with one seed
+#[derive(Debug, Clone)]
+pub struct BenchmarkEntry {
+    pub instance_name: String,
+    pub algorithm: String,
+    pub seed: u64,
+    /// The solver's `config_description`
+    pub config: String,
+    pub time_seconds: f64,
+    pub stats: SearchStats,
+    pub solution: Solution,
+}
+
+impl BenchmarkEntry {
+    pub fn csv_header() -> String {
+        format!(
+            "instance_name,algorithm,seed,config,time_seconds,time_to_best,iterations,evaluations,\
+             objective_value,fairness_measure,fairness,jain_fairness,valid,num_vehicles,used_vehicles,{}",
+            ObjectiveBreakdown::csv_header()
+        )
+    }
+
+    /// Fields matching `csv_header`
+    pub fn csv_fields(&self) -> String {
+        let breakdown = self.solution.objective_breakdown();
+        format!(
+            "{},{},{},{},{:.6},{:.6},{},{},{:.6},{},{:.6},{:.6},{},{},{},{}",
+            csv_safe(&self.instance_name),
+            csv_safe(&self.algorithm),
+            self.seed,
+            csv_safe(&self.config),
+            self.time_seconds,
+            self.stats.time_to_best_seconds.unwrap_or(self.time_seconds),
+            self.stats.iterations,
+            self.stats.evaluations,
+            self.solution.objective_function_value(),
+            csv_safe(&self.solution.instance.fairness_measure().to_string()),
+            self.solution.fairness(),
+            self.solution.jain_fairness(),
+            self.solution.is_valid(),
+            self.solution.routes.len(),
+            breakdown.routes.len() - breakdown.idle_vehicles,
+            breakdown.csv_fields()
+        )
+    }
+
+    fn to_result_row(&self) -> ResultRow {
+        ResultRow {
+            instance_name: self.instance_name.clone(),
+            time_seconds: self.time_seconds,
+            objective_value: self.solution.objective_function_value(),
+            jain_fairness: self.solution.jain_fairness(),
+            fairness_measure: self.solution.instance.fairness_measure().to_string(),
+            // Lets the comparison leave invalid solutions out
+            fields: [("valid".to_string(), self.solution.is_valid().to_string())].into(),
+        }
+    }
+}
+
+/// Table of benchmark entries, one per (instance, algorithm, seed)
+#[derive(Debug, Clone, Default)]
+pub struct BenchmarkResults {
+    pub algorithms: Vec<String>,
+    pub entries: Vec<BenchmarkEntry>,
+}
+
+impl BenchmarkResults {
+    pub fn get(&self, instance_name: &str, algorithm: &str) -> Option<&BenchmarkEntry> {
+        self.entries.iter()
+            .find(|e| e.instance_name == instance_name && e.algorithm == algorithm)
+    }
+
+    /// All entries of one algorithm, in instance order
+    pub fn for_algorithm(&self, algorithm: &str) -> Vec<&BenchmarkEntry> {
+        self.entries.iter().filter(|e| e.algorithm == algorithm).collect()
+    }
+
+    /// Long-format CSV with one row per (instance, algorithm, seed)
+    pub fn to_csv(&self) -> String {
+        let mut lines = vec![BenchmarkEntry::csv_header()];
+        lines.extend(self.entries.iter().map(|e| e.csv_fields()));
+        lines.join("\n") + "\n"
+    }
+
+    /// Statistical comparison of the algorithms on the benchmarked instances
+    pub fn comparison(&self) -> Result<ComparisonReport, Box<dyn std::error::Error>> {
+        let results: Vec<(String, Vec<ResultRow>)> = self.algorithms.iter()
+            .map(|algorithm| {
+                let rows = self.for_algorithm(algorithm)
+                    .into_iter()
+                    .map(|e| e.to_result_row())
+                    .collect();
+                (algorithm.clone(), rows)
+            })
+            .collect();
+        ComparisonReport::new(&results, None)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::instance::tests::from_text;
+
+    const INSTANCE: &str = "\
+2 1 10 2 0
+# demands
+1 1
+# request locations
+0 0
+0 1
+0 3
+0 2
+0 4
+";
+
+    /// Returns the given route for the only vehicle
+    struct Fixed<'a> {
+        instance: &'a Instance,
+        routes: Vec<usize>,
+    }
+
+    impl Solver for Fixed<'_> {
+        fn solve(&self) -> Solution {
+            Solution::new(self.instance.clone(), vec![self.routes.clone()])
+        }
+
+        fn config_description(&self) -> String {
+            format!("{:?}", self.routes)
+        }
+    }
+
+    fn benchmark() -> Benchmark {
+        Benchmark::new()
+            .with_algorithm("one_vehicle", |instance, _seed| Box::new(Fixed { instance, routes: vec![1, 3, 2, 4] }))
+            .with_algorithm("detour", |instance, _seed| Box::new(Fixed { instance, routes: vec![2, 1, 4, 3] }))
+            // Serves one of the two requests except with seed 0
+            .with_algorithm("partial", |instance, seed| {
+                let routes = if seed == 0 { vec![1, 3, 2, 4] } else { vec![1, 3] };
+                Box::new(Fixed { instance, routes })
+            })
+    }
+
+    #[test]
+    fn runs_every_algorithm_on_every_instance_and_seed() {
+        let instances = vec![from_text("benchmark_a", INSTANCE), from_text("benchmark_b", INSTANCE)];
+        let results = benchmark().run(&instances, &[3, 4]);
+
+        assert_eq!(results.algorithms, vec!["one_vehicle", "detour", "partial"]);
+        assert_eq!(results.entries.len(), 2 * 2 * 3);
+        assert_eq!(results.for_algorithm("detour").len(), 4);
+        let entry = results.get(instances[1].name(), "partial").unwrap();
+        assert_eq!(entry.seed, 3);
+        assert_eq!(entry.config, "[1, 3]");
+        assert!(entry.stats.time_to_best_seconds.is_some());
+    }
+
+    #[test]
+    fn csv_has_one_row_per_entry_matching_the_header() {
+        let instances = vec![from_text("benchmark_csv", INSTANCE)];
+        let csv = benchmark().run(&instances, &[0]).to_csv();
+        let lines: Vec<&str> = csv.lines().collect();
+        assert_eq!(lines.len(), 4);
+        let columns = lines[0].split(',').count();
+        assert!(lines.iter().all(|line| line.split(',').count() == columns));
+        assert!(lines[3].contains(",partial,0,"));
+    }
+
+    #[test]
+    fn comparison_leaves_invalid_entries_out() {
+        let instances = vec![from_text("benchmark_compare", INSTANCE)];
+        let report = benchmark().run(&instances, &[0, 1]).comparison().unwrap();
+        assert_eq!(report.algorithms.len(), 3);
+        assert_eq!((report.algorithms[2].runs, report.algorithms[2].invalid_runs), (1, 1));
+        assert_eq!(report.algorithms[2].objectives, report.algorithms[0].objectives);
+    }
+}
@@ -0,0 +1,231 @@
+use std::collections::HashMap;
+use std::fs::{self, File};
+use std::io::{BufRead, BufReader, Write};
+use std::path::Path;
+
+use crate::compare::relative_percentage_deviation;
+use crate::{FairnessMeasure, Solution};
+
+/// Default location of the registry file
+pub const DEFAULT_REGISTRY_PATH: &str = "results/best_known.csv";
+
+/// Best known objective of one instance under one fairness measure and where it came from
+#[derive(Debug, Clone, PartialEq)]
+pub struct BestKnown {
+    pub fairness_measure: FairnessMeasure,
+    pub objective_value: f64,
+    pub solution_file: String,
+    pub algorithm: String,
+}
+
+/// Persistent best-known-solutions registry, stored as CSV with one row per
+/// instance and fairness measure, since objectives under different measures
+/// are not comparable. Solutions that improve an entry are written next to the
+/// registry in `best_known/<instance>.sol`, with `_<measure>` appended to the
+/// name for measures other than Jain.
+#[derive(Debug, Clone, PartialEq)]
+pub struct BestKnownRegistry {
+    path: String,
+    /// Keyed by instance name and the measure's name
+    entries: HashMap<(String, String), BestKnown>,
+}
+
+impl BestKnownRegistry {
+    /// Load the registry from `path`; a missing file gives an empty registry.
+    /// Rows of the older format without a measure column count as Jain.
+    pub fn load(path: &str) -> Result<Self, Box<dyn std::error::Error>> {
+        let mut entries = HashMap::new();
+
+        if Path::new(path).exists() {
+            let reader = BufReader::new(File::open(path)?);
+            for (line_number, line) in reader.lines().enumerate().skip(1) {
+                let line = line?;
+                if line.trim().is_empty() {
+                    continue;
+                }
+                let parts: Vec<&str> = line.split(',').collect();
+                let (name, fairness_measure, rest) = match parts.as_slice() {
+                    [name, measure, rest @ ..] if rest.len() == 3 => (name, measure.parse()?, rest),
+                    [name, rest @ ..] if rest.len() == 3 => (name, FairnessMeasure::default(), rest),
+                    _ => return Err(format!("Malformed line {} in {}", line_number + 1, path).into()),
+                };
+                entries.insert(Self::key(name, fairness_measure), BestKnown {
+                    fairness_measure,
+                    objective_value: rest[0].parse()?,
+                    solution_file: rest[1].to_string(),
+                    algorithm: rest[2].to_string(),
+                });
+            }
+        }
+
+        Ok(Self { path: path.to_string(), entries })
+    }
+
+    fn key(instance_name: &str, measure: FairnessMeasure) -> (String, String) {
+        (instance_name.to_string(), measure.to_string())
+    }
+
+    pub fn get(&self, instance_name: &str, measure: FairnessMeasure) -> Option<&BestKnown> {
+        self.entries.get(&Self::key(instance_name, measure))
+    }
+
+    /// Best known objective of every instance under `measure`, e.g. for `ComparisonReport::new`
+    pub fn objectives(&self, measure: FairnessMeasure) -> HashMap<String, f64> {
+        self.entries.iter()
+            .filter(|(_, entry)| entry.fairness_measure == measure)
+            .map(|((name, _), entry)| (name.clone(), entry.objective_value))
+            .collect()
+    }
+
+    /// Relative percentage gap of `objective` to the best known value under
+    /// `measure`, None if the instance has no entry for it
+    pub fn gap(&self, instance_name: &str, measure: FairnessMeasure, objective: f64) -> Option<f64> {
+        self.get(instance_name, measure)
+            .map(|entry| relative_percentage_deviation(objective, entry.objective_value))
+    }
+
+    /// Record `solution` if it is valid and better than the current entry for
+    /// its instance and fairness measure. Writes the solution file and saves
+    /// the registry; returns true on improvement.
+    pub fn update(&mut self, solution: &Solution, algorithm: &str) -> Result<bool, Box<dyn std::error::Error>> {
+        if !solution.is_valid() {
+            return Ok(false);
+        }
+
+        let instance_name = solution.instance.name();
+        let measure = solution.instance.fairness_measure();
+        let objective = solution.objective_function_value();
+        if let Some(entry) = self.get(instance_name, measure) {
+            if objective >= entry.objective_value - 1e-9 {
+                return Ok(false);
+            }
+        }
+
+        let solutions_dir = Path::new(&self.path)
+            .parent()
+            .unwrap_or(Path::new("."))
+            .join("best_known");
+        fs::create_dir_all(&solutions_dir)?;
+        let file_stem = if measure == FairnessMeasure::default() {
+            instance_name.to_string()
+        } else {
+            format!("{}_{}", instance_name, measure.to_string().replace(':', "_"))
+        };
+        let solution_file = solutions_dir
+            .join(format!("{}.sol", file_stem))
+            .to_string_lossy()
+            .into_owned();
+        solution.to_file(&solution_file)?;
+
+        self.entries.insert(Self::key(instance_name, measure), BestKnown {
+            fairness_measure: measure,
+            objective_value: objective,
+            solution_file,
+            algorithm: algorithm.to_string(),
+        });
+        self.save()?;
+        Ok(true)
+    }
+
+    /// Write the registry, replacing the file atomically
+    pub fn save(&self) -> Result<(), Box<dyn std::error::Error>> {
+        if let Some(parent) = Path::new(&self.path).parent() {
+            fs::create_dir_all(parent)?;
+        }
+
+        let mut keys: Vec<&(String, String)> = self.entries.keys().collect();
+        keys.sort();
+
+        let tmp_path = format!("{}.tmp", self.path);
+        {
+            let mut file = File::create(&tmp_path)?;
+            writeln!(file, "instance_name,fairness_measure,objective_value,solution_file,algorithm")?;
+            for key in keys {
+                let entry = &self.entries[key];
+                writeln!(
+                    file,
+                    "{},{},{:.6},{},{}",
+                    key.0, key.1, entry.objective_value, entry.solution_file, entry.algorithm
+                )?;
+            }
+            file.sync_all()?;
+        }
+        fs::rename(&tmp_path, &self.path)?;
+        Ok(())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::instance::tests::from_text;
+
+    const INSTANCE: &str = "\
+2 2 10 2 0
+# demands
+1 1
+# request locations
+0 0
+0 1
+0 3
+0 2
+0 4
+";
+
+    fn registry_path(name: &str) -> String {
+        let dir = std::env::temp_dir().join(format!("scf_pdp_bks_{}_{}", name, std::process::id()));
+        let _ = fs::remove_dir_all(&dir);
+        dir.join("best_known.csv").to_string_lossy().into_owned()
+    }
+
+    #[test]
+    fn updates_only_on_valid_better_solutions() {
+        let path = registry_path("update");
+        let instance = from_text("bks", INSTANCE);
+        let mut registry = BestKnownRegistry::load(&path).unwrap();
+
+        // Serves only one of the two requests
+        let invalid = Solution::new(instance.clone(), vec![vec![1, 3], vec![]]);
+        assert!(!registry.update(&invalid, "a").unwrap());
+        assert!(registry.get(instance.name(), FairnessMeasure::Jain).is_none());
+
+        // Distance 12, then the same distance by a detour, then 8
+        let split = Solution::new(instance.clone(), vec![vec![1, 3], vec![2, 4]]);
+        assert!(registry.update(&split, "a").unwrap());
+        let detour = Solution::new(instance.clone(), vec![vec![2, 1, 4, 3], vec![]]);
+        assert!(!registry.update(&detour, "b").unwrap());
+        let one_vehicle = Solution::new(instance.clone(), vec![vec![1, 3, 2, 4], vec![]]);
+        assert!(registry.update(&one_vehicle, "c").unwrap());
+        assert!(!registry.update(&split, "d").unwrap());
+
+        let entry = registry.get(instance.name(), FairnessMeasure::Jain).unwrap();
+        assert_eq!(entry.algorithm, "c");
+        assert_eq!(entry.objective_value, 8.0);
+        assert!(Path::new(&entry.solution_file).exists());
+        let _ = fs::remove_dir_all(Path::new(&path).parent().unwrap());
+    }
+
+    #[test]
+    fn saved_registry_loads_again() {
+        let path = registry_path("load");
+        let instance = from_text("bks_load", INSTANCE);
+        let mut registry = BestKnownRegistry::load(&path).unwrap();
+        registry.update(&Solution::new(instance.clone(), vec![vec![1, 3], vec![2, 4]]), "a").unwrap();
+
+        assert_eq!(BestKnownRegistry::load(&path).unwrap(), registry);
+        let _ = fs::remove_dir_all(Path::new(&path).parent().unwrap());
+    }
+
+    #[test]
+    fn rows_without_measure_count_as_jain() {
+        let path = registry_path("old_format");
+        fs::create_dir_all(Path::new(&path).parent().unwrap()).unwrap();
+        fs::write(&path, "instance_name,objective_value,solution_file,algorithm\nx,200,x.sol,grasp\n").unwrap();
+
+        let registry = BestKnownRegistry::load(&path).unwrap();
+        assert_eq!(registry.get("x", FairnessMeasure::Jain).unwrap().objective_value, 200.0);
+        assert_eq!(registry.gap("x", FairnessMeasure::Jain, 210.0), Some(5.0));
+        assert_eq!(registry.gap("y", FairnessMeasure::Jain, 210.0), None);
+        let _ = fs::remove_dir_all(Path::new(&path).parent().unwrap());
+    }
+}
@@ -0,0 +1,109 @@
+use std::fmt;
+
+/// Per-route part of an `ObjectiveBreakdown`
+#[derive(Debug, Clone, PartialEq)]
+pub struct RouteBreakdown {
+    pub distance: f64,
+    /// Number of pickup and dropoff stops, depot visits excluded
+    pub stops: usize,
+    /// Vehicle load after each stop of the route
+    pub load_profile: Vec<usize>,
+    pub max_load: usize,
+    pub served_requests: usize,
+    /// Share of the fairness penalty (without rho) attributed to this route,
+    /// per measure as in `FairnessMeasure::contributions`
+    pub unfairness_contribution: f64,
+}
+
+impl RouteBreakdown {
+    pub fn is_idle(&self) -> bool {
+        self.stops == 0
+    }
+}
+
+/// Decomposition of the objective
+/// `distance_term + fleet_cost_term + fairness_term + unserved_profit_term` of a solution
+#[derive(Debug, Clone, PartialEq)]
+pub struct ObjectiveBreakdown {
+    pub routes: Vec<RouteBreakdown>,
+    pub served_requests: usize,
+    pub idle_vehicles: usize,
+    /// Total travel distance
+    pub distance_term: f64,
+    /// Fixed vehicle costs plus the distance cost beyond one per unit;
+    /// zero for a homogeneous fleet without costs
+    pub fleet_cost_term: f64,
+    /// Fairness penalty before multiplying by rho
+    pub fairness_penalty: f64,
+    /// rho * fairness_penalty
+    pub fairness_term: f64,
+    /// Profit of the requests not served; zero without profits
+    pub unserved_profit_term: f64,
+}
+
+impl ObjectiveBreakdown {
+    pub fn objective_value(&self) -> f64 {
+        self.distance_term + self.fleet_cost_term + self.fairness_term + self.unserved_profit_term
+    }
+
+    /// Column names matching `csv_fields`
+    pub fn csv_header() -> &'static str {
+        "distance_term,fleet_cost_term,fairness_term,unserved_profit_term,served_requests,idle_vehicles,route_distances"
+    }
+
+    /// Comma separated fields; per-route distances are joined with ';'
+    pub fn csv_fields(&self) -> String {
+        let route_distances: Vec<String> = self.routes.iter()
+            .map(|r| format!("{:.2}", r.distance))
+            .collect();
+        format!(
+            "{:.6},{:.6},{:.6},{:.6},{},{},{}",
+            self.distance_term,
+            self.fleet_cost_term,
+            self.fairness_term,
+            self.unserved_profit_term,
+            self.served_requests,
+            self.idle_vehicles,
+            route_distances.join(";")
+        )
+    }
+}
+
+impl fmt::Display for ObjectiveBreakdown {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        writeln!(f, "Objective breakdown:")?;
+        writeln!(f, "  Distance term: {:.2}", self.distance_term)?;
+        if self.fleet_cost_term != 0.0 {
+            writeln!(f, "  Fleet cost term: {:.2}", self.fleet_cost_term)?;
+        }
+        writeln!(f, "  Fairness term: {:.2} (penalty {:.4})", self.fairness_term, self.fairness_penalty)?;
+        if self.unserved_profit_term != 0.0 {
+            writeln!(f, "  Unserved profit term: {:.2}", self.unserved_profit_term)?;
+        }
+        writeln!(f, "  Served requests: {}", self.served_requests)?;
+        writeln!(f, "  Idle vehicles: {}", self.idle_vehicles)?;
+
+        for (i, route) in self.routes.iter().enumerate() {
+            if route.is_idle() {
+                writeln!(f, "  Vehicle {}: idle, unfairness contribution {:.4}",
+                         i + 1, route.unfairness_contribution)?;
+                continue;
+            }
+            writeln!(
+                f,
+                "  Vehicle {}: distance {:.2}, served {}, max load {}, unfairness contribution {:.4}",
+                i + 1,
+                route.distance,
+                route.served_requests,
+                route.max_load,
+                route.unfairness_contribution
+            )?;
+            writeln!(f, "    Load profile: [{}]", route.load_profile.iter()
+                .map(|l| l.to_string())
+                .collect::<Vec<String>>()
+                .join(", "))?;
+        }
+
+        Ok(())
+    }
+}
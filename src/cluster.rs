@@ -0,0 +1,634 @@
+use std::f64::consts::PI;
+
+use crate::insertion::InsertionConstruction;
+use crate::instance::Point;
+use crate::savings::cheapest_standalone_requests;
+use crate::time_windows::TimeWindow;
+use crate::{Instance, Solution, Solver};
+
+/// How requests are grouped into one cluster per vehicle
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum ClusteringMethod {
+    /// Contiguous angular sectors around the depot
+    Sweep,
+    /// Balanced k-means, started from the sweep clusters
+    KMeans { iterations: usize },
+}
+
+impl ClusteringMethod {
+    /// Solver name used for result files
+    pub fn name(&self) -> &'static str {
+        match self {
+            ClusteringMethod::Sweep => "cluster_first_sweep",
+            ClusteringMethod::KMeans { .. } => "cluster_first_kmeans",
+        }
+    }
+}
+
+/// Clusters may exceed the even share of the weight by this fraction in k-means
+const BALANCE_SLACK: f64 = 0.1;
+
+/// Stops are only relocated this many positions away in the intra-route improvement
+const RELOCATE_WINDOW: usize = 25;
+
+/*
+Cluster-first route-second construction for large instances.
+Selects the gamma requests that are cheapest to serve on their own and
+partitions them into one cluster per vehicle, represented by the midpoint of
+pickup and dropoff. Clusters are balanced by a weight that counts demand and
+pickup-dropoff distance equally, so routes get similar lengths and the
+fairness penalty stays low. Each cluster is then sequenced with a nearest
+neighbour rule that only visits a dropoff after its pickup and never exceeds
+the capacity, followed by relocating single stops within the route. Requests
+their cluster's vehicle cannot serve are inserted at their cheapest feasible
+position in any route; requests that fit nowhere stay unserved, which shows as
+unmet coverage.
+
+All steps work on coordinates, so no distance matrix is built and runtime is
+about linear in the number of requests for a fixed cluster size.
+*/
+pub struct ClusterFirstConstruction<'a> {
+    instance: &'a Instance,
+    method: ClusteringMethod,
+    max_improvement_passes: usize,
+}
+
+impl<'a> ClusterFirstConstruction<'a> {
+    pub fn new(instance: &'a Instance, method: ClusteringMethod) -> Self {
+        Self { instance, method, max_improvement_passes: 10 }
+    }
+
+    /// Passes of the intra-route relocation; 0 disables it
+    pub fn with_max_improvement_passes(mut self, passes: usize) -> Self {
+        self.max_improvement_passes = passes;
+        self
+    }
+
+    fn pickup_node(&self, req_id: usize) -> usize {
+        req_id + 1
+    }
+
+    fn dropoff_node(&self, req_id: usize) -> usize {
+        req_id + 1 + self.instance.n_reqs()
+    }
+
+    /// Request of a pickup or dropoff node
+    fn request_of(&self, node: usize) -> usize {
+        if node <= self.instance.n_reqs() { node - 1 } else { node - self.instance.n_reqs() - 1 }
+    }
+
+    fn d(&self, u: usize, v: usize) -> f64 {
+        self.instance.distance(u, v) as f64
+    }
+
+    /// Distance between two stops of a vehicle's route, `None` being its start
+    /// depot before the first stop and its end depot after the last
+    fn leg(&self, vehicle_id: usize, from: Option<usize>, to: Option<usize>) -> f64 {
+        match (from, to) {
+            (Some(u), Some(v)) => self.d(u, v),
+            (None, Some(v)) => self.instance.start_distance(vehicle_id, v) as f64,
+            (Some(u), None) => self.instance.end_distance(u, vehicle_id) as f64,
+            (None, None) => 0.0,
+        }
+    }
+
+    /// Share of the weight each cluster should get, proportional to the
+    /// capacity of the vehicle serving it
+    fn cluster_shares(&self, n_clusters: usize) -> Vec<f64> {
+        let capacities: Vec<f64> = (0..n_clusters)
+            .map(|k| self.instance.vehicle(k).capacity as f64)
+            .collect();
+        let total: f64 = capacities.iter().sum();
+        if total > 0.0 {
+            capacities.iter().map(|c| c / total).collect()
+        } else {
+            vec![1.0 / n_clusters as f64; n_clusters]
+        }
+    }
+
+    fn midpoint(&self, req_id: usize) -> Point {
+        let pickup = self.instance.pickup_locations()[req_id];
+        let dropoff = self.instance.dropoff_locations()[req_id];
+        Point { x: (pickup.x + dropoff.x) / 2.0, y: (pickup.y + dropoff.y) / 2.0 }
+    }
+
+    /// Balancing weight of each request: share of total demand plus share of
+    /// total pickup-dropoff distance
+    fn weights(&self, requests: &[usize]) -> Vec<f64> {
+        let demands = self.instance.demands();
+        let total_demand: f64 = requests.iter().map(|&r| demands[r] as f64).sum();
+        let lengths: Vec<f64> = requests.iter()
+            .map(|&r| self.d(self.pickup_node(r), self.dropoff_node(r)))
+            .collect();
+        let total_length: f64 = lengths.iter().sum();
+
+        requests.iter()
+            .zip(&lengths)
+            .map(|(&r, &length)| {
+                let demand_share = if total_demand > 0.0 { demands[r] as f64 / total_demand } else { 0.0 };
+                let length_share = if total_length > 0.0 { length / total_length } else { 0.0 };
+                demand_share + length_share
+            })
+            .collect()
+    }
+
+    /// Cut the requests, sorted by angle around the depot starting after the
+    /// widest empty sector, into consecutive groups of about equal weight
+    fn sweep_clusters(&self, requests: &[usize], weights: &[f64], n_clusters: usize) -> Vec<usize> {
+        let depot = self.instance.depot();
+        let mut order: Vec<(f64, usize)> = requests.iter()
+            .enumerate()
+            .map(|(i, &r)| {
+                let m = self.midpoint(r);
+                ((m.y - depot.y).atan2(m.x - depot.x), i)
+            })
+            .collect();
+        order.sort_by(|a, b| a.0.total_cmp(&b.0));
+
+        // Start after the largest angular gap, so no cluster straddles it
+        let mut start = 0;
+        let mut widest_gap = 0.0;
+        for k in 0..order.len() {
+            let next = (k + 1) % order.len();
+            let mut gap = order[next].0 - order[k].0;
+            // Only the step from the last angle back to the first wraps around
+            if next <= k {
+                gap += 2.0 * PI;
+            }
+            if gap > widest_gap {
+                widest_gap = gap;
+                start = next;
+            }
+        }
+        order.rotate_left(start);
+
+        let total_weight: f64 = weights.iter().sum();
+        let shares = self.cluster_shares(n_clusters);
+        let mut assignment = vec![0; requests.len()];
+        let mut cluster = 0;
+        let mut cumulative = 0.0;
+        let mut threshold = total_weight * shares[0];
+        for (_, i) in order {
+            // Move on once this cluster holds its share of the weight so far
+            if cumulative >= threshold && cluster + 1 < n_clusters {
+                cluster += 1;
+                threshold += total_weight * shares[cluster];
+            }
+            assignment[i] = cluster;
+            cumulative += weights[i];
+        }
+        assignment
+    }
+
+    /// Balanced k-means on request midpoints: requests are assigned in order of
+    /// how much they prefer their nearest centroid, each to the nearest
+    /// centroid whose cluster still has room
+    fn kmeans_clusters(&self, requests: &[usize], weights: &[f64], n_clusters: usize, iterations: usize) -> Vec<usize> {
+        let points: Vec<Point> = requests.iter().map(|&r| self.midpoint(r)).collect();
+        let mut assignment = self.sweep_clusters(requests, weights, n_clusters);
+        let total_weight: f64 = weights.iter().sum();
+        let room: Vec<f64> = self.cluster_shares(n_clusters).iter()
+            .map(|share| total_weight * share * (1.0 + BALANCE_SLACK))
+            .collect();
+        let dist = |a: Point, b: Point| (a.x - b.x).hypot(a.y - b.y);
+
+        for _ in 0..iterations {
+            let mut sums = vec![(0.0, 0.0, 0usize); n_clusters];
+            for (i, &c) in assignment.iter().enumerate() {
+                sums[c].0 += points[i].x;
+                sums[c].1 += points[i].y;
+                sums[c].2 += 1;
+            }
+            let centroids: Vec<Option<Point>> = sums.iter()
+                .map(|&(x, y, count)| (count > 0).then(|| Point { x: x / count as f64, y: y / count as f64 }))
+                .collect();
+
+            // Requests with a clear favourite go first
+            let mut order: Vec<(f64, usize)> = points.iter()
+                .enumerate()
+                .map(|(i, &p)| {
+                    let mut distances: Vec<f64> = centroids.iter().flatten().map(|&c| dist(p, c)).collect();
+                    distances.sort_by(|a, b| a.total_cmp(b));
+                    let preference = match distances.as_slice() {
+                        [first, second, ..] => second - first,
+                        _ => 0.0,
+                    };
+                    (preference, i)
+                })
+                .collect();
+            order.sort_by(|a, b| b.0.total_cmp(&a.0));
+
+            let mut load = vec![0.0; n_clusters];
+            let mut new_assignment = vec![0; requests.len()];
+            for (_, i) in order {
+                let mut candidates: Vec<(f64, usize)> = centroids.iter()
+                    .enumerate()
+                    .filter_map(|(c, centroid)| centroid.map(|centroid| (dist(points[i], centroid), c)))
+                    .collect();
+                candidates.sort_by(|a, b| a.0.total_cmp(&b.0));
+                let cluster = candidates.iter()
+                    .find(|&&(_, c)| load[c] + weights[i] <= room[c])
+                    .or(candidates.first())
+                    .map(|&(_, c)| c)
+                    .unwrap_or(assignment[i]);
+                load[cluster] += weights[i];
+                new_assignment[i] = cluster;
+            }
+
+            if new_assignment == assignment {
+                break;
+            }
+            assignment = new_assignment;
+        }
+        assignment
+    }
+
+    /// Nearest neighbour over the stops that may come next: pickups that fit
+    /// into the vehicle and dropoffs of requests on board. With time windows
+    /// the stop that can be served first is taken, skipping stops whose window
+    /// would be missed. With a maximum ride time, a stop is only taken if all
+    /// requests on board can still be dropped off in time afterwards.
+    /// Returns the route and the requests the vehicle cannot serve.
+    fn sequence_cluster(&self, requests: &[usize], vehicle_id: usize) -> (Vec<usize>, Vec<usize>) {
+        let demands = self.instance.demands();
+        let window = |node: usize| {
+            self.instance.time_windows().map_or(TimeWindow::OPEN, |windows| windows.window(node))
+        };
+
+        let mut waiting: Vec<usize> = requests.to_vec();
+        let mut on_board: Vec<usize> = Vec::new();
+        // Distance driven since each request on board was picked up
+        let mut ride: Vec<f64> = Vec::new();
+        let max_ride = self.instance.max_ride_time().unwrap_or(f64::INFINITY);
+        let mut load = 0;
+        let mut current = None;
+        let mut time = self.instance.depot_window(vehicle_id).earliest;
+        let mut route = Vec::with_capacity(2 * requests.len());
+        // A rear-loaded vehicle can only unload the request on top, the last in `on_board`
+        let lifo = self.instance.vehicle(vehicle_id).lifo;
+        let unloadable = |i: usize, on_board: &[usize]| !lifo || i + 1 == on_board.len();
+
+        // Whether, after driving to `node`, all requests then on board can be
+        // dropped off within the maximum ride time, most urgent first
+        let keeps_rides = |current: Option<usize>, ride: &[f64], on_board: &[usize], node: usize| {
+            if max_ride.is_infinite() {
+                return true;
+            }
+            let leg = self.leg(vehicle_id, current, Some(node));
+            let mut pending: Vec<(usize, f64)> = on_board.iter()
+                .zip(ride)
+                .map(|(&r, &driven)| (self.dropoff_node(r), driven + leg))
+                .filter(|&(dropoff, _)| dropoff != node)
+                .collect();
+            if node <= self.instance.n_reqs() {
+                pending.push((node + self.instance.n_reqs(), 0.0));
+            }
+            let mut at = node;
+            while !pending.is_empty() {
+                let slack = |&(dropoff, driven): &(usize, f64)| max_ride - driven - self.d(at, dropoff);
+                let (i, _) = pending.iter()
+                    .enumerate()
+                    .min_by(|a, b| slack(a.1).total_cmp(&slack(b.1)))
+                    .expect("pending is not empty");
+                if slack(&pending[i]) < 0.0 {
+                    return false;
+                }
+                let (dropoff, _) = pending.swap_remove(i);
+                let step = self.d(at, dropoff);
+                for entry in pending.iter_mut() {
+                    entry.1 += step;
+                }
+                at = dropoff;
+            }
+            true
+        };
+
+        // Service start at `node` if it is reached in time
+        let start_at = |current: Option<usize>, time: f64, node: usize| {
+            let tw = window(node);
+            let arrival = time + self.leg(vehicle_id, current, Some(node));
+            (arrival <= tw.latest).then(|| arrival.max(tw.earliest))
+        };
+
+        while !waiting.is_empty() || !on_board.is_empty() {
+            let pickups = waiting.iter()
+                .enumerate()
+                .filter(|&(_, &r)| self.instance.within_capacity(vehicle_id, load + demands[r]))
+                .filter(|&(_, &r)| {
+                    keeps_rides(current, &ride, &on_board, self.pickup_node(r))
+                })
+                .filter_map(|(i, &r)| {
+                    let pickup = self.pickup_node(r);
+                    let start = start_at(current, time, pickup)?;
+                    // The dropoff must still be reachable directly afterwards
+                    start_at(Some(pickup), start + window(pickup).service, self.dropoff_node(r))?;
+                    Some((start, i, true))
+                });
+            let dropoffs = on_board.iter()
+                .enumerate()
+                .filter(|&(i, _)| unloadable(i, &on_board))
+                .filter(|&(_, &r)| keeps_rides(current, &ride, &on_board, self.dropoff_node(r)))
+                .filter_map(|(i, &r)| Some((start_at(current, time, self.dropoff_node(r))?, i, false)));
+            let next = pickups.chain(dropoffs).min_by(|a, b| a.0.total_cmp(&b.0));
+            let Some((start, i, is_pickup)) = next.or_else(|| {
+                // A late dropoff still has to happen, even if it misses its window
+                // or ride time: the one closest to its ride limit goes first
+                on_board.iter()
+                    .zip(&ride)
+                    .enumerate()
+                    .filter(|&(i, _)| unloadable(i, &on_board))
+                    .map(|(i, (&r, &driven))| {
+                        let leg = self.leg(vehicle_id, current, Some(self.dropoff_node(r)));
+                        (time + leg, driven + leg, i)
+                    })
+                    .max_by(|a, b| a.1.total_cmp(&b.1))
+                    .map(|(start, _, i)| (start, i, false))
+            }) else {
+                // Only requests larger than the capacity or out of reach in time are left
+                break;
+            };
+
+            let next_node = if is_pickup {
+                self.pickup_node(waiting[i])
+            } else {
+                self.dropoff_node(on_board[i])
+            };
+            let travelled = self.leg(vehicle_id, current, Some(next_node));
+            for driven in ride.iter_mut() {
+                *driven += travelled;
+            }
+
+            if is_pickup {
+                let r = waiting.swap_remove(i);
+                load += demands[r];
+                route.push(self.pickup_node(r));
+                on_board.push(r);
+                ride.push(0.0);
+            } else {
+                let r = on_board.remove(i);
+                ride.remove(i);
+                load -= demands[r];
+                route.push(self.dropoff_node(r));
+            }
+            current = route.last().copied();
+            time = start + window(route[route.len() - 1]).service;
+        }
+
+        (route, waiting)
+    }
+
+    /// Every dropoff follows its pickup, and the route meets the vehicle's
+    /// capacity, time windows and route limits
+    fn route_feasible(&self, vehicle_id: usize, route: &[usize]) -> bool {
+        let n_reqs = self.instance.n_reqs();
+        let mut picked_up = std::collections::HashSet::new();
+        for &node in route {
+            let r = self.request_of(node);
+            if node <= n_reqs {
+                picked_up.insert(r);
+            } else if !picked_up.contains(&r) {
+                return false;
+            }
+        }
+        self.instance.route_within_capacity(vehicle_id, route)
+            && self.instance.route_constraints_feasible(vehicle_id, route)
+    }
+
+    /// First-improvement relocation of single stops within `RELOCATE_WINDOW`
+    /// positions, keeping precedence and capacity
+    fn improve_route(&self, vehicle_id: usize, route: &mut Vec<usize>) {
+        let at = |route: &[usize], pos: isize| -> Option<usize> {
+            if pos < 0 { None } else { route.get(pos as usize).copied() }
+        };
+        let d = |u: Option<usize>, v: Option<usize>| self.leg(vehicle_id, u, v);
+
+        for _ in 0..self.max_improvement_passes {
+            let mut improved = false;
+            let mut i = 0;
+            while i < route.len() {
+                let node = route[i];
+                let (prev, next) = (at(route, i as isize - 1), at(route, i as isize + 1));
+                let removal_gain = d(prev, Some(node)) + d(Some(node), next) - d(prev, next);
+
+                let mut reduced = route.clone();
+                reduced.remove(i);
+                let lo = i.saturating_sub(RELOCATE_WINDOW);
+                let hi = (i + RELOCATE_WINDOW).min(reduced.len());
+
+                let mut moved = false;
+                for j in lo..=hi {
+                    if j == i {
+                        continue;
+                    }
+                    let (a, b) = (at(&reduced, j as isize - 1), at(&reduced, j as isize));
+                    let insertion_cost = d(a, Some(node)) + d(Some(node), b) - d(a, b);
+                    if insertion_cost - removal_gain < -1e-9 {
+                        let mut candidate = reduced.clone();
+                        candidate.insert(j, node);
+                        if self.route_feasible(vehicle_id, &candidate) {
+                            *route = candidate;
+                            moved = true;
+                            break;
+                        }
+                    }
+                }
+
+                if moved {
+                    improved = true;
+                } else {
+                    i += 1;
+                }
+            }
+            if !improved {
+                break;
+            }
+        }
+    }
+
+    /// Insert requests one by one at their cheapest feasible position in any
+    /// route; returns the requests that fit nowhere
+    fn insert_leftover(&self, routes: &mut [Vec<usize>], requests: &[usize]) -> Vec<usize> {
+        let insertion = InsertionConstruction::cheapest(self.instance);
+        let mut unserved = Vec::new();
+        for &req_id in requests {
+            let best = routes.iter()
+                .enumerate()
+                .filter_map(|(k, route)| {
+                    let ins = insertion.best_insertion(route, k, req_id, 0)?;
+                    let vehicle = self.instance.vehicle(k);
+                    Some((vehicle.route_cost(ins.delta, route.is_empty()), k, ins))
+                })
+                .min_by(|a, b| a.0.total_cmp(&b.0));
+            match best {
+                Some((_, k, ins)) => {
+                    routes[k].insert(ins.dropoff_pos, self.dropoff_node(req_id));
+                    routes[k].insert(ins.pickup_pos, self.pickup_node(req_id));
+                }
+                None => unserved.push(req_id),
+            }
+        }
+        unserved
+    }
+
+    /// With vehicles starting from different depots, hand every cluster to a
+    /// vehicle near its centroid. Cluster `k` was sized for vehicle `k`, so
+    /// clusters only move between vehicles of equal capacity.
+    fn match_clusters_to_vehicles(&self, clusters: Vec<Vec<usize>>) -> Vec<Vec<usize>> {
+        let n_vehicles = clusters.len();
+        let first_start = self.instance.start_location(0);
+        if (0..n_vehicles).all(|k| self.instance.start_location(k) == first_start) {
+            return clusters;
+        }
+
+        let dist = |a: Point, b: Point| (a.x - b.x).hypot(a.y - b.y);
+        let centroid = |cluster: &[usize]| {
+            let points: Vec<Point> = cluster.iter().map(|&r| self.midpoint(r)).collect();
+            let n = points.len().max(1) as f64;
+            Point {
+                x: points.iter().map(|p| p.x).sum::<f64>() / n,
+                y: points.iter().map(|p| p.y).sum::<f64>() / n,
+            }
+        };
+
+        let mut pairs: Vec<(f64, usize, usize)> = Vec::new();
+        for (c, cluster) in clusters.iter().enumerate() {
+            if cluster.is_empty() {
+                continue;
+            }
+            let center = centroid(cluster);
+            let capacity = self.instance.vehicle(c).capacity;
+            for k in (0..n_vehicles).filter(|&k| self.instance.vehicle(k).capacity == capacity) {
+                pairs.push((dist(center, self.instance.start_location(k)), c, k));
+            }
+        }
+        pairs.sort_by(|a, b| a.0.total_cmp(&b.0).then(a.1.cmp(&b.1)).then(a.2.cmp(&b.2)));
+
+        let mut matched: Vec<Option<Vec<usize>>> = vec![None; n_vehicles];
+        let mut clusters: Vec<Option<Vec<usize>>> = clusters.into_iter().map(Some).collect();
+        for (_, c, k) in pairs {
+            if matched[k].is_none() && clusters[c].is_some() {
+                matched[k] = clusters[c].take();
+            }
+        }
+        // Clusters left over when a capacity group has more clusters than free vehicles
+        let mut leftovers = clusters.into_iter().flatten();
+        matched.into_iter()
+            .map(|cluster| cluster.or_else(|| leftovers.next()).unwrap_or_default())
+            .collect()
+    }
+
+    fn construct_solution(&self) -> Solution {
+        let n_vehicles = self.instance.n_vehicles();
+        let requests = cheapest_standalone_requests(self.instance);
+        let mut routes = vec![Vec::new(); n_vehicles];
+        if requests.is_empty() || n_vehicles == 0 {
+            return Solution::new(self.instance.clone(), routes);
+        }
+
+        let weights = self.weights(&requests);
+        let assignment = match self.method {
+            ClusteringMethod::Sweep => self.sweep_clusters(&requests, &weights, n_vehicles),
+            ClusteringMethod::KMeans { iterations } => {
+                self.kmeans_clusters(&requests, &weights, n_vehicles, iterations)
+            }
+        };
+
+        let mut clusters = vec![Vec::new(); n_vehicles];
+        for (&r, &c) in requests.iter().zip(&assignment) {
+            clusters[c].push(r);
+        }
+        let clusters = self.match_clusters_to_vehicles(clusters);
+
+        let mut leftover = Vec::new();
+        for (vehicle_id, (route, cluster)) in routes.iter_mut().zip(&clusters).enumerate() {
+            let (sequenced, unserved) = self.sequence_cluster(cluster, vehicle_id);
+            *route = sequenced;
+            self.improve_route(vehicle_id, route);
+            leftover.extend(unserved);
+        }
+
+        let unserved = self.insert_leftover(&mut routes, &leftover);
+        if !unserved.is_empty() {
+            eprintln!(
+                "Cluster-first construction: {} of {} selected requests fit into no route",
+                unserved.len(),
+                requests.len()
+            );
+        }
+
+        Solution::new(self.instance.clone(), routes)
+    }
+}
+
+impl<'a> Solver for ClusterFirstConstruction<'a> {
+    fn solve(&self) -> Solution {
+        self.construct_solution()
+    }
+
+    fn config_description(&self) -> String {
+        format!("{:?} max_improvement_passes={}", self.method, self.max_improvement_passes)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::instance::tests::from_text;
+
+    const METHODS: [ClusteringMethod; 2] = [ClusteringMethod::Sweep, ClusteringMethod::KMeans { iterations: 10 }];
+
+    #[test]
+    fn serves_gamma_requests_with_one_route_per_vehicle() {
+        // Two requests east and two north of the depot
+        let instance = from_text("cluster", "\
+4 2 10 4 0
+# demands
+1 1 1 1
+# request locations
+0 0
+10 0
+11 0
+0 10
+0 11
+12 0
+13 0
+0 12
+0 13
+");
+        for method in METHODS {
+            let solution = ClusterFirstConstruction::new(&instance, method).solve();
+            assert!(solution.is_valid(), "{:?}", method);
+            let mut east = solution.routes.iter().find(|route| route.contains(&1)).unwrap().clone();
+            east.sort();
+            assert_eq!(east, vec![1, 2, 5, 6], "{:?}", method);
+        }
+    }
+
+    #[test]
+    fn requests_the_cluster_vehicle_cannot_carry_go_to_another_route() {
+        // The large request 0 is swept into the cluster of the small vehicle 0
+        let instance = from_text("cluster_leftover", "\
+2 2 2 2 0
+# demands
+2 1
+# request locations
+0 0
+10 0
+0 10
+12 0
+0 12
+# fleet
+1 0 1
+2 0 1
+");
+        let construction = ClusterFirstConstruction::new(&instance, ClusteringMethod::Sweep);
+        let requests = cheapest_standalone_requests(&instance);
+        let assignment = construction.sweep_clusters(&requests, &construction.weights(&requests), 2);
+        assert_eq!(assignment[requests.iter().position(|&r| r == 0).unwrap()], 0);
+        assert_eq!(construction.sequence_cluster(&[0], 0), (vec![], vec![0]));
+
+        let solution = construction.solve();
+        assert!(solution.is_valid());
+        assert!(solution.routes[0].is_empty());
+    }
+}
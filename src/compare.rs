@@ -0,0 +1,315 @@
+use std::collections::{BTreeMap, HashMap};
+use std::fmt;
+
+use crate::results::ResultRow;
+use crate::statistics::{friedman_test, nemenyi_critical_difference, wilcoxon_signed_rank, FriedmanResult, Summary, WilcoxonResult};
+
+/// Relative tolerance under which two objective values count as a tie
+const TIE_TOLERANCE: f64 = 1e-6;
+
+/// Per-algorithm part of a `ComparisonReport`
+#[derive(Debug, Clone, PartialEq)]
+pub struct AlgorithmSummary {
+    pub algorithm: String,
+    /// Mean objective per instance, after averaging over seeds
+    pub objectives: Vec<f64>,
+    /// Relative percentage deviation from the best known value per instance
+    pub rpd: Vec<f64>,
+    /// Number of runs (rows) aggregated over all instances
+    pub runs: usize,
+    /// Runs left out because their solution is invalid
+    pub invalid_runs: usize,
+}
+
+impl AlgorithmSummary {
+    pub fn mean_rpd(&self) -> f64 {
+        Summary::of(&self.rpd).map(|s| s.mean).unwrap_or(0.0)
+    }
+}
+
+/// Pairwise comparison of two algorithms on the common instances
+#[derive(Debug, Clone, PartialEq)]
+pub struct PairwiseComparison {
+    pub first: String,
+    pub second: String,
+    /// Instances where `first` is better, equal, or worse than `second`
+    pub wins: usize,
+    pub ties: usize,
+    pub losses: usize,
+    pub wilcoxon: Option<WilcoxonResult>,
+    /// Whether the mean rank difference exceeds the Nemenyi critical difference
+    pub nemenyi_significant: Option<bool>,
+}
+
+/// Statistical comparison of two or more algorithms over the same instances
+#[derive(Debug, Clone, PartialEq)]
+pub struct ComparisonReport {
+    pub instances: Vec<String>,
+    /// Reference value per instance used for the RPD
+    pub best_known: Vec<f64>,
+    pub algorithms: Vec<AlgorithmSummary>,
+    pub pairwise: Vec<PairwiseComparison>,
+    /// Only computed for three or more algorithms
+    pub friedman: Option<FriedmanResult>,
+    pub critical_difference: Option<f64>,
+}
+
+impl ComparisonReport {
+    /// Compare named result sets. Rows whose `valid` column is false are left
+    /// out and counted per algorithm. Rows of the same instance (e.g. several
+    /// seeds) are averaged; only instances present for every algorithm are
+    /// used. The best known value per instance is taken from `best_known`
+    /// when given, otherwise it is the best mean among the compared algorithms.
+    pub fn new(
+        results: &[(String, Vec<ResultRow>)],
+        best_known: Option<&HashMap<String, f64>>,
+    ) -> Result<Self, Box<dyn std::error::Error>> {
+        if results.len() < 2 {
+            return Err("At least two algorithms are needed for a comparison".into());
+        }
+
+        // Aggregate seeds: instance -> mean objective, and run counts
+        let aggregated: Vec<(BTreeMap<String, f64>, usize)> = results.iter()
+            .map(|(_, rows)| {
+                let mut per_instance: BTreeMap<String, Vec<f64>> = BTreeMap::new();
+                let valid_rows: Vec<&ResultRow> = rows.iter().filter(|row| is_valid(row)).collect();
+                for row in &valid_rows {
+                    per_instance.entry(row.instance_name.clone())
+                        .or_default()
+                        .push(row.objective_value);
+                }
+                let means = per_instance.into_iter()
+                    .map(|(name, values)| {
+                        let mean = values.iter().sum::<f64>() / values.len() as f64;
+                        (name, mean)
+                    })
+                    .collect();
+                (means, valid_rows.len())
+            })
+            .collect();
+
+        let instances: Vec<String> = aggregated[0].0.keys()
+            .filter(|name| aggregated.iter().all(|(means, _)| means.contains_key(*name)))
+            .cloned()
+            .collect();
+        if instances.is_empty() {
+            return Err("The algorithms have no instances in common".into());
+        }
+
+        let objectives: Vec<Vec<f64>> = aggregated.iter()
+            .map(|(means, _)| instances.iter().map(|name| means[name]).collect())
+            .collect();
+
+        let best_known: Vec<f64> = instances.iter()
+            .enumerate()
+            .map(|(i, name)| {
+                let best_compared = objectives.iter()
+                    .map(|values| values[i])
+                    .fold(f64::INFINITY, f64::min);
+                match best_known.and_then(|bks| bks.get(name)) {
+                    Some(&value) => value.min(best_compared),
+                    None => best_compared,
+                }
+            })
+            .collect();
+
+        let algorithms: Vec<AlgorithmSummary> = results.iter()
+            .zip(&objectives)
+            .zip(&aggregated)
+            .map(|(((name, rows), values), (_, runs))| AlgorithmSummary {
+                algorithm: name.clone(),
+                rpd: values.iter()
+                    .zip(&best_known)
+                    .map(|(v, b)| relative_percentage_deviation(*v, *b))
+                    .collect(),
+                objectives: values.clone(),
+                runs: *runs,
+                invalid_runs: rows.len() - runs,
+            })
+            .collect();
+
+        let k = algorithms.len();
+        let (friedman, critical_difference) = if k >= 3 {
+            let data: Vec<Vec<f64>> = (0..instances.len())
+                .map(|i| objectives.iter().map(|values| values[i]).collect())
+                .collect();
+            (friedman_test(&data), nemenyi_critical_difference(k, instances.len()))
+        } else {
+            (None, None)
+        };
+
+        let mut pairwise = Vec::new();
+        for a in 0..k {
+            for b in a + 1..k {
+                let (mut wins, mut ties, mut losses) = (0, 0, 0);
+                for (x, y) in objectives[a].iter().zip(&objectives[b]) {
+                    let scale = x.abs().max(y.abs()).max(1.0);
+                    if (x - y).abs() <= TIE_TOLERANCE * scale {
+                        ties += 1;
+                    } else if x < y {
+                        wins += 1;
+                    } else {
+                        losses += 1;
+                    }
+                }
+
+                let nemenyi_significant = match (&friedman, critical_difference) {
+                    (Some(f), Some(cd)) => Some((f.mean_ranks[a] - f.mean_ranks[b]).abs() > cd),
+                    _ => None,
+                };
+
+                pairwise.push(PairwiseComparison {
+                    first: algorithms[a].algorithm.clone(),
+                    second: algorithms[b].algorithm.clone(),
+                    wins,
+                    ties,
+                    losses,
+                    wilcoxon: wilcoxon_signed_rank(&objectives[a], &objectives[b]),
+                    nemenyi_significant,
+                });
+            }
+        }
+
+        Ok(Self { instances, best_known, algorithms, pairwise, friedman, critical_difference })
+    }
+
+    /// One row per algorithm: mean objective, RPD statistics and mean rank
+    pub fn to_csv(&self) -> String {
+        let mut lines = vec!["algorithm,instances,runs,invalid_runs,mean_objective,mean_rpd,median_rpd,max_rpd,mean_rank".to_string()];
+        for (j, algorithm) in self.algorithms.iter().enumerate() {
+            let objective = Summary::of(&algorithm.objectives);
+            let rpd = Summary::of(&algorithm.rpd);
+            let mean_rank = self.friedman.as_ref()
+                .map(|f| format!("{:.4}", f.mean_ranks[j]))
+                .unwrap_or_default();
+            lines.push(format!(
+                "{},{},{},{},{:.6},{:.6},{:.6},{:.6},{}",
+                algorithm.algorithm,
+                self.instances.len(),
+                algorithm.runs,
+                algorithm.invalid_runs,
+                objective.map(|s| s.mean).unwrap_or(0.0),
+                rpd.map(|s| s.mean).unwrap_or(0.0),
+                rpd.map(|s| s.median).unwrap_or(0.0),
+                rpd.map(|s| s.max).unwrap_or(0.0),
+                mean_rank
+            ));
+        }
+        lines.join("\n") + "\n"
+    }
+}
+
+/// Rows without a `valid` column count as valid
+fn is_valid(row: &ResultRow) -> bool {
+    row.field("valid") != Some("false")
+}
+
+/// 100 * (value - best) / |best|, zero when the best value is zero
+pub fn relative_percentage_deviation(value: f64, best: f64) -> f64 {
+    if best.abs() < 1e-12 {
+        0.0
+    } else {
+        100.0 * (value - best) / best.abs()
+    }
+}
+
+impl fmt::Display for ComparisonReport {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        writeln!(f, "Comparison over {} common instances", self.instances.len())?;
+        writeln!(f)?;
+        writeln!(f, "{:<24} {:>6} {:>14} {:>10} {:>10} {:>10}", "Algorithm", "Runs", "Mean obj", "Mean RPD", "Max RPD", "Mean rank")?;
+        for (j, algorithm) in self.algorithms.iter().enumerate() {
+            let objective = Summary::of(&algorithm.objectives).map(|s| s.mean).unwrap_or(0.0);
+            let max_rpd = Summary::of(&algorithm.rpd).map(|s| s.max).unwrap_or(0.0);
+            let mean_rank = self.friedman.as_ref()
+                .map(|fr| format!("{:.3}", fr.mean_ranks[j]))
+                .unwrap_or_else(|| "-".to_string());
+            writeln!(
+                f,
+                "{:<24} {:>6} {:>14.2} {:>9.3}% {:>9.3}% {:>10}",
+                algorithm.algorithm, algorithm.runs, objective, algorithm.mean_rpd(), max_rpd, mean_rank
+            )?;
+        }
+        for algorithm in self.algorithms.iter().filter(|a| a.invalid_runs > 0) {
+            writeln!(f, "{}: {} invalid runs left out", algorithm.algorithm, algorithm.invalid_runs)?;
+        }
+
+        if let Some(friedman) = &self.friedman {
+            writeln!(f)?;
+            writeln!(
+                f,
+                "Friedman: chi² = {:.3}, df = {}, p = {:.4}",
+                friedman.chi_square, friedman.degrees_of_freedom, friedman.p_value
+            )?;
+            match self.critical_difference {
+                Some(cd) => writeln!(f, "Nemenyi critical difference (alpha = 0.05): {:.3}", cd)?,
+                None => writeln!(f, "Nemenyi critical difference not available for this many algorithms")?,
+            }
+        }
+
+        writeln!(f)?;
+        writeln!(f, "Pairwise (W/T/L from the first algorithm's view):")?;
+        for pair in &self.pairwise {
+            let wilcoxon = match &pair.wilcoxon {
+                Some(w) => format!("Wilcoxon z = {:.3}, p = {:.4}", w.z, w.p_value),
+                None => "Wilcoxon: identical results".to_string(),
+            };
+            let nemenyi = match pair.nemenyi_significant {
+                Some(true) => ", Nemenyi: significant",
+                Some(false) => ", Nemenyi: not significant",
+                None => "",
+            };
+            writeln!(
+                f,
+                "  {} vs {}: {}/{}/{}, {}{}",
+                pair.first, pair.second, pair.wins, pair.ties, pair.losses, wilcoxon, nemenyi
+            )?;
+        }
+
+        Ok(())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn row(instance: &str, objective: f64, valid: bool) -> ResultRow {
+        ResultRow {
+            instance_name: instance.to_string(),
+            time_seconds: 1.0,
+            objective_value: objective,
+            jain_fairness: 1.0,
+            fairness_measure: "jain".to_string(),
+            fields: HashMap::from([("valid".to_string(), valid.to_string())]),
+        }
+    }
+
+    #[test]
+    fn invalid_runs_are_left_out_and_counted() {
+        let results = vec![
+            // The invalid run of a would otherwise make it the best
+            ("first".to_string(), vec![row("a", 10.0, true), row("a", 1.0, false), row("b", 20.0, true)]),
+            ("second".to_string(), vec![row("a", 12.0, true), row("b", 18.0, true)]),
+        ];
+        let report = ComparisonReport::new(&results, None).unwrap();
+
+        assert_eq!(report.algorithms[0].objectives, vec![10.0, 20.0]);
+        assert_eq!((report.algorithms[0].runs, report.algorithms[0].invalid_runs), (2, 1));
+        assert_eq!(report.algorithms[1].invalid_runs, 0);
+        assert_eq!(report.best_known, vec![10.0, 18.0]);
+        assert_eq!((report.pairwise[0].wins, report.pairwise[0].losses), (1, 1));
+        assert!(report.to_string().contains("first: 1 invalid runs left out"));
+        assert!(report.to_csv().lines().nth(1).unwrap().starts_with("first,2,2,1,"));
+    }
+
+    #[test]
+    fn instances_without_valid_runs_are_not_compared() {
+        let results = vec![
+            ("first".to_string(), vec![row("a", 10.0, true), row("b", 1.0, false)]),
+            ("second".to_string(), vec![row("a", 12.0, true), row("b", 18.0, true)]),
+        ];
+        let report = ComparisonReport::new(&results, None).unwrap();
+        assert_eq!(report.instances, vec!["a".to_string()]);
+    }
+}
@@ -1,3 +1,4 @@
+use crate::savings::standalone_distance;
 use crate::{Instance, Solution, Solver};
 use std::collections::HashMap;
 
@@ -11,15 +12,12 @@ impl<'a> DeterministicConstruction<'a> {
     }
 
     /* 
-    Simple construction heuristic: serve first gamma requests
+    Simple construction heuristic: serve the first requests until gamma, or the minimum profit, is covered
     */
     fn construct_solution(&self) -> Solution {
         let n_reqs = self.instance.n_reqs();
-        let gamma = self.instance.gamma();
         let n_vehicles = self.instance.n_vehicles();
-        let capacity = self.instance.cap();
         let demands = self.instance.demands();
-        let dist_matrix = self.instance.compute_distance_matrix();
 
         // Initialize empty routes (will add depot at start/end later)
         let mut routes: Vec<Vec<usize>> = vec![Vec::new(); n_vehicles];
@@ -33,47 +31,18 @@ impl<'a> DeterministicConstruction<'a> {
         let pickup_index = |req_id: usize| -> usize { 1 + req_id };
         let dropoff_index = |req_id: usize| -> usize { 1 + n_reqs + req_id };
 
-        // Helper to compute complete route distance including depot start/end
-        let compute_route_distance = |route: &[usize]| -> f64 {
-            if route.is_empty() {
-                return 0.0;
-            }
-            
-            let mut distance = 0.0;
-            
-            // From depot to first stop
-            distance += dist_matrix[0][route[0]] as f64;
-            
-            // Between stops
-            for i in 0..route.len() - 1 {
-                distance += dist_matrix[route[i]][route[i + 1]] as f64;
-            }
-            
-            // From last stop back to depot
-            distance += dist_matrix[route[route.len() - 1]][0] as f64;
-            
-            distance
-        };
-
-        // Compute Jain fairness for current routes
-        let compute_fairness = |routes: &[Vec<usize>]| -> f64 {
+        // Compute the fairness penalty (without rho) for current routes
+        let fairness_measure = self.instance.fairness_measure();
+        let compute_fairness_penalty = |routes: &[Vec<usize>]| -> f64 {
             let distances: Vec<f64> = routes.iter()
-                .map(|route| compute_route_distance(route))
+                .enumerate()
+                .map(|(k, route)| self.instance.route_distance(k, route))
                 .collect();
-                
-            let sum: f64 = distances.iter().sum();
-            let sum_sq: f64 = distances.iter().map(|d| d * d).sum();
-            let k = distances.len() as f64;
-
-            if sum_sq == 0.0 {
-                1.0
-            } else {
-                (sum * sum) / (k * sum_sq)
-            }
+            fairness_measure.penalty(&distances)
         };
 
-        // Try to assign each of the first gamma requests
-        for req_id in 0..gamma.min(n_reqs) {
+        // Try to assign each of the first requests needed to cover the requirement
+        for req_id in self.instance.requests_to_cover(0..n_reqs) {
             let demand = demands[req_id];
             let pickup = pickup_index(req_id);
             let dropoff = dropoff_index(req_id);
@@ -84,7 +53,7 @@ impl<'a> DeterministicConstruction<'a> {
             // Try each vehicle
             for k in 0..n_vehicles {
                 // Check capacity constraint
-                if loads[k] + demand > capacity {
+                if !self.instance.within_capacity(k, loads[k] + demand) {
                     continue;
                 }
 
@@ -92,27 +61,32 @@ impl<'a> DeterministicConstruction<'a> {
                 let mut test_route = routes[k].clone();
                 test_route.push(pickup);
                 test_route.push(dropoff);
+                if !self.instance.route_constraints_feasible(k, &test_route) {
+                    continue;
+                }
 
                 // Compute new distances for all vehicles
                 let mut test_routes = routes.clone();
                 test_routes[k] = test_route;
 
-                let current_fairness = compute_fairness(&routes);
-                let new_fairness = compute_fairness(&test_routes);
+                let current_penalty = compute_fairness_penalty(&routes);
+                let new_penalty = compute_fairness_penalty(&test_routes);
                 
                 let current_distance: f64 = routes.iter()
-                    .map(|r| compute_route_distance(r))
+                    .enumerate()
+                    .map(|(k, r)| self.instance.route_distance(k, r))
                     .sum();
                 let new_distance: f64 = test_routes.iter()
-                    .map(|r| compute_route_distance(r))
+                    .enumerate()
+                    .map(|(k, r)| self.instance.route_distance(k, r))
                     .sum();
                 
                 let delta_distance = new_distance - current_distance;
-                let delta_fairness = new_fairness - current_fairness;
+                let delta_penalty = new_penalty - current_penalty;
                 
-                // Objective: minimize total_distance + rho * (1 - fairness)
-                // So we want to minimize: delta_distance - rho * delta_fairness
-                let score = delta_distance - self.instance.rho() * delta_fairness;
+                // Objective: minimize total_distance + rho * penalty
+                // So we want to minimize: delta_distance + rho * delta_penalty
+                let score = delta_distance + self.instance.rho() * delta_penalty;
 
                 if score < best_score {
                     best_score = score;
@@ -158,9 +132,7 @@ impl<'a> DeterministicConstruction<'a> {
      */
     pub fn utility_based_construction(&self) -> Solution {
         let n_reqs = self.instance.n_reqs();
-        let gamma = self.instance.gamma().min(n_reqs);
         let n_vehicles = self.instance.n_vehicles();
-        let capacity = self.instance.cap();
         let demands = self.instance.demands();
         
         let utility_map = self.compute_utility();
@@ -169,12 +141,10 @@ impl<'a> DeterministicConstruction<'a> {
         let mut requests_with_utility: Vec<(usize, f64)> = utility_map.into_iter().collect();
         requests_with_utility.sort_by(|a, b| b.1.partial_cmp(&a.1).unwrap_or(std::cmp::Ordering::Equal));
         
-        // Take top gamma requests
-        let top_gamma_reqs: Vec<usize> = requests_with_utility
-            .into_iter()
-            .take(gamma)
-            .map(|(req_id, _)| req_id)
-            .collect();
+        // Take the top requests until gamma, or the minimum profit, is covered
+        let top_gamma_reqs: Vec<usize> = self.instance.requests_to_cover(
+            requests_with_utility.into_iter().map(|(req_id, _)| req_id)
+        );
         
         // Initialize routes and loads
         let mut routes: Vec<Vec<usize>> = vec![Vec::new(); n_vehicles];
@@ -190,7 +160,7 @@ impl<'a> DeterministicConstruction<'a> {
             let demand = demands[req_id];
             
             // Check capacity constraint
-            if loads[vehicle] + demand <= capacity {
+            if self.instance.within_capacity(vehicle, loads[vehicle] + demand) {
                 routes[vehicle].push(pickup_index(req_id));
                 routes[vehicle].push(dropoff_index(req_id));
                 loads[vehicle] += demand;
@@ -198,7 +168,7 @@ impl<'a> DeterministicConstruction<'a> {
                 // If capacity exceeded, try to find another vehicle with capacity
                 let mut assigned = false;
                 for v in 0..n_vehicles {
-                    if loads[v] + demand <= capacity {
+                    if self.instance.within_capacity(v, loads[v] + demand) {
                         routes[v].push(pickup_index(req_id));
                         routes[v].push(dropoff_index(req_id));
                         loads[v] += demand;
@@ -219,31 +189,30 @@ impl<'a> DeterministicConstruction<'a> {
 
     /*
     Associates each request with a utility score, defined as follows:
-    u_i = (c_i)/ (distance depot-pickup + pickup-dropoff-distance + distance drop_off-depot
-    That way we bias in favor of routes closer to the depot
+    u_i = (c_i)/ (distance start-pickup + pickup-dropoff-distance + distance drop_off-end
+    for the vehicle with the shortest such route, so requests close to a depot are preferred
+    With request profits, the profit p_i takes the place of the demand c_i
      */
     fn compute_utility(&self) -> HashMap<usize, f64> {
         let demands = self.instance.demands();
-        let dist_matrix = self.instance.compute_distance_matrix();
         let n_reqs = self.instance.n_reqs();
         
         let mut utility_scores = HashMap::new();
         
         for request_id in 0..n_reqs {
-            let pickup_idx = request_id + 1;
-            let dropoff_idx = request_id + 1 + n_reqs;
-            
-            let depot_to_pickup = dist_matrix[0][pickup_idx] as f64;
-            let pickup_to_dropoff = dist_matrix[pickup_idx][dropoff_idx] as f64;
-            let dropoff_to_depot = dist_matrix[dropoff_idx][0] as f64;
-            
-            let total_distance = depot_to_pickup + pickup_to_dropoff + dropoff_to_depot;
+            let total_distance = (0..self.instance.n_vehicles())
+                .map(|k| standalone_distance(self.instance, k, request_id))
+                .fold(f64::INFINITY, f64::min);
             
+            let value = match self.instance.profits() {
+                Some(profits) => profits[request_id],
+                None => demands[request_id] as f64,
+            };
             // Avoid division by zero
             let utility = if total_distance > 0.0 {
-                demands[request_id] as f64 / total_distance
+                value / total_distance
             } else {
-                demands[request_id] as f64
+                value
             };
             
             utility_scores.insert(request_id, utility);
@@ -0,0 +1,465 @@
+use std::fmt;
+use std::time::{Duration, Instant};
+
+use crate::insertion::{Insertion, InsertionConstruction};
+use crate::time_windows::TimeWindow;
+use crate::{Instance, Solution};
+
+/// Configuration of the online router
+#[derive(Debug, Clone)]
+pub struct OnlineConfig {
+    /// Time allowed for inserting one request and re-optimising afterwards
+    pub latency_budget_ms: u64,
+}
+
+impl Default for OnlineConfig {
+    fn default() -> Self {
+        Self { latency_budget_ms: 50 }
+    }
+}
+
+/*
+Online routing: requests are revealed one at a time while the vehicles drive.
+Every route has a committed prefix, the stops whose service has started by the
+current time; only the stops after it can change. A revealed request is
+inserted at its cheapest feasible position after the committed prefixes, then
+the uncommitted requests are relocated between routes until no move improves
+the objective or the latency budget is used up.
+
+Committed stops keep the times they were served at. The remaining stops are
+timed from the last committed stop, leaving no earlier than the current time,
+so a stop can be inserted right before the stop a vehicle is heading to. No
+pickup starts before its request is released, and time windows are checked
+against this schedule.
+*/
+pub struct OnlineRouter<'a> {
+    instance: &'a Instance,
+    config: OnlineConfig,
+    insertion: InsertionConstruction<'a>,
+    routes: Vec<Vec<usize>>,
+    /// Number of stops of each route that can no longer change
+    committed: Vec<usize>,
+    /// Service start of each committed stop
+    started: Vec<Vec<f64>>,
+    time: f64,
+}
+
+impl<'a> OnlineRouter<'a> {
+    /// Empty routes at the time the depot opens
+    pub fn new(instance: &'a Instance, config: OnlineConfig) -> Self {
+        let start = instance.time_windows().map_or(0.0, |windows| windows.depot.earliest);
+        Self {
+            instance,
+            config,
+            insertion: InsertionConstruction::cheapest(instance),
+            routes: vec![Vec::new(); instance.n_vehicles()],
+            committed: vec![0; instance.n_vehicles()],
+            started: vec![Vec::new(); instance.n_vehicles()],
+            time: start,
+        }
+    }
+
+    /// Continue from an existing solution at `time`: the stops served by then are committed
+    pub fn from_solution(instance: &'a Instance, solution: &Solution, time: f64, config: OnlineConfig) -> Self {
+        let mut router = Self::new(instance, config);
+        router.routes = solution.routes.clone();
+        router.routes.resize(instance.n_vehicles(), Vec::new());
+        router.advance_to(time);
+        router
+    }
+
+    pub fn time(&self) -> f64 {
+        self.time
+    }
+
+    /// Length of the committed prefix of every route
+    pub fn committed(&self) -> &[usize] {
+        &self.committed
+    }
+
+    pub fn solution(&self) -> Solution {
+        Solution::new(self.instance.clone(), self.routes.clone())
+    }
+
+    /// Move the clock forward, committing every stop whose service has started
+    /// by then under the schedule planned so far
+    pub fn advance_to(&mut self, time: f64) {
+        let time = self.time.max(time);
+        for vehicle_id in 0..self.routes.len() {
+            // A route that misses its time windows keeps its committed prefix
+            let Some(schedule) = self.schedule(vehicle_id, &self.routes[vehicle_id]) else {
+                continue;
+            };
+            let started = schedule.iter().take_while(|&&start| start <= time).count();
+            let committed = self.committed[vehicle_id];
+            if started > committed {
+                self.started[vehicle_id].extend(&schedule[committed..started]);
+                self.committed[vehicle_id] = started;
+            }
+        }
+        self.time = time;
+    }
+
+    /// Service start of every stop of a vehicle's route whose committed prefix
+    /// is unchanged: committed stops keep their times, the others follow the
+    /// last committed stop no earlier than the current time, and pickups wait
+    /// for their release. None if a time window is missed.
+    fn schedule(&self, vehicle_id: usize, route: &[usize]) -> Option<Vec<f64>> {
+        let n_reqs = self.instance.n_reqs();
+        let window = |node: usize| {
+            self.instance.time_windows().map_or(TimeWindow::OPEN, |windows| windows.window(node))
+        };
+        let depot = self.instance.depot_window(vehicle_id);
+
+        let committed = self.committed[vehicle_id];
+        let mut schedule = self.started[vehicle_id].clone();
+        let (mut time, mut prev) = match committed.checked_sub(1) {
+            Some(last) => (schedule[last] + window(route[last]).service, Some(route[last])),
+            None => (depot.earliest, None),
+        };
+        time = time.max(self.time);
+
+        for &node in &route[committed..] {
+            let travel = match prev {
+                Some(prev) => self.instance.distance(prev, node),
+                None => self.instance.start_distance(vehicle_id, node),
+            };
+            let tw = window(node);
+            let mut start = (time + travel as f64).max(tw.earliest);
+            if node >= 1 && node <= n_reqs {
+                start = start.max(self.instance.release_time(node - 1));
+            }
+            if start > tw.latest {
+                return None;
+            }
+            schedule.push(start);
+            time = start + tw.service;
+            prev = Some(node);
+        }
+        if let Some(last) = prev {
+            if time + self.instance.end_distance(last, vehicle_id) as f64 > depot.latest {
+                return None;
+            }
+        }
+        Some(schedule)
+    }
+
+    /// Whether the route can still be driven from the current state of the vehicle
+    fn route_feasible(&self, vehicle_id: usize, route: &[usize]) -> bool {
+        self.instance.route_limits_feasible(vehicle_id, route)
+            && self.instance.route_lifo_feasible(vehicle_id, route)
+            && self.schedule(vehicle_id, route).is_some()
+    }
+
+    /// Insert a request at its cheapest feasible position after the committed
+    /// stops and re-optimise the uncommitted parts; false if no vehicle can take it
+    pub fn insert_request(&mut self, req_id: usize) -> bool {
+        let deadline = self.deadline();
+        let Some((_, vehicle_id, insertion)) = self.cheapest_insertion(&self.routes, req_id) else {
+            return false;
+        };
+        self.apply(vehicle_id, req_id, insertion, deadline);
+        true
+    }
+
+    /// Accept a revealed request if it is needed for gamma or the minimum
+    /// profit, or if its profit pays for its insertion, and insert it
+    pub fn offer_request(&mut self, req_id: usize) -> bool {
+        let deadline = self.deadline();
+        let served = self.served_requests();
+        if served.contains(&req_id) {
+            return true;
+        }
+        let needed = !self.instance.coverage_met(&served);
+        if !needed && self.instance.profits().is_none() {
+            return false;
+        }
+        let Some((cost, vehicle_id, insertion)) = self.cheapest_insertion(&self.routes, req_id) else {
+            return false;
+        };
+        if !needed && cost >= self.instance.profit(req_id) {
+            return false;
+        }
+        self.apply(vehicle_id, req_id, insertion, deadline);
+        true
+    }
+
+    /// End of the latency budget of a request arriving now
+    fn deadline(&self) -> Instant {
+        Instant::now() + Duration::from_millis(self.config.latency_budget_ms)
+    }
+
+    fn served_requests(&self) -> Vec<usize> {
+        let n_reqs = self.instance.n_reqs();
+        self.routes.iter()
+            .flatten()
+            .filter(|&&node| node > n_reqs && node <= 2 * n_reqs)
+            .map(|&node| node - n_reqs - 1)
+            .collect()
+    }
+
+    /// Cheapest feasible insertion over all vehicles, scored like the insertion
+    /// construction by the change of vehicle cost + rho * fairness penalty
+    fn cheapest_insertion(&self, routes: &[Vec<usize>], req_id: usize) -> Option<(f64, usize, Insertion)> {
+        let fairness_measure = self.instance.fairness_measure();
+        let distances: Vec<f64> = routes.iter()
+            .enumerate()
+            .map(|(k, route)| self.instance.route_distance(k, route))
+            .collect();
+        let current_penalty = fairness_measure.penalty(&distances);
+
+        routes.iter()
+            .enumerate()
+            .filter_map(|(k, route)| {
+                let insertion = self.insertion.best_insertion_where(route, k, req_id, self.committed[k], |candidate| {
+                    self.route_feasible(k, candidate)
+                })?;
+                let mut new_distances = distances.clone();
+                new_distances[k] += insertion.delta;
+                let penalty = fairness_measure.penalty(&new_distances);
+                let vehicle = self.instance.vehicle(k);
+                let fixed_cost = if route.is_empty() { vehicle.fixed_cost } else { 0.0 };
+                let cost = fixed_cost + vehicle.cost_factor * insertion.delta
+                    + self.instance.rho() * (penalty - current_penalty);
+                Some((cost, k, insertion))
+            })
+            .min_by(|a, b| a.0.total_cmp(&b.0))
+    }
+
+    /// Insert the request as found by `cheapest_insertion`, then re-optimise
+    /// until the deadline
+    fn apply(&mut self, vehicle_id: usize, req_id: usize, insertion: Insertion, deadline: Instant) {
+        let route = &mut self.routes[vehicle_id];
+        route.insert(insertion.dropoff_pos, req_id + 1 + self.instance.n_reqs());
+        route.insert(insertion.pickup_pos, req_id + 1);
+        self.reoptimise(deadline);
+    }
+
+    fn objective(&self, routes: &[Vec<usize>]) -> f64 {
+        Solution::new(self.instance.clone(), routes.to_vec()).objective_function_value()
+    }
+
+    /// Relocate uncommitted requests to their cheapest position, first
+    /// improvement, until no move improves the objective or time is up
+    fn reoptimise(&mut self, deadline: Instant) {
+        let n_reqs = self.instance.n_reqs();
+        let mut current = self.objective(&self.routes);
+        let mut improved = true;
+        while improved && Instant::now() < deadline {
+            improved = false;
+            for vehicle_id in 0..self.routes.len() {
+                // Requests picked up after the committed prefix, so both stops can move
+                let movable: Vec<usize> = self.routes[vehicle_id][self.committed[vehicle_id]..].iter()
+                    .filter(|&&node| node >= 1 && node <= n_reqs)
+                    .map(|&node| node - 1)
+                    .collect();
+                for req_id in movable {
+                    if Instant::now() >= deadline {
+                        return;
+                    }
+                    let mut routes = self.routes.clone();
+                    routes[vehicle_id].retain(|&node| node != req_id + 1 && node != req_id + 1 + n_reqs);
+                    if !self.route_feasible(vehicle_id, &routes[vehicle_id]) {
+                        continue;
+                    }
+                    let Some((_, target, insertion)) = self.cheapest_insertion(&routes, req_id) else {
+                        continue;
+                    };
+                    let route = &mut routes[target];
+                    route.insert(insertion.dropoff_pos, req_id + 1 + n_reqs);
+                    route.insert(insertion.pickup_pos, req_id + 1);
+
+                    let objective = self.objective(&routes);
+                    if objective < current - 1e-9 {
+                        self.routes = routes;
+                        current = objective;
+                        improved = true;
+                    }
+                }
+            }
+        }
+    }
+}
+
+/// Result of replaying an instance online against the objective of an offline solution
+#[derive(Debug, Clone)]
+pub struct SimulationReport {
+    pub online: Solution,
+    pub online_objective: f64,
+    pub offline_objective: f64,
+    /// Revealed requests the router inserted
+    pub accepted: usize,
+    /// Revealed requests the router did not need or could not insert
+    pub rejected: usize,
+}
+
+impl SimulationReport {
+    /// Relative cost of not knowing the requests in advance
+    pub fn gap(&self) -> f64 {
+        if self.offline_objective > 0.0 {
+            (self.online_objective - self.offline_objective) / self.offline_objective
+        } else {
+            0.0
+        }
+    }
+}
+
+impl fmt::Display for SimulationReport {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        writeln!(f, "Online simulation:")?;
+        writeln!(f, "  Accepted requests: {}", self.accepted)?;
+        writeln!(f, "  Rejected requests: {}", self.rejected)?;
+        writeln!(f, "  Online objective: {:.2} (valid: {})", self.online_objective, self.online.is_valid())?;
+        writeln!(f, "  Offline objective: {:.2}", self.offline_objective)?;
+        writeln!(f, "  Gap: {:.2}%", 100.0 * self.gap())
+    }
+}
+
+/// Replay an instance online: requests are revealed in order of their release
+/// times and offered to an `OnlineRouter` whose clock follows the releases.
+/// The online objective is compared against `offline_objective`
+pub fn simulate(instance: &Instance, offline_objective: f64, config: OnlineConfig) -> SimulationReport {
+    let mut order: Vec<usize> = (0..instance.n_reqs()).collect();
+    order.sort_by(|&a, &b| instance.release_time(a).total_cmp(&instance.release_time(b)));
+
+    let mut router = OnlineRouter::new(instance, config);
+    let (mut accepted, mut rejected) = (0, 0);
+    for req_id in order {
+        router.advance_to(instance.release_time(req_id));
+        if router.offer_request(req_id) {
+            accepted += 1;
+        } else {
+            rejected += 1;
+        }
+    }
+
+    let online = router.solution();
+    SimulationReport {
+        online_objective: online.objective_function_value(),
+        offline_objective,
+        online,
+        accepted,
+        rejected,
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::instance::tests::from_text;
+
+    // Two requests on a line: pickups at x = 1, 2 and dropoffs at x = 3, 4
+    const LINE: &str = "\
+2 1 10 2 0
+# demands
+1 1
+# request locations
+0 0
+1 0
+2 0
+3 0
+4 0
+";
+
+    fn released(times: &str) -> String {
+        format!("{}# release times\n{}\n", LINE, times)
+    }
+
+    #[test]
+    fn stops_served_by_the_current_time_are_committed() {
+        let instance = from_text("online_commit", LINE);
+        let solution = Solution::new(instance.clone(), vec![vec![1, 2, 3, 4]]);
+        // Served at 1, 2, 3 and 4
+        let router = OnlineRouter::from_solution(&instance, &solution, 2.5, OnlineConfig::default());
+        assert_eq!(router.time(), 2.5);
+        assert_eq!(router.committed(), &[2]);
+        assert_eq!(router.solution().routes, solution.routes);
+    }
+
+    #[test]
+    fn requests_are_inserted_after_the_committed_stops() {
+        let instance = from_text("online_insert", LINE);
+        let solution = Solution::new(instance.clone(), vec![vec![1, 3]]);
+        // Request 1 would ride along for free from the depot, but the vehicle left at 0
+        let mut router = OnlineRouter::from_solution(&instance, &solution, 3.0, OnlineConfig::default());
+        assert_eq!(router.committed(), &[2]);
+        assert!(router.insert_request(1));
+        assert_eq!(router.solution().routes, vec![vec![1, 3, 2, 4]]);
+    }
+
+    #[test]
+    fn pickups_wait_for_their_release() {
+        let instance = from_text("online_release", &released("0 10"));
+        let mut router = OnlineRouter::new(&instance, OnlineConfig::default());
+        assert!(router.offer_request(0));
+        router.advance_to(1.0);
+        // The vehicle is at pickup 1 at 1, next door to pickup 2, but request 1
+        // is only released at 10
+        let schedule = router.schedule(0, &[1, 2, 3, 4]).unwrap();
+        assert_eq!(schedule, vec![1.0, 10.0, 11.0, 12.0]);
+        router.advance_to(10.0);
+        assert!(router.offer_request(1));
+        let route = &router.solution().routes[0];
+        let schedule = router.schedule(0, route).unwrap();
+        let pickup = route.iter().position(|&node| node == 2).unwrap();
+        assert!(schedule[pickup] >= 10.0);
+    }
+
+    #[test]
+    fn requests_beyond_gamma_are_rejected_without_profits() {
+        let instance = from_text("online_gamma", &LINE.replacen("2 1 10 2 0", "2 1 10 1 0", 1));
+        let mut router = OnlineRouter::new(&instance, OnlineConfig::default());
+        assert!(router.offer_request(0));
+        assert!(!router.offer_request(1));
+
+        // Serving request 1 as well adds 2 to the route
+        let instance = instance.with_profits(Some(vec![3.0, 1.0]), None);
+        let mut router = OnlineRouter::new(&instance, OnlineConfig::default());
+        assert!(router.offer_request(0));
+        assert!(!router.offer_request(1));
+        let instance = instance.with_profits(Some(vec![3.0, 3.0]), None);
+        let mut router = OnlineRouter::new(&instance, OnlineConfig::default());
+        assert!(router.offer_request(0));
+        assert!(router.offer_request(1));
+    }
+
+    #[test]
+    fn replay_never_moves_committed_stops() {
+        let instance = from_text("online_replay", &released("0 2").replacen("2 1 10 2 0", "2 2 10 2 0", 1));
+        let mut router = OnlineRouter::new(&instance, OnlineConfig::default());
+        for (req_id, release) in [(0, 0.0), (1, 2.0)] {
+            router.advance_to(release);
+            let prefixes: Vec<Vec<usize>> = router.solution().routes.iter()
+                .zip(router.committed())
+                .map(|(route, &committed)| route[..committed].to_vec())
+                .collect();
+            assert!(router.offer_request(req_id));
+            for (route, prefix) in router.solution().routes.iter().zip(&prefixes) {
+                assert!(route.starts_with(prefix));
+            }
+        }
+        let solution = router.solution();
+        assert!(solution.is_valid());
+        for (vehicle_id, route) in solution.routes.iter().enumerate() {
+            let schedule = router.schedule(vehicle_id, route).unwrap();
+            for (&node, &start) in route.iter().zip(&schedule) {
+                if node <= instance.n_reqs() {
+                    assert!(start >= instance.release_time(node - 1));
+                }
+            }
+        }
+    }
+
+    #[test]
+    fn simulation_reports_the_gap_to_the_offline_objective() {
+        // Request 1 is released after the vehicle dropped request 0 at 3
+        let instance = from_text("online_simulation", &released("0 8"));
+        let report = simulate(&instance, 8.0, OnlineConfig::default());
+        assert_eq!((report.accepted, report.rejected), (2, 0));
+        assert!(report.online.is_valid());
+        // It drives on from there: 1 + 2 + 1 + 2 + 4 instead of 8
+        assert_eq!(report.online.routes, vec![vec![1, 3, 2, 4]]);
+        assert_eq!(report.online_objective, 10.0);
+        assert!((report.gap() - 0.25).abs() < 1e-9);
+    }
+}
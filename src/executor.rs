@@ -1,5 +1,4 @@
 
 
 /// responsible for running different heuristics for different sizes and gathering data
-
 pub struct Executor {}
\ No newline at end of file
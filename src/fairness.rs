@@ -0,0 +1,312 @@
+use std::fmt;
+use std::str::FromStr;
+
+/// Measure used to quantify how evenly travel distance is spread across routes.
+///
+/// Every measure is turned into a penalty that enters the objective as
+/// `total_distance + rho * penalty`. For the normalized measures the penalty is
+/// `1 - fairness`, so a perfectly balanced solution has zero penalty.
+#[derive(Debug, Clone, Copy, PartialEq, Default)]
+pub enum FairnessMeasure {
+    /// Jain index: (Σd)² / (k·Σd²)
+    #[default]
+    Jain,
+    /// Gini coefficient of the route distances
+    Gini,
+    /// Ratio between the shortest and the longest route
+    MaxMinRatio,
+    /// Length of the longest route (not normalized)
+    Makespan,
+    /// Coefficient of variation (standard deviation / mean)
+    CoefficientOfVariation,
+    /// Mean divided by the power mean of order 1 + alpha.
+    /// alpha = 1 gives sqrt(Jain), large alpha approaches mean / max.
+    AlphaFairness { alpha: f64 },
+}
+
+impl FairnessMeasure {
+    /// All measures with default parameters, useful for trade-off studies
+    pub fn all() -> Vec<FairnessMeasure> {
+        vec![
+            FairnessMeasure::Jain,
+            FairnessMeasure::Gini,
+            FairnessMeasure::MaxMinRatio,
+            FairnessMeasure::Makespan,
+            FairnessMeasure::CoefficientOfVariation,
+            FairnessMeasure::AlphaFairness { alpha: 2.0 },
+        ]
+    }
+
+    /// Fairness score of the given route distances, higher is fairer.
+    /// Normalized measures lie in [0, 1]; for `Makespan` the score is the
+    /// negated longest route.
+    pub fn fairness(&self, distances: &[f64]) -> f64 {
+        match self {
+            FairnessMeasure::Makespan => -self.penalty(distances),
+            FairnessMeasure::CoefficientOfVariation => 1.0 / (1.0 + self.penalty(distances)),
+            _ => 1.0 - self.penalty(distances),
+        }
+    }
+
+    /// Unfairness term that gets multiplied by rho in the objective
+    pub fn penalty(&self, distances: &[f64]) -> f64 {
+        // numeric safety
+        let eps = 1e-12;
+        let k = distances.len() as f64;
+        let sum: f64 = distances.iter().sum();
+
+        if distances.is_empty() || sum <= eps {
+            return 0.0; // all routes empty (or numerically zero) → perfectly fair
+        }
+
+        let mean = sum / k;
+
+        match self {
+            FairnessMeasure::Jain => 1.0 - jain_index(distances),
+            FairnessMeasure::Gini => {
+                // G = Σ_i Σ_j |d_i - d_j| / (2 k² mean), computed on sorted values
+                let mut sorted = distances.to_vec();
+                sorted.sort_by(|a, b| a.partial_cmp(b).unwrap_or(std::cmp::Ordering::Equal));
+                let weighted: f64 = sorted.iter()
+                    .enumerate()
+                    .map(|(i, d)| (2.0 * (i as f64 + 1.0) - k - 1.0) * d)
+                    .sum();
+                weighted / (k * k * mean)
+            }
+            FairnessMeasure::MaxMinRatio => {
+                let max = distances.iter().cloned().fold(f64::NEG_INFINITY, f64::max);
+                let min = distances.iter().cloned().fold(f64::INFINITY, f64::min);
+                1.0 - min / max
+            }
+            FairnessMeasure::Makespan => {
+                distances.iter().cloned().fold(0.0, f64::max)
+            }
+            FairnessMeasure::CoefficientOfVariation => {
+                let variance: f64 = distances.iter()
+                    .map(|d| (d - mean).powi(2))
+                    .sum::<f64>() / k;
+                variance.sqrt() / mean
+            }
+            FairnessMeasure::AlphaFairness { alpha } => {
+                let p = 1.0 + alpha.max(0.0);
+                let power_mean = (distances.iter()
+                    .map(|d| d.powf(p))
+                    .sum::<f64>() / k)
+                    .powf(1.0 / p);
+                if power_mean <= eps {
+                    0.0
+                } else {
+                    1.0 - mean / power_mean
+                }
+            }
+        }
+    }
+}
+
+impl FairnessMeasure {
+    /// Share of the penalty attributed to each route; the shares sum to `penalty`.
+    /// - Jain: (d_i - mean)² / Σd², which is exact
+    /// - Gini: the route's summed absolute differences to all routes, which is exact
+    /// - Coefficient of variation and alpha fairness: the route's share of the
+    ///   convexity gap Σ(d^p - mean^p), p = 2 for CV; for p = 2 this is the
+    ///   squared deviation from the mean
+    /// - Makespan: the longest route(s)
+    /// - Max/min ratio: half to the longest, half to the shortest route(s)
+    pub fn contributions(&self, distances: &[f64]) -> Vec<f64> {
+        let penalty = self.penalty(distances);
+        let k = distances.len() as f64;
+        let mut shares = vec![0.0; distances.len()];
+        if penalty == 0.0 {
+            return shares;
+        }
+        let sum: f64 = distances.iter().sum();
+        let mean = sum / k;
+        let max = distances.iter().cloned().fold(f64::NEG_INFINITY, f64::max);
+        let min = distances.iter().cloned().fold(f64::INFINITY, f64::min);
+
+        // Spread `amount` over `weights` in proportion
+        let proportional = |shares: &mut [f64], weights: &[f64], amount: f64| {
+            let total: f64 = weights.iter().sum();
+            if total > 0.0 {
+                for (share, weight) in shares.iter_mut().zip(weights) {
+                    *share += amount * weight / total;
+                }
+            }
+        };
+
+        match self {
+            FairnessMeasure::Jain => {
+                let sum_sq: f64 = distances.iter().map(|d| d * d).sum();
+                for (share, d) in shares.iter_mut().zip(distances) {
+                    *share = (d - mean).powi(2) / sum_sq;
+                }
+            }
+            FairnessMeasure::Gini => {
+                for (share, a) in shares.iter_mut().zip(distances) {
+                    let differences: f64 = distances.iter().map(|b| (a - b).abs()).sum();
+                    *share = differences / (2.0 * k * k * mean);
+                }
+            }
+            FairnessMeasure::CoefficientOfVariation | FairnessMeasure::AlphaFairness { .. } => {
+                let p = match self {
+                    FairnessMeasure::AlphaFairness { alpha } => 1.0 + alpha.max(0.0),
+                    _ => 2.0,
+                };
+                // d^p - mean^p - p mean^(p-1) (d - mean) >= 0, summing to Σd^p - k mean^p
+                let gaps: Vec<f64> = distances.iter()
+                    .map(|d| d.powf(p) - mean.powf(p) - p * mean.powf(p - 1.0) * (d - mean))
+                    .collect();
+                proportional(&mut shares, &gaps, penalty);
+            }
+            FairnessMeasure::Makespan => {
+                let longest: Vec<f64> = distances.iter().map(|&d| if d == max { 1.0 } else { 0.0 }).collect();
+                proportional(&mut shares, &longest, penalty);
+            }
+            FairnessMeasure::MaxMinRatio => {
+                let longest: Vec<f64> = distances.iter().map(|&d| if d == max { 1.0 } else { 0.0 }).collect();
+                let shortest: Vec<f64> = distances.iter().map(|&d| if d == min { 1.0 } else { 0.0 }).collect();
+                proportional(&mut shares, &longest, penalty / 2.0);
+                proportional(&mut shares, &shortest, penalty / 2.0);
+            }
+        }
+        shares
+    }
+}
+
+/// Jain fairness index of the given route distances
+pub fn jain_index(distances: &[f64]) -> f64 {
+    let sum: f64 = distances.iter().sum();
+    let sum_sq: f64 = distances.iter().map(|d| d * d).sum();
+    let k = distances.len() as f64;
+
+    // numeric safety
+    let eps = 1e-12;
+    if sum_sq <= eps {
+        return 1.0; // all routes empty (or numerically zero) → perfectly fair
+    }
+
+    (sum * sum) / (k * sum_sq)
+}
+
+impl fmt::Display for FairnessMeasure {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        match self {
+            FairnessMeasure::Jain => write!(f, "jain"),
+            FairnessMeasure::Gini => write!(f, "gini"),
+            FairnessMeasure::MaxMinRatio => write!(f, "max_min_ratio"),
+            FairnessMeasure::Makespan => write!(f, "makespan"),
+            FairnessMeasure::CoefficientOfVariation => write!(f, "cv"),
+            FairnessMeasure::AlphaFairness { alpha } => write!(f, "alpha:{}", alpha),
+        }
+    }
+}
+
+impl FromStr for FairnessMeasure {
+    type Err = String;
+
+    /// Parses the names produced by `Display`, e.g. `jain`, `gini` or `alpha:2`
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        let s = s.trim().to_lowercase();
+        match s.as_str() {
+            "jain" => Ok(FairnessMeasure::Jain),
+            "gini" => Ok(FairnessMeasure::Gini),
+            "max_min_ratio" | "maxmin" => Ok(FairnessMeasure::MaxMinRatio),
+            "makespan" => Ok(FairnessMeasure::Makespan),
+            "cv" | "coefficient_of_variation" => Ok(FairnessMeasure::CoefficientOfVariation),
+            other => {
+                if let Some(alpha) = other.strip_prefix("alpha:") {
+                    let alpha: f64 = alpha.parse()
+                        .map_err(|_| format!("Invalid alpha value: {}", alpha))?;
+                    Ok(FairnessMeasure::AlphaFairness { alpha })
+                } else {
+                    Err(format!("Unknown fairness measure: {}", other))
+                }
+            }
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    // Sum 12, mean 3, Σd² = 50, pairwise differences 1, 2, 5, 1, 4, 3
+    const DISTANCES: [f64; 4] = [1.0, 2.0, 3.0, 6.0];
+
+    fn assert_close(actual: f64, expected: f64) {
+        assert!((actual - expected).abs() < 1e-9, "{} != {}", actual, expected);
+    }
+
+    #[test]
+    fn jain_penalty() {
+        assert_close(jain_index(&DISTANCES), 144.0 / 200.0);
+        assert_close(FairnessMeasure::Jain.penalty(&DISTANCES), 0.28);
+    }
+
+    #[test]
+    fn gini_penalty() {
+        // Σ_i Σ_j |d_i - d_j| / (2 k² mean) = 32 / 96
+        assert_close(FairnessMeasure::Gini.penalty(&DISTANCES), 1.0 / 3.0);
+    }
+
+    #[test]
+    fn max_min_ratio_penalty() {
+        assert_close(FairnessMeasure::MaxMinRatio.penalty(&DISTANCES), 1.0 - 1.0 / 6.0);
+    }
+
+    #[test]
+    fn makespan_penalty() {
+        assert_close(FairnessMeasure::Makespan.penalty(&DISTANCES), 6.0);
+        assert_close(FairnessMeasure::Makespan.fairness(&DISTANCES), -6.0);
+    }
+
+    #[test]
+    fn coefficient_of_variation_penalty() {
+        // Population variance (4 + 1 + 0 + 9) / 4
+        assert_close(FairnessMeasure::CoefficientOfVariation.penalty(&DISTANCES), 3.5f64.sqrt() / 3.0);
+    }
+
+    #[test]
+    fn alpha_fairness_penalty() {
+        // alpha = 1 is one minus the square root of Jain
+        let alpha_one = FairnessMeasure::AlphaFairness { alpha: 1.0 };
+        assert_close(alpha_one.penalty(&DISTANCES), 1.0 - jain_index(&DISTANCES).sqrt());
+        assert_close(alpha_one.penalty(&DISTANCES), 1.0 - 3.0 / 12.5f64.sqrt());
+    }
+
+    #[test]
+    fn balanced_and_empty_routes_have_no_penalty() {
+        for measure in FairnessMeasure::all() {
+            assert_eq!(measure.penalty(&[]), 0.0, "{}", measure);
+            assert_eq!(measure.penalty(&[0.0, 0.0]), 0.0, "{}", measure);
+            if measure != FairnessMeasure::Makespan {
+                assert_close(measure.penalty(&[5.0, 5.0, 5.0]), 0.0);
+            }
+        }
+    }
+
+    #[test]
+    fn contributions_sum_to_penalty() {
+        for measure in FairnessMeasure::all() {
+            let contributions = measure.contributions(&DISTANCES);
+            assert_eq!(contributions.len(), DISTANCES.len());
+            assert!(contributions.iter().all(|&c| c >= 0.0), "{}", measure);
+            assert_close(contributions.iter().sum(), measure.penalty(&DISTANCES));
+        }
+    }
+
+    #[test]
+    fn makespan_and_max_min_ratio_blame_the_extremes() {
+        assert_eq!(FairnessMeasure::Makespan.contributions(&DISTANCES), vec![0.0, 0.0, 0.0, 6.0]);
+        let half = (1.0 - 1.0 / 6.0) / 2.0;
+        assert_eq!(FairnessMeasure::MaxMinRatio.contributions(&DISTANCES), vec![half, 0.0, 0.0, half]);
+    }
+
+    #[test]
+    fn parses_display_names() {
+        for measure in FairnessMeasure::all() {
+            assert_eq!(measure.to_string().parse::<FairnessMeasure>(), Ok(measure));
+        }
+        assert!("fair".parse::<FairnessMeasure>().is_err());
+    }
+}
@@ -0,0 +1,233 @@
+use std::fmt;
+
+use crate::instance::Point;
+
+/// One vehicle of a possibly heterogeneous fleet
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct Vehicle {
+    pub capacity: usize,
+    /// Paid once if the vehicle serves at least one stop
+    pub fixed_cost: f64,
+    /// Cost per unit of travelled distance
+    pub cost_factor: f64,
+    /// Depot the vehicle belongs to, 0 being the depot of the request locations
+    pub home_depot: usize,
+    /// Where the route starts and ends, the home depot if `None`
+    pub start_depot: Option<Point>,
+    pub end_depot: Option<Point>,
+    /// Rear-loaded: only the request loaded last can be dropped off
+    pub lifo: bool,
+}
+
+impl Vehicle {
+    /// A vehicle with the given capacity, no fixed cost and unit distance cost,
+    /// starting and ending at the instance depot
+    pub fn with_capacity(capacity: usize) -> Self {
+        Self {
+            capacity,
+            fixed_cost: 0.0,
+            cost_factor: 1.0,
+            home_depot: 0,
+            start_depot: None,
+            end_depot: None,
+            lifo: false,
+        }
+    }
+
+    /// Cost of a route of the given distance, zero for an unused vehicle
+    pub fn route_cost(&self, distance: f64, used: bool) -> f64 {
+        if used {
+            self.fixed_cost + self.cost_factor * distance
+        } else {
+            0.0
+        }
+    }
+
+    /// Parse `capacity fixed_cost cost_factor [start_x start_y [end_x end_y]]`;
+    /// a vehicle with a start but no end depot returns to its start
+    fn parse(line: &str, vehicle_id: usize) -> Result<Self, Box<dyn std::error::Error>> {
+        let parts: Vec<&str> = line.split_whitespace().collect();
+        if !matches!(parts.len(), 3 | 5 | 7) {
+            return Err(format!("Vehicle {} should have 3, 5 or 7 values", vehicle_id).into());
+        }
+        let coordinates: Vec<f64> = parts[3..].iter()
+            .map(|s| s.parse())
+            .collect::<Result<_, _>>()?;
+        let points: Vec<Point> = coordinates.chunks(2)
+            .map(|c| Point { x: c[0], y: c[1] })
+            .collect();
+
+        let vehicle = Vehicle {
+            capacity: parts[0].parse()?,
+            fixed_cost: parts[1].parse()?,
+            cost_factor: parts[2].parse()?,
+            home_depot: 0,
+            start_depot: points.first().copied(),
+            end_depot: points.get(1).or(points.first()).copied(),
+            lifo: false,
+        };
+        if vehicle.fixed_cost < 0.0 || vehicle.cost_factor < 0.0 {
+            return Err(format!("Vehicle {} has a negative cost", vehicle_id).into());
+        }
+        Ok(vehicle)
+    }
+}
+
+/// Parse the lines following `# fleet`: one line per vehicle
+pub(crate) fn parse_fleet<I>(lines: &mut I, n_vehicles: usize) -> Result<Vec<Vehicle>, Box<dyn std::error::Error>>
+where
+    I: Iterator<Item = std::io::Result<String>>,
+{
+    (0..n_vehicles)
+        .map(|k| {
+            let line = lines.next().ok_or(format!("Missing vehicle {}", k))??;
+            Vehicle::parse(&line, k)
+        })
+        .collect()
+}
+
+/// Parse the lines following `# depots`: the number of additional depots, one
+/// line of coordinates per additional depot and a line with the home depot of
+/// every vehicle, 0 being the depot of the request locations
+pub(crate) fn parse_depots<I>(
+    lines: &mut I,
+    n_vehicles: usize,
+) -> Result<(Vec<Point>, Vec<usize>), Box<dyn std::error::Error>>
+where
+    I: Iterator<Item = std::io::Result<String>>,
+{
+    let count_line = lines.next().ok_or("Missing number of depots")??;
+    let count: usize = count_line.trim().parse()?;
+
+    let mut depots = Vec::with_capacity(count);
+    for i in 0..count {
+        let line = lines.next().ok_or(format!("Missing depot {}", i + 1))??;
+        let parts: Vec<f64> = line
+            .split_whitespace()
+            .map(|s| s.parse())
+            .collect::<Result<_, _>>()?;
+        if parts.len() != 2 {
+            return Err(format!("Depot {} should have 2 values", i + 1).into());
+        }
+        depots.push(Point { x: parts[0], y: parts[1] });
+    }
+
+    let homes_line = lines.next().ok_or("Missing home depots of the vehicles")??;
+    let homes: Vec<usize> = homes_line
+        .split_whitespace()
+        .map(|s| s.parse())
+        .collect::<Result<_, _>>()?;
+    if homes.len() != n_vehicles {
+        return Err(format!("Expected {} home depots, got {}", n_vehicles, homes.len()).into());
+    }
+    if let Some(&home) = homes.iter().find(|&&home| home > count) {
+        return Err(format!("Invalid home depot {}", home).into());
+    }
+    Ok((depots, homes))
+}
+
+/// Parse the line following `# lifo`: `all`, or the indices of the vehicles
+/// that load last-in-first-out
+pub(crate) fn parse_lifo<I>(lines: &mut I, n_vehicles: usize) -> Result<Vec<bool>, Box<dyn std::error::Error>>
+where
+    I: Iterator<Item = std::io::Result<String>>,
+{
+    let line = lines.next().ok_or("Missing LIFO vehicles")??;
+    if line.trim() == "all" {
+        return Ok(vec![true; n_vehicles]);
+    }
+    let mut lifo = vec![false; n_vehicles];
+    for part in line.split_whitespace() {
+        let vehicle_id: usize = part.parse()?;
+        if vehicle_id >= n_vehicles {
+            return Err(format!("Invalid LIFO vehicle {}", vehicle_id).into());
+        }
+        lifo[vehicle_id] = true;
+    }
+    Ok(lifo)
+}
+
+impl fmt::Display for Vehicle {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        write!(f, "capacity {}, fixed cost {}, cost factor {}, home depot {}",
+               self.capacity, self.fixed_cost, self.cost_factor, self.home_depot)?;
+        if let Some(start) = self.start_depot {
+            write!(f, ", start {}", start)?;
+        }
+        if let Some(end) = self.end_depot {
+            write!(f, ", end {}", end)?;
+        }
+        if self.lifo {
+            write!(f, ", LIFO")?;
+        }
+        Ok(())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::instance::tests::from_text;
+    use crate::{InsertionConstruction, Solution, Solver};
+
+    // A small cheap vehicle and a large one with fixed and doubled distance cost
+    const FLEET: &str = "\
+2 2 10 2 0
+# demands
+1 1
+# request locations
+0 0
+1 0
+2 0
+3 0
+4 0
+# fleet
+1 0 1
+2 5 2
+";
+
+    #[test]
+    fn parses_costs_and_depots() {
+        let vehicle = Vehicle::parse("4 10 1.5 2 3", 0).unwrap();
+        assert_eq!((vehicle.capacity, vehicle.fixed_cost, vehicle.cost_factor), (4, 10.0, 1.5));
+        // Without an end depot the vehicle returns to its start
+        assert_eq!(vehicle.start_depot, Some(Point { x: 2.0, y: 3.0 }));
+        assert_eq!(vehicle.end_depot, vehicle.start_depot);
+        assert!(Vehicle::parse("4 10", 0).is_err());
+        assert!(Vehicle::parse("4 -1 1", 0).is_err());
+    }
+
+    #[test]
+    fn unused_vehicles_cost_nothing() {
+        let vehicle = Vehicle::parse("2 5 2", 0).unwrap();
+        assert_eq!(vehicle.route_cost(8.0, true), 21.0);
+        assert_eq!(vehicle.route_cost(0.0, false), 0.0);
+    }
+
+    #[test]
+    fn capacity_and_cost_are_per_vehicle() {
+        let instance = from_text("fleet", FLEET);
+        let both_on = |k: usize| {
+            let mut routes = vec![vec![], vec![]];
+            routes[k] = vec![1, 2, 3, 4];
+            Solution::new(instance.clone(), routes)
+        };
+        assert!(!both_on(0).is_valid());
+        assert!(both_on(1).is_valid());
+
+        assert_eq!(both_on(1).objective_function_value(), 5.0 + 2.0 * 8.0);
+        assert_eq!(both_on(1).objective_breakdown().fleet_cost_term, 13.0);
+        // One request after the other fits the small vehicle
+        let small = Solution::new(instance, vec![vec![1, 3, 2, 4], vec![]]);
+        assert!(small.is_valid());
+        assert_eq!(small.objective_function_value(), 10.0);
+    }
+
+    #[test]
+    fn insertion_prefers_the_cheaper_vehicle() {
+        let instance = from_text("fleet_insertion", FLEET);
+        let solution = InsertionConstruction::cheapest(&instance).solve();
+        assert!(solution.is_valid());
+        assert_eq!(solution.routes, vec![vec![1, 3, 2, 4], vec![]]);
+    }
+}
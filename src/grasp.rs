@@ -1,5 +1,8 @@
+use std::cell::{Cell, RefCell};
 use std::time::Instant;
-use crate::{Instance, LocalSearch, LocalSearchConfig, Neighborhood, RandomConstruction, Solution, Solver, StepFunction, AcceptanceCriterion, VND};
+use rand::prelude::*;
+use crate::solver::SearchStats;
+use crate::{Instance, LocalSearch, LocalSearchConfig, Neighborhood, RandomConstruction, Solution, Solver, StepFunction, AcceptanceCriterion};
 
 /// Greedy Randomized Adaptive Search Procedure (GRASP)
 /// Combines randomized construction with local search improvement
@@ -7,13 +10,13 @@ use crate::{Instance, LocalSearch, LocalSearchConfig, Neighborhood, RandomConstr
 pub enum LocalSearchStrategy {
     /// Use a single neighborhood with best improvement
     SingleNeighborhood(Neighborhood),
-    /// Use VND with multiple neighborhoods
+    /// Use VND over the configured neighborhoods
     VND,
-    /// Use a composite neighborhood (union of all neighborhoods)
+    /// Use a composite neighborhood (union of the configured neighborhoods)
     CompositeNeighborhood,
 }
 
-#[derive(Debug, Clone, Copy)]
+#[derive(Debug, Clone)]
 pub struct GRASPConfig {
     pub max_iterations: usize,
     pub time_limit_seconds: u64,
@@ -21,6 +24,8 @@ pub struct GRASPConfig {
     pub biased_construction: bool,
     pub local_search_max_iterations: usize,
     pub local_search_time_limit: u64,
+    /// Neighborhoods of the VND and composite strategies, in VND order
+    pub neighborhoods: Vec<Neighborhood>,
 }
 
 impl Default for GRASPConfig {
@@ -32,6 +37,7 @@ impl Default for GRASPConfig {
             biased_construction: true,
             local_search_max_iterations: 100,
             local_search_time_limit: 60,
+            neighborhoods: Neighborhood::vnd_default().to_vec(),
         }
     }
 }
@@ -39,16 +45,37 @@ impl Default for GRASPConfig {
 pub struct GRASP<'a> {
     instance: &'a Instance,
     config: GRASPConfig,
+    rng: RefCell<StdRng>,
+    stats: Cell<SearchStats>,
 }
 
 impl<'a> GRASP<'a> {
     pub fn new(instance: &'a Instance, config: GRASPConfig) -> Self {
-        Self { instance, config }
+        Self {
+            instance,
+            config,
+            rng: RefCell::new(StdRng::from_entropy()),
+            stats: Cell::new(SearchStats::default()),
+        }
+    }
+
+    /// Make the run reproducible; every construction is seeded from this generator
+    pub fn with_seed(self, seed: u64) -> Self {
+        self.rng.replace(StdRng::seed_from_u64(seed));
+        self
+    }
+
+    fn count_evaluations(&self, evaluations: usize) {
+        let mut stats = self.stats.get();
+        stats.evaluations += evaluations;
+        self.stats.set(stats);
     }
 
     /// Construction phase: Generate a randomized solution
     fn construct_solution(&self) -> Solution {
-        let random_construction = RandomConstruction::new(self.instance, self.config.biased_construction);
+        let seed = self.rng.borrow_mut().gen();
+        let random_construction = RandomConstruction::new(self.instance, self.config.biased_construction)
+            .with_seed(seed);
         random_construction.solve()
     }
 
@@ -86,6 +113,7 @@ impl<'a> GRASP<'a> {
         while improved {
             improved = false;
             let neighbors = local_search.generate_neighbors(&current);
+            self.count_evaluations(neighbors.len());
             
             if neighbors.is_empty() {
                 break;
@@ -114,24 +142,13 @@ impl<'a> GRASP<'a> {
 
     /// Local search using Variable Neighborhood Descent
     fn vnd_search(&self, solution: Solution) -> Solution {
-        // Use all three neighborhoods in VND
-        let neighborhoods = vec![
-            Neighborhood::Relocate,
-            Neighborhood::Exchange,
-            Neighborhood::TwoOpt,
-        ];
-
-        let vnd = VND::new(self.instance, neighborhoods.clone())
-            .with_max_iterations(self.config.local_search_max_iterations)
-            .with_biased_construction(false); // Start from provided solution
-
-        // Since VND constructs its own initial solution, we need to improve the given solution
-        // We'll use a modified approach
-        self.vnd_search_with_initial(solution, neighborhoods)
+        // VND constructs its own initial solution, so the given solution is
+        // improved with a VND loop that starts from it instead
+        self.vnd_search_with_initial(solution, &self.config.neighborhoods)
     }
 
     /// VND search starting from a given solution
-    fn vnd_search_with_initial(&self, mut current: Solution, neighborhoods: Vec<Neighborhood>) -> Solution {
+    fn vnd_search_with_initial(&self, mut current: Solution, neighborhoods: &[Neighborhood]) -> Solution {
         let mut best_solution = current.clone();
         let mut best_obj = current.objective_function_value();
         let mut iterations = 0;
@@ -140,7 +157,7 @@ impl<'a> GRASP<'a> {
             let mut improved = false;
             
             // Try each neighborhood in sequence
-            for &neighborhood in &neighborhoods {
+            for &neighborhood in neighborhoods {
                 if let Some(better_solution) = self.explore_neighborhood(&current, neighborhood) {
                     let better_obj = better_solution.objective_function_value();
                     
@@ -180,6 +197,7 @@ impl<'a> GRASP<'a> {
 
         let local_search = LocalSearch::new(self.instance, config);
         let neighbors = local_search.generate_neighbors(current);
+        self.count_evaluations(neighbors.len());
         
         if neighbors.is_empty() {
             return None;
@@ -200,24 +218,18 @@ impl<'a> GRASP<'a> {
         best_neighbor
     }
 
-    /// Local search using composite neighborhood (union of all neighborhoods)
+    /// Local search using composite neighborhood (union of the configured neighborhoods)
     fn composite_neighborhood_search(&self, solution: Solution) -> Solution {
         let mut current = solution;
         let mut improved = true;
 
-        let neighborhoods = vec![
-            Neighborhood::Relocate,
-            Neighborhood::Exchange,
-            Neighborhood::TwoOpt,
-        ];
-
         while improved {
             improved = false;
             
             // Generate neighbors from ALL neighborhoods (composite)
             let mut all_neighbors = Vec::new();
             
-            for &neighborhood in &neighborhoods {
+            for &neighborhood in &self.config.neighborhoods {
                 let config = LocalSearchConfig {
                     neighborhood,
                     step_function: StepFunction::BestImprovement,
@@ -232,6 +244,7 @@ impl<'a> GRASP<'a> {
                 all_neighbors.extend(neighbors);
             }
 
+            self.count_evaluations(all_neighbors.len());
             if all_neighbors.is_empty() {
                 break;
             }
@@ -262,9 +275,11 @@ impl<'a> GRASP<'a> {
 impl<'a> Solver for GRASP<'a> {
     fn solve(&self) -> Solution {
         let start_time = Instant::now();
+        self.stats.set(SearchStats::default());
         
         let mut best_solution: Option<Solution> = None;
         let mut best_obj = f64::INFINITY;
+        let mut time_to_best = None;
         
         let mut iteration = 0;
 
@@ -289,16 +304,31 @@ impl<'a> Solver for GRASP<'a> {
                 if obj < best_obj {
                     best_obj = obj;
                     best_solution = Some(improved_solution);
+                    time_to_best = Some(start_time.elapsed().as_secs_f64());
                 }
             }
 
             iteration += 1;
         }
 
+        let mut stats = self.stats.get();
+        stats.iterations = iteration;
+        stats.time_to_best_seconds = time_to_best;
+        self.stats.set(stats);
+
         // Return best solution found, or construct a fallback if none found
         best_solution.unwrap_or_else(|| {
-            let fallback = RandomConstruction::new(self.instance, true);
+            let seed = self.rng.borrow_mut().gen();
+            let fallback = RandomConstruction::new(self.instance, true).with_seed(seed);
             fallback.solve()
         })
     }
+
+    fn stats(&self) -> SearchStats {
+        self.stats.get()
+    }
+
+    fn config_description(&self) -> String {
+        format!("{:?}", self.config)
+    }
 }
\ No newline at end of file
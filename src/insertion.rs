@@ -0,0 +1,394 @@
+use std::cmp::Ordering;
+
+use crate::{Instance, Solution, Solver};
+
+/// How the next request to insert is chosen
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum InsertionStrategy {
+    /// Parallel cheapest insertion: the request with the cheapest insertion over all vehicles
+    Cheapest,
+    /// Regret-k: the request that loses most if it cannot go to its best vehicle,
+    /// summed over its k - 1 next-best vehicles
+    Regret(usize),
+}
+
+impl InsertionStrategy {
+    /// Solver name used for result files
+    pub fn name(&self) -> String {
+        match self {
+            InsertionStrategy::Cheapest => "cheapest_insertion".to_string(),
+            InsertionStrategy::Regret(k) => format!("regret{}_insertion", k),
+        }
+    }
+}
+
+/// Cheapest way to insert one request into one route
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub(crate) struct Insertion {
+    /// Increase of the route distance
+    pub(crate) delta: f64,
+    /// The pickup goes before `route[pickup_pos]`, the dropoff before `route[dropoff_pos]`
+    /// of the route without the request; equal positions place them next to each other
+    pub(crate) pickup_pos: usize,
+    pub(crate) dropoff_pos: usize,
+}
+
+/*
+Builds routes by repeatedly inserting a request at its best pickup and dropoff
+positions in any vehicle, until gamma requests are served. Every feasible
+(pickup position, dropoff position, vehicle) triple is tried; a vehicle is
+scored by the change of its cost + rho * fairness penalty it causes.
+
+Only requests among the cheapest ones still needed for gamma are candidates,
+so regret cannot pull in requests that are expensive to serve at all.
+
+With request profits the cost is net of the profit: requests are inserted
+until gamma, or the minimum profit, is covered and then as long as some
+request's profit exceeds its insertion cost.
+*/
+pub struct InsertionConstruction<'a> {
+    instance: &'a Instance,
+    strategy: InsertionStrategy,
+}
+
+impl<'a> InsertionConstruction<'a> {
+    pub fn new(instance: &'a Instance, strategy: InsertionStrategy) -> Self {
+        Self { instance, strategy }
+    }
+
+    pub fn cheapest(instance: &'a Instance) -> Self {
+        Self::new(instance, InsertionStrategy::Cheapest)
+    }
+
+    pub fn regret(instance: &'a Instance, k: usize) -> Self {
+        Self::new(instance, InsertionStrategy::Regret(k.max(2)))
+    }
+
+    fn construct_solution(&self) -> Solution {
+        let n_reqs = self.instance.n_reqs();
+        let n_vehicles = self.instance.n_vehicles();
+        let gamma = self.instance.gamma().min(n_reqs);
+        let rho = self.instance.rho();
+        let fairness_measure = self.instance.fairness_measure();
+
+        let mut routes: Vec<Vec<usize>> = vec![Vec::new(); n_vehicles];
+        let mut route_distances = vec![0.0; n_vehicles];
+        let mut unserved = vec![true; n_reqs];
+
+        // Best insertion of every request into every route, refreshed when the route changes
+        let mut best: Vec<Vec<Option<Insertion>>> = (0..n_reqs)
+            .map(|req_id| {
+                routes.iter()
+                    .enumerate()
+                    .map(|(k, route)| self.best_insertion(route, k, req_id, 0))
+                    .collect()
+            })
+            .collect();
+
+        let profits = self.instance.profits();
+        let mut served = Vec::new();
+        loop {
+            let covered = self.instance.coverage_met(&served);
+            if covered && profits.is_none() {
+                break;
+            }
+            let current_penalty = fairness_measure.penalty(&route_distances);
+
+            // Per request: fairness-aware cost of each feasible vehicle, cheapest first,
+            // net of the request's profit
+            let mut candidates: Vec<(usize, Vec<(f64, usize)>)> = Vec::new();
+            for req_id in (0..n_reqs).filter(|&r| unserved[r]) {
+                let profit = profits.map_or(0.0, |profits| profits[req_id]);
+                let mut costs: Vec<(f64, usize)> = best[req_id].iter()
+                    .enumerate()
+                    .filter_map(|(k, insertion)| insertion.map(|ins| (k, ins.delta)))
+                    .map(|(k, delta)| {
+                        let mut distances = route_distances.clone();
+                        distances[k] += delta;
+                        let penalty = fairness_measure.penalty(&distances);
+                        let vehicle = self.instance.vehicle(k);
+                        let fixed_cost = if routes[k].is_empty() { vehicle.fixed_cost } else { 0.0 };
+                        (fixed_cost + vehicle.cost_factor * delta + rho * (penalty - current_penalty) - profit, k)
+                    })
+                    .collect();
+                if costs.is_empty() {
+                    continue;
+                }
+                costs.sort_by(|a, b| a.0.partial_cmp(&b.0).unwrap_or(Ordering::Equal));
+                candidates.push((req_id, costs));
+            }
+
+            candidates.sort_by(|a, b| a.1[0].0.partial_cmp(&b.1[0].0).unwrap_or(Ordering::Equal));
+            if covered {
+                // Beyond the requirement, only requests whose profit pays for their insertion
+                candidates.retain(|(_, costs)| costs[0].0 < 0.0);
+            } else if self.instance.min_profit().is_none() {
+                candidates.truncate(gamma.saturating_sub(served.len()));
+            }
+
+            if candidates.is_empty() {
+                // No remaining request fits into any vehicle, or is worth serving
+                break;
+            }
+
+            let (req_id, costs) = match self.strategy {
+                InsertionStrategy::Cheapest => &candidates[0],
+                InsertionStrategy::Regret(k) => candidates.iter()
+                    .max_by(|a, b| {
+                        regret_key(&a.1, k, n_vehicles)
+                            .partial_cmp(&regret_key(&b.1, k, n_vehicles))
+                            .unwrap_or(Ordering::Equal)
+                            // Prefer the cheaper request on equal regret
+                            .then(b.1[0].0.partial_cmp(&a.1[0].0).unwrap_or(Ordering::Equal))
+                    })
+                    .expect("candidates are not empty"),
+            };
+            let (req_id, vehicle) = (*req_id, costs[0].1);
+            let insertion = best[req_id][vehicle].expect("cost computed from an insertion");
+
+            let route = &mut routes[vehicle];
+            route.insert(insertion.dropoff_pos, self.dropoff_node(req_id));
+            route.insert(insertion.pickup_pos, self.pickup_node(req_id));
+            route_distances[vehicle] += insertion.delta;
+            unserved[req_id] = false;
+            served.push(req_id);
+
+            for other in (0..n_reqs).filter(|&r| unserved[r]) {
+                best[other][vehicle] = self.best_insertion(&routes[vehicle], vehicle, other, 0);
+            }
+        }
+
+        Solution::new(self.instance.clone(), routes)
+    }
+
+    fn pickup_node(&self, req_id: usize) -> usize {
+        req_id + 1
+    }
+
+    fn dropoff_node(&self, req_id: usize) -> usize {
+        req_id + 1 + self.instance.n_reqs()
+    }
+
+    /// Cheapest feasible insertion of a request into a vehicle's route, with
+    /// the pickup no earlier than `first_pos`
+    pub(crate) fn best_insertion(
+        &self,
+        route: &[usize],
+        vehicle_id: usize,
+        req_id: usize,
+        first_pos: usize,
+    ) -> Option<Insertion> {
+        let constrained = self.instance.has_route_constraints();
+        self.best_insertion_where(route, vehicle_id, req_id, first_pos, |candidate| {
+            !constrained || self.instance.route_constraints_feasible(vehicle_id, candidate)
+        })
+    }
+
+    /// Like `best_insertion`, with `feasible` instead of the instance's time
+    /// windows and route limits deciding whether the route with the request
+    /// inserted can be driven. Capacity and LIFO loading are checked here.
+    pub(crate) fn best_insertion_where<F>(
+        &self,
+        route: &[usize],
+        vehicle_id: usize,
+        req_id: usize,
+        first_pos: usize,
+        feasible: F,
+    ) -> Option<Insertion>
+    where
+        F: Fn(&[usize]) -> bool,
+    {
+        let n_reqs = self.instance.n_reqs();
+        let demands = self.instance.demands();
+        let demand = demands[req_id];
+        let fits = |load: usize| self.instance.within_capacity(vehicle_id, load);
+        if !fits(demand) {
+            return None;
+        }
+
+        // Load on board after visiting each stop
+        let mut load_after = Vec::with_capacity(route.len());
+        let mut load = 0usize;
+        for &node in route {
+            if node >= 1 && node <= n_reqs {
+                load += demands[node - 1];
+            } else if node > n_reqs && node <= 2 * n_reqs {
+                load = load.saturating_sub(demands[node - n_reqs - 1]);
+            }
+            load_after.push(load);
+        }
+
+        let pickup = self.pickup_node(req_id);
+        let dropoff = self.dropoff_node(req_id);
+        // Stops before and at a position, `None` for the vehicle's start and end depot
+        let prev = |pos: usize| if pos == 0 { None } else { Some(route[pos - 1]) };
+        let at = |pos: usize| route.get(pos).copied();
+        let d = |a: Option<usize>, b: Option<usize>| match (a, b) {
+            (Some(a), Some(b)) => self.instance.distance(a, b) as f64,
+            (None, Some(b)) => self.instance.start_distance(vehicle_id, b) as f64,
+            (Some(a), None) => self.instance.end_distance(a, vehicle_id) as f64,
+            // Only between the depots of an empty route, which costs nothing
+            (None, None) => 0.0,
+        };
+        let (pickup, dropoff) = (Some(pickup), Some(dropoff));
+
+        let mut best: Option<Insertion> = None;
+        for pickup_pos in first_pos..=route.len() {
+            let load_before = if pickup_pos == 0 { 0 } else { load_after[pickup_pos - 1] };
+            if !fits(load_before + demand) {
+                continue;
+            }
+
+            let (a, b) = (prev(pickup_pos), at(pickup_pos));
+            let pickup_delta = d(a, pickup) + d(pickup, b) - d(a, b);
+
+            // A rear-loaded vehicle must unload the request before anything loaded earlier
+            let lifo_positions = self.instance.vehicle(vehicle_id).lifo
+                .then(|| self.instance.lifo_dropoff_positions(route, pickup_pos));
+
+            for dropoff_pos in pickup_pos..=route.len() {
+                // The request is on board while visiting route[pickup_pos..dropoff_pos],
+                // which must stay within one trip
+                if dropoff_pos > pickup_pos
+                    && (!fits(load_after[dropoff_pos - 1] + demand) || self.instance.is_depot(route[dropoff_pos - 1]))
+                {
+                    break;
+                }
+                if lifo_positions.as_ref().is_some_and(|positions| !positions.contains(&dropoff_pos)) {
+                    continue;
+                }
+
+                let delta = if dropoff_pos == pickup_pos {
+                    d(a, pickup) + d(pickup, dropoff) + d(dropoff, b) - d(a, b)
+                } else {
+                    let (c, e) = (prev(dropoff_pos), at(dropoff_pos));
+                    pickup_delta + d(c, dropoff) + d(dropoff, e) - d(c, e)
+                };
+
+                if best.is_none_or(|current| delta < current.delta)
+                    && feasible(&self.with_request(route, req_id, pickup_pos, dropoff_pos))
+                {
+                    best = Some(Insertion { delta, pickup_pos, dropoff_pos });
+                }
+            }
+        }
+
+        best
+    }
+
+    /// The route with the request's pickup before `route[pickup_pos]` and its
+    /// dropoff before `route[dropoff_pos]`
+    fn with_request(&self, route: &[usize], req_id: usize, pickup_pos: usize, dropoff_pos: usize) -> Vec<usize> {
+        let mut candidate = route.to_vec();
+        candidate.insert(dropoff_pos, self.dropoff_node(req_id));
+        candidate.insert(pickup_pos, self.pickup_node(req_id));
+        candidate
+    }
+}
+
+/// Regret of a request with `costs` sorted ascending: requests that fit into
+/// fewer vehicles come first, then the summed difference to the k - 1 next-best vehicles
+fn regret_key(costs: &[(f64, usize)], k: usize, n_vehicles: usize) -> (usize, f64) {
+    let considered = k.min(n_vehicles);
+    let missing = considered.saturating_sub(costs.len());
+    let regret = costs.iter()
+        .take(considered)
+        .skip(1)
+        .map(|(cost, _)| cost - costs[0].0)
+        .sum();
+    (missing, regret)
+}
+
+impl<'a> Solver for InsertionConstruction<'a> {
+    fn solve(&self) -> Solution {
+        self.construct_solution()
+    }
+
+    fn config_description(&self) -> String {
+        format!("{:?}", self.strategy)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::instance::tests::from_text;
+
+    // Pickups at x = 1, 2 and dropoffs at x = 3, 4; the header is prepended
+    const LINE: &str = "\
+# demands
+1 1
+# request locations
+0 0
+1 0
+2 0
+3 0
+4 0
+";
+
+    fn line(name: &str, header: &str) -> Instance {
+        from_text(name, &format!("{}\n{}", header, LINE))
+    }
+
+    #[test]
+    fn finds_the_cheapest_pickup_and_dropoff_positions() {
+        let instance = line("insertion_positions", "2 1 10 2 0");
+        // Request 0 fits into 0 2 4 0 for free, on the way out as 0 1 2 3 4 0
+        let insertion = InsertionConstruction::cheapest(&instance).best_insertion(&[2, 4], 0, 0, 0);
+        assert_eq!(insertion, Some(Insertion { delta: 0.0, pickup_pos: 0, dropoff_pos: 1 }));
+    }
+
+    #[test]
+    fn insertion_respects_capacity() {
+        // Only one request fits at a time: 0 1 3 2 4 0 has length 10
+        let instance = line("insertion_capacity", "2 1 1 2 0");
+        let construction = InsertionConstruction::cheapest(&instance);
+        let insertion = construction.best_insertion(&[1, 3], 0, 1, 0);
+        assert_eq!(insertion, Some(Insertion { delta: 4.0, pickup_pos: 2, dropoff_pos: 2 }));
+        // Pickups no earlier than `first_pos`
+        assert_eq!(construction.best_insertion(&[1, 3], 0, 1, 3), None);
+    }
+
+    #[test]
+    fn serves_gamma_requests_and_skips_expensive_ones() {
+        let instance = from_text("insertion_gamma", "\
+3 1 10 2 0
+# demands
+1 1 1
+# request locations
+0 0
+1 0
+2 0
+50 0
+3 0
+4 0
+60 0
+");
+        for strategy in [InsertionStrategy::Cheapest, InsertionStrategy::Regret(3)] {
+            let solution = InsertionConstruction::new(&instance, strategy).solve();
+            assert!(solution.is_valid());
+            let mut served = solution.served_requests();
+            served.sort();
+            assert_eq!(served, vec![0, 1]);
+        }
+    }
+
+    #[test]
+    fn fairness_weight_spreads_requests_over_vehicles() {
+        let shortest = InsertionConstruction::cheapest(&line("insertion_rho0", "2 2 10 2 0")).solve();
+        assert_eq!(shortest.total_travel_distance(), 8.0);
+        assert!(shortest.routes[1].is_empty());
+
+        let fair = InsertionConstruction::cheapest(&line("insertion_rho", "2 2 10 2 100")).solve();
+        assert!(fair.routes.iter().all(|route| route.len() == 2));
+        assert!(fair.is_valid());
+    }
+
+    #[test]
+    fn regret_ranks_requests_with_fewer_vehicles_first() {
+        assert_eq!(regret_key(&[(1.0, 0), (5.0, 1), (6.0, 2)], 3, 3), (0, 9.0));
+        assert_eq!(regret_key(&[(1.0, 0), (5.0, 1), (6.0, 2)], 2, 3), (0, 4.0));
+        assert_eq!(regret_key(&[(3.0, 0)], 3, 3), (2, 0.0));
+        assert!(regret_key(&[(3.0, 0)], 2, 3) > regret_key(&[(1.0, 0), (50.0, 1)], 2, 3));
+    }
+}
@@ -4,6 +4,11 @@ use std::io::{BufRead, BufReader};
 use std::path::Path;
 use std::fmt::{self};
 
+use crate::fairness::FairnessMeasure;
+use crate::fleet::{parse_depots, parse_fleet, parse_lifo, Vehicle};
+use crate::stochastic::StochasticDemands;
+use crate::time_windows::{TimeWindow, TimeWindows};
+
 #[derive(Debug, Clone, Copy, PartialEq)]
 pub struct Point {
     pub x: f64,
@@ -23,6 +28,14 @@ pub struct PickupIndex(usize);
 #[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
 pub struct DropoffIndex(usize);
 
+/// A problem instance. Locations are addressed by node index:
+///
+/// - `0` is the depot given with the request locations
+/// - `1..=n_reqs` are the pickups, `n_reqs + 1..=2 * n_reqs` the dropoffs
+/// - `2 * n_reqs + 1..2 * n_reqs + n_depots` are the additional depots
+///
+/// Every vehicle starts and ends at its home depot unless its own start or
+/// end location is given.
 #[derive(Debug, PartialEq, Clone)]
 pub struct Instance {
     name: String,
@@ -35,6 +48,27 @@ pub struct Instance {
     depot: Point,
     pickup_locations: Vec<Point>,
     dropoff_locations: Vec<Point>,
+    fairness: FairnessMeasure,
+    time_windows: Option<TimeWindows>,
+    fleet: Vec<Vehicle>,
+    /// Depots besides `depot`, nodes `2 * n_reqs + 1` onwards
+    extra_depots: Vec<Point>,
+    /// Longest allowed route, depots included
+    max_route_length: Option<f64>,
+    /// Longest allowed distance a request travels on board
+    max_ride_time: Option<f64>,
+    /// Profit of serving each request; unserved profit is added to the objective
+    profits: Option<Vec<f64>>,
+    /// Served profit required instead of serving gamma requests
+    min_profit: Option<f64>,
+    /// Routes end at their last stop instead of returning to a depot
+    open_routes: bool,
+    /// When each request becomes known, for online routing
+    release_times: Option<Vec<f64>>,
+    /// How the realised loads vary around `demands`, for evaluating solutions
+    stochastic_demands: Option<StochasticDemands>,
+    /// Routes may return to a depot with an empty vehicle and start another trip
+    multi_trip: bool,
 }
 
 impl Instance {
@@ -120,6 +154,103 @@ impl Instance {
             dropoff_locations.push(Point { x: parts[0], y: parts[1] });
         }
 
+        // Optional sections after the request locations
+        let mut fairness = FairnessMeasure::default();
+        let mut time_windows = None;
+        let mut fleet = vec![Vehicle::with_capacity(cap); n_vehicles];
+        let mut extra_depots = Vec::new();
+        let mut home_depots = None;
+        let mut lifo_vehicles = None;
+        let mut max_route_length = None;
+        let mut max_ride_time = None;
+        let mut profits = None;
+        let mut min_profit = None;
+        let mut open_routes = false;
+        let mut release_times = None;
+        let mut stochastic_demands = None;
+        let mut multi_trip = false;
+        while let Some(line) = lines.next() {
+            let line = line?;
+            match line.trim() {
+                "# fairness" => {
+                    let measure_line = lines.next().ok_or("Missing fairness measure")??;
+                    fairness = measure_line.parse()?;
+                }
+                "# time windows" => {
+                    time_windows = Some(TimeWindows::parse(&mut lines, n_reqs)?);
+                }
+                "# fleet" => {
+                    fleet = parse_fleet(&mut lines, n_vehicles)?;
+                }
+                "# max route length" => {
+                    let limit_line = lines.next().ok_or("Missing maximum route length")??;
+                    max_route_length = Some(limit_line.trim().parse()?);
+                }
+                "# max ride time" => {
+                    let limit_line = lines.next().ok_or("Missing maximum ride time")??;
+                    max_ride_time = Some(limit_line.trim().parse()?);
+                }
+                "# profits" => {
+                    let profits_line = lines.next().ok_or("Missing profits")??;
+                    let values: Vec<f64> = profits_line
+                        .split_whitespace()
+                        .map(|s| s.parse())
+                        .collect::<Result<_, _>>()?;
+                    if values.len() != n_reqs {
+                        return Err(format!("Expected {} profits, got {}", n_reqs, values.len()).into());
+                    }
+                    if values.iter().any(|&profit| profit < 0.0) {
+                        return Err("Profits must not be negative".into());
+                    }
+                    profits = Some(values);
+                }
+                "# min profit" => {
+                    let profit_line = lines.next().ok_or("Missing minimum profit")??;
+                    min_profit = Some(profit_line.trim().parse()?);
+                }
+                "# release times" => {
+                    let times_line = lines.next().ok_or("Missing release times")??;
+                    let times: Vec<f64> = times_line
+                        .split_whitespace()
+                        .map(|s| s.parse())
+                        .collect::<Result<_, _>>()?;
+                    if times.len() != n_reqs {
+                        return Err(format!("Expected {} release times, got {}", n_reqs, times.len()).into());
+                    }
+                    release_times = Some(times);
+                }
+                "# stochastic demands" => {
+                    stochastic_demands = Some(StochasticDemands::parse(&mut lines, n_reqs)?);
+                }
+                "# multi trip" => {
+                    multi_trip = true;
+                }
+                "# open routes" => {
+                    open_routes = true;
+                }
+                "# lifo" => {
+                    lifo_vehicles = Some(parse_lifo(&mut lines, n_vehicles)?);
+                }
+                "# depots" => {
+                    let (depots, homes) = parse_depots(&mut lines, n_vehicles)?;
+                    extra_depots = depots;
+                    home_depots = Some(homes);
+                }
+                _ => {}
+            }
+        }
+        // Sections may come in any order, so these are applied once the fleet is known
+        if let Some(homes) = home_depots {
+            for (vehicle, home) in fleet.iter_mut().zip(homes) {
+                vehicle.home_depot = home;
+            }
+        }
+        if let Some(lifo) = lifo_vehicles {
+            for (vehicle, lifo) in fleet.iter_mut().zip(lifo) {
+                vehicle.lifo = lifo;
+            }
+        }
+
         Ok(Instance {
             name: instance_name,
             n_reqs,
@@ -131,9 +262,381 @@ impl Instance {
             depot,
             pickup_locations,
             dropoff_locations,
+            fairness,
+            time_windows,
+            fleet,
+            extra_depots,
+            max_route_length,
+            max_ride_time,
+            profits,
+            min_profit,
+            open_routes,
+            release_times,
+            stochastic_demands,
+            multi_trip,
         })
     }
 
+    /// Override the fairness measure used in the objective, e.g. for a single run
+    pub fn with_fairness_measure(mut self, fairness: FairnessMeasure) -> Self {
+        self.fairness = fairness;
+        self
+    }
+
+    /// Replace the time windows, `None` removes them
+    pub fn with_time_windows(mut self, time_windows: Option<TimeWindows>) -> Self {
+        self.time_windows = time_windows;
+        self
+    }
+
+    /// Replace the fleet, one vehicle per route. Fails if a vehicle's home
+    /// depot is not one of the instance's depots.
+    pub fn with_fleet(mut self, fleet: Vec<Vehicle>) -> Result<Self, Box<dyn std::error::Error>> {
+        if let Some((vehicle_id, vehicle)) = fleet.iter().enumerate().find(|(_, v)| v.home_depot >= self.n_depots()) {
+            return Err(format!(
+                "Vehicle {} has home depot {}, but the instance has {} depots",
+                vehicle_id + 1, vehicle.home_depot, self.n_depots()
+            ).into());
+        }
+        self.n_vehicles = fleet.len();
+        self.fleet = fleet;
+        Ok(self)
+    }
+
+    /// The capacity rule for all solvers: whether `load` fits into the vehicle
+    pub fn within_capacity(&self, vehicle_id: usize, load: usize) -> bool {
+        load <= self.fleet[vehicle_id].capacity
+    }
+
+    /// Whether the load never exceeds the vehicle's capacity along a route
+    /// and the vehicle is empty at every depot visit
+    pub fn route_within_capacity(&self, vehicle_id: usize, route: &[usize]) -> bool {
+        let mut load = 0usize;
+        for &node in route {
+            if self.is_depot(node) {
+                if load > 0 {
+                    return false;
+                }
+            } else if node <= self.n_reqs {
+                load += self.demands[node - 1];
+                if !self.within_capacity(vehicle_id, load) {
+                    return false;
+                }
+            } else if node > self.n_reqs && node <= 2 * self.n_reqs {
+                load = load.saturating_sub(self.demands[node - self.n_reqs - 1]);
+            }
+        }
+        true
+    }
+
+    /// Number of depots, at least one
+    pub fn n_depots(&self) -> usize {
+        1 + self.extra_depots.len()
+    }
+
+    /// Node index of a depot: 0 for the first depot, after the dropoffs for the others
+    pub fn depot_node(&self, depot: usize) -> usize {
+        if depot == 0 { 0 } else { 2 * self.n_reqs + depot }
+    }
+
+    /// Whether a node index is one of the depots
+    pub fn is_depot(&self, node: usize) -> bool {
+        node == 0 || node > 2 * self.n_reqs
+    }
+
+    /// Number of node indices: depots, pickups and dropoffs
+    pub fn n_nodes(&self) -> usize {
+        1 + 2 * self.n_reqs + self.extra_depots.len()
+    }
+
+    /// Node index of the vehicle's home depot
+    pub fn home_node(&self, vehicle_id: usize) -> usize {
+        self.depot_node(self.fleet[vehicle_id].home_depot)
+    }
+
+    /// Where the vehicle's route starts
+    pub fn start_location(&self, vehicle_id: usize) -> Point {
+        let vehicle = &self.fleet[vehicle_id];
+        vehicle.start_depot.unwrap_or_else(|| self.location(self.home_node(vehicle_id)))
+    }
+
+    /// Where the vehicle's route ends
+    pub fn end_location(&self, vehicle_id: usize) -> Point {
+        let vehicle = &self.fleet[vehicle_id];
+        vehicle.end_depot.unwrap_or_else(|| self.location(self.home_node(vehicle_id)))
+    }
+
+    /// Distance from the vehicle's start depot to a node
+    pub fn start_distance(&self, vehicle_id: usize, node: usize) -> usize {
+        point_distance(self.start_location(vehicle_id), self.location(node))
+    }
+
+    /// Distance from a node to the vehicle's end depot, zero with open routes
+    pub fn end_distance(&self, node: usize, vehicle_id: usize) -> usize {
+        if self.open_routes {
+            return 0;
+        }
+        point_distance(self.location(node), self.end_location(vehicle_id))
+    }
+
+    /// Length of a vehicle's route from its start depot to its end depot, or
+    /// to its last stop with open routes; zero for a route without stops.
+    /// The one route distance all solvers and the objective use
+    pub fn route_distance(&self, vehicle_id: usize, route: &[usize]) -> f64 {
+        let (Some(&first), Some(&last)) = (route.first(), route.last()) else {
+            return 0.0;
+        };
+        let inner: usize = route.windows(2).map(|pair| self.distance(pair[0], pair[1])).sum();
+        (self.start_distance(vehicle_id, first) + inner + self.end_distance(last, vehicle_id)) as f64
+    }
+
+    /// Time window of a node, always open without time windows
+    fn window(&self, node: usize) -> TimeWindow {
+        self.time_windows.as_ref().map_or(TimeWindow::OPEN, |windows| windows.window(node))
+    }
+
+    /// Window of the vehicle's home depot: its route starts when it opens and
+    /// must end before it closes
+    pub fn depot_window(&self, vehicle_id: usize) -> TimeWindow {
+        self.window(self.home_node(vehicle_id))
+    }
+
+    /// Service start time at every stop of a vehicle's route, or `None` if a
+    /// time window is missed. Travel times equal the distances; the route
+    /// starts when the home depot opens and must return before it closes, or
+    /// with open routes finish its last service before then. Without
+    /// time windows every location is always open.
+    pub fn route_schedule(&self, vehicle_id: usize, route: &[usize]) -> Option<Vec<f64>> {
+        let window = |node: usize| self.window(node);
+        let depot = self.depot_window(vehicle_id);
+
+        let mut schedule = Vec::with_capacity(route.len());
+        let mut time = depot.earliest;
+        let mut prev = None;
+        for &node in route {
+            let tw = window(node);
+            let travel = match prev {
+                Some(prev) => self.distance(prev, node),
+                None => self.start_distance(vehicle_id, node),
+            };
+            let arrival = time + travel as f64;
+            if arrival > tw.latest {
+                return None;
+            }
+            let start = arrival.max(tw.earliest);
+            schedule.push(start);
+            time = start + tw.service;
+            prev = Some(node);
+        }
+        if let Some(last) = prev {
+            if time + self.end_distance(last, vehicle_id) as f64 > depot.latest {
+                return None;
+            }
+        }
+        Some(schedule)
+    }
+
+    /// Whether a vehicle's route meets all time windows, always true without them
+    pub fn route_time_feasible(&self, vehicle_id: usize, route: &[usize]) -> bool {
+        self.time_windows.is_none() || self.route_schedule(vehicle_id, route).is_some()
+    }
+
+    /// Set or remove the maximum route length
+    pub fn with_max_route_length(mut self, max_route_length: Option<f64>) -> Self {
+        self.max_route_length = max_route_length;
+        self
+    }
+
+    /// Set or remove the maximum ride time
+    pub fn with_max_ride_time(mut self, max_ride_time: Option<f64>) -> Self {
+        self.max_ride_time = max_ride_time;
+        self
+    }
+
+    /// Set or remove the request profits and the minimum served profit
+    pub fn with_profits(mut self, profits: Option<Vec<f64>>, min_profit: Option<f64>) -> Self {
+        self.profits = profits;
+        self.min_profit = min_profit;
+        self
+    }
+
+    /// Let routes end at their last stop, or return to their end depot again
+    pub fn with_open_routes(mut self, open_routes: bool) -> Self {
+        self.open_routes = open_routes;
+        self
+    }
+
+    /// Set or remove the release times of the requests
+    pub fn with_release_times(mut self, release_times: Option<Vec<f64>>) -> Self {
+        self.release_times = release_times;
+        self
+    }
+
+    /// Set or remove the demand distributions
+    pub fn with_stochastic_demands(mut self, stochastic_demands: Option<StochasticDemands>) -> Self {
+        self.stochastic_demands = stochastic_demands;
+        self
+    }
+
+    /// Allow or forbid routes of several trips
+    pub fn with_multi_trip(mut self, multi_trip: bool) -> Self {
+        self.multi_trip = multi_trip;
+        self
+    }
+
+    /// Trips of a route: the non-empty stretches between depot visits
+    pub fn trips<'r>(&self, route: &'r [usize]) -> Vec<&'r [usize]> {
+        route.split(|&node| self.is_depot(node))
+            .filter(|trip| !trip.is_empty())
+            .collect()
+    }
+
+    /// A vehicle's route made of the given trips, with a return to its home
+    /// depot between consecutive trips
+    pub fn join_trips(&self, vehicle_id: usize, trips: &[&[usize]]) -> Vec<usize> {
+        let mut route = Vec::new();
+        for (i, trip) in trips.iter().enumerate() {
+            if i > 0 {
+                route.push(self.home_node(vehicle_id));
+            }
+            route.extend_from_slice(trip);
+        }
+        route
+    }
+
+    /// Whether a vehicle's route respects the maximum route length and every
+    /// request it serves the maximum ride time, measured as the distance driven
+    /// between pickup and dropoff
+    pub fn route_limits_feasible(&self, vehicle_id: usize, route: &[usize]) -> bool {
+        if let Some(limit) = self.max_route_length {
+            if self.route_distance(vehicle_id, route) > limit {
+                return false;
+            }
+        }
+
+        if let Some(limit) = self.max_ride_time {
+            // Distance driven since the start of the route, at each pickup on board
+            let mut driven = 0.0;
+            let mut picked_up_at = std::collections::HashMap::new();
+            for (i, &node) in route.iter().enumerate() {
+                if i > 0 {
+                    driven += self.distance(route[i - 1], node) as f64;
+                }
+                if node >= 1 && node <= self.n_reqs {
+                    picked_up_at.insert(node, driven);
+                } else if node > self.n_reqs && node <= 2 * self.n_reqs {
+                    let pickup = node - self.n_reqs;
+                    if picked_up_at.get(&pickup).is_some_and(|&start| driven - start > limit) {
+                        return false;
+                    }
+                }
+            }
+        }
+        true
+    }
+
+    /// Whether a LIFO vehicle only ever drops off the request loaded last;
+    /// always true for other vehicles
+    pub fn route_lifo_feasible(&self, vehicle_id: usize, route: &[usize]) -> bool {
+        if !self.fleet[vehicle_id].lifo {
+            return true;
+        }
+        let mut stack = Vec::new();
+        for &node in route {
+            if node >= 1 && node <= self.n_reqs {
+                stack.push(node);
+            } else if node > self.n_reqs && node <= 2 * self.n_reqs {
+                // A dropoff whose pickup is missing is left to the precedence check
+                if stack.contains(&(node - self.n_reqs)) && stack.pop() != Some(node - self.n_reqs) {
+                    return false;
+                }
+            }
+        }
+        true
+    }
+
+    /// Dropoff positions that keep a LIFO route LIFO when a request is picked
+    /// up before `route[pickup_pos]`: the dropoff goes before `route[q]` for
+    /// each returned `q`, and `route[pickup_pos..q]` loads and unloads
+    /// everything it touches in stack order
+    pub fn lifo_dropoff_positions(&self, route: &[usize], pickup_pos: usize) -> Vec<usize> {
+        let mut positions = vec![pickup_pos];
+        let mut depth = 0usize;
+        for (q, &node) in route.iter().enumerate().skip(pickup_pos) {
+            if node >= 1 && node <= self.n_reqs {
+                depth += 1;
+            } else if node > self.n_reqs && node <= 2 * self.n_reqs {
+                if depth == 0 {
+                    // Unloads a request from below the new one
+                    break;
+                }
+                depth -= 1;
+            }
+            if depth == 0 {
+                positions.push(q + 1);
+            }
+        }
+        positions
+    }
+
+    /// Whether the instance has constraints beyond capacity and precedence
+    /// that depend on the order of a route
+    pub fn has_route_constraints(&self) -> bool {
+        self.time_windows.is_some()
+            || self.max_route_length.is_some()
+            || self.max_ride_time.is_some()
+            || self.fleet.iter().any(|vehicle| vehicle.lifo)
+    }
+
+    /// Time windows, maximum route length, maximum ride time and LIFO loading
+    /// of a vehicle's route
+    pub fn route_constraints_feasible(&self, vehicle_id: usize, route: &[usize]) -> bool {
+        self.route_time_feasible(vehicle_id, route)
+            && self.route_limits_feasible(vehicle_id, route)
+            && self.route_lifo_feasible(vehicle_id, route)
+    }
+
+    /// Profit of serving a request, one per request without profits
+    pub fn profit(&self, req_id: usize) -> f64 {
+        self.profits.as_ref().map_or(1.0, |profits| profits[req_id])
+    }
+
+    /// Whether the served requests cover the requirement: the minimum profit
+    /// if one is given, at least gamma requests otherwise
+    pub fn coverage_met(&self, served: &[usize]) -> bool {
+        self.coverage_shortfall(served) <= 0.0
+    }
+
+    /// How far the served requests are from the requirement, in profit if a
+    /// minimum profit is given and in requests otherwise
+    pub fn coverage_shortfall(&self, served: &[usize]) -> f64 {
+        match self.min_profit {
+            Some(min_profit) => min_profit - served.iter().map(|&r| self.profit(r)).sum::<f64>(),
+            None => self.gamma as f64 - served.len() as f64,
+        }
+    }
+
+    /// Requests taken in the given order until the requirement is covered
+    pub fn requests_to_cover(&self, order: impl IntoIterator<Item = usize>) -> Vec<usize> {
+        let mut selected = Vec::new();
+        for req_id in order {
+            if self.coverage_met(&selected) {
+                break;
+            }
+            selected.push(req_id);
+        }
+        selected
+    }
+
+    /// Summed profit of the requests not served, the prize term of the
+    /// objective; zero without profits
+    pub fn unserved_profit(&self, served: &[usize]) -> f64 {
+        match &self.profits {
+            Some(profits) => profits.iter().sum::<f64>() - served.iter().map(|&r| profits[r]).sum::<f64>(),
+            None => 0.0,
+        }
+    }
+
     pub fn compute_distance_matrix(&self) -> Vec<Vec<usize>> {
         // list of all locations for distance matrix
         let all_locations = self.all_locations();
@@ -150,12 +653,33 @@ impl Instance {
         dist
     }
 
-    /// Get all locations in order: [depot, pickup_0, pickup_1, ..., dropoff_0, dropoff_1, ...]
+    /// Location of a node index: 0 is the depot, then pickups, dropoffs and
+    /// the additional depots
+    pub fn location(&self, index: usize) -> Point {
+        if index == 0 {
+            self.depot
+        } else if index <= self.n_reqs {
+            self.pickup_locations[index - 1]
+        } else if index <= 2 * self.n_reqs {
+            self.dropoff_locations[index - self.n_reqs - 1]
+        } else {
+            self.extra_depots[index - 2 * self.n_reqs - 1]
+        }
+    }
+
+    /// Distance between two node indices, equal to `compute_distance_matrix()[u][v]`
+    /// without building the matrix
+    pub fn distance(&self, u: usize, v: usize) -> usize {
+        point_distance(self.location(u), self.location(v))
+    }
+
+    /// Get all locations in node order: [depot, pickup_0, ..., dropoff_0, ..., depot_1, ...]
     pub fn all_locations(&self) -> Vec<Point> {
-        let mut all = Vec::with_capacity(1 + 2 * self.n_reqs);
+        let mut all = Vec::with_capacity(self.n_nodes());
         all.push(self.depot);
         all.extend(&self.pickup_locations);
         all.extend(&self.dropoff_locations);
+        all.extend(&self.extra_depots);
         all
     }
 
@@ -164,6 +688,7 @@ impl Instance {
             0 => "Depot".to_string(),
             i if i <= self.n_reqs => format!("Pickup-{}", i),
             i if i <= 2 * self.n_reqs => format!("Dropoff-{}", i - self.n_reqs),
+            i if i < self.n_nodes() => format!("Depot-{}", i - 2 * self.n_reqs),
             _ => "Invalid".to_string(),
         }
     }
@@ -171,13 +696,47 @@ impl Instance {
     pub fn name(&self) -> &str { &self.name }
     pub fn n_reqs(&self) -> usize { self.n_reqs }
     pub fn n_vehicles(&self) -> usize { self.n_vehicles }
+    /// Capacity from the header line; a `# fleet` section gives every vehicle its own
     pub fn cap(&self) -> usize { self.cap }
     pub fn gamma(&self) -> usize { self.gamma }
     pub fn rho(&self) -> f64 { self.rho }
     pub fn demands(&self) -> &Vec<usize> { &self.demands }
     pub fn depot(&self) -> Point { self.depot }
+    /// All depots, the first being `depot()`
+    pub fn depots(&self) -> Vec<Point> {
+        std::iter::once(self.depot).chain(self.extra_depots.iter().copied()).collect()
+    }
     pub fn pickup_locations(&self) -> &Vec<Point> { &self.pickup_locations }
     pub fn dropoff_locations(&self) -> &Vec<Point> { &self.dropoff_locations }
+    pub fn fairness_measure(&self) -> FairnessMeasure { self.fairness }
+    pub fn time_windows(&self) -> Option<&TimeWindows> { self.time_windows.as_ref() }
+    pub fn max_route_length(&self) -> Option<f64> { self.max_route_length }
+    pub fn max_ride_time(&self) -> Option<f64> { self.max_ride_time }
+    pub fn profits(&self) -> Option<&[f64]> { self.profits.as_deref() }
+    pub fn min_profit(&self) -> Option<f64> { self.min_profit }
+    pub fn open_routes(&self) -> bool { self.open_routes }
+    pub fn release_times(&self) -> Option<&[f64]> { self.release_times.as_deref() }
+    pub fn stochastic_demands(&self) -> Option<&StochasticDemands> { self.stochastic_demands.as_ref() }
+    pub fn multi_trip(&self) -> bool { self.multi_trip }
+
+    /// When a request becomes known; every request is known from the start
+    /// without release times
+    pub fn release_time(&self, req_id: usize) -> f64 {
+        self.release_times.as_ref().map_or(0.0, |times| times[req_id])
+    }
+    pub fn fleet(&self) -> &[Vehicle] { &self.fleet }
+    pub fn vehicle(&self, vehicle_id: usize) -> &Vehicle { &self.fleet[vehicle_id] }
+
+    /// Capacity of the largest vehicle
+    pub fn max_capacity(&self) -> usize {
+        self.fleet.iter().map(|v| v.capacity).max().unwrap_or(0)
+    }
+
+    /// Whether vehicles differ in capacity, cost or depots
+    pub fn is_heterogeneous(&self) -> bool {
+        self.fleet.windows(2).any(|pair| pair[0] != pair[1])
+            || self.fleet.first().is_some_and(|v| *v != Vehicle::with_capacity(self.cap))
+    }
     
     /// Get pickup location for a specific request
     pub fn pickup_location(&self, request_id: usize) -> Option<Point> {
@@ -198,6 +757,11 @@ impl Instance {
     }
 }
 
+/// Rounded-up euclidean distance, as used for all node distances
+fn point_distance(a: Point, b: Point) -> usize {
+    (a.x - b.x).hypot(a.y - b.y).ceil() as usize
+}
+
 impl fmt::Display for Instance {
     fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
         writeln!(f, "Instance: {}", self.name)?;
@@ -206,6 +770,43 @@ impl fmt::Display for Instance {
         writeln!(f, "  Capacity: {}", self.cap)?;
         writeln!(f, "  Gamma: {}", self.gamma)?;
         writeln!(f, "  Rho: {}", self.rho)?;
+        writeln!(f, "  Fairness measure: {}", self.fairness)?;
+        if let Some(time_windows) = &self.time_windows {
+            writeln!(f, "  Time windows: {}", time_windows)?;
+        }
+        if self.open_routes {
+            writeln!(f, "  Open routes: no return to the depot")?;
+        }
+        if self.multi_trip {
+            writeln!(f, "  Multi-trip: vehicles may return to the depot between trips")?;
+        }
+        if let Some(times) = &self.release_times {
+            let last = times.iter().copied().fold(0.0, f64::max);
+            writeln!(f, "  Release times: up to {}", last)?;
+        }
+        if let Some(stochastic_demands) = &self.stochastic_demands {
+            writeln!(f, "  Stochastic demands: {}", stochastic_demands)?;
+        }
+        if let Some(limit) = self.max_route_length {
+            writeln!(f, "  Max route length: {}", limit)?;
+        }
+        if let Some(limit) = self.max_ride_time {
+            writeln!(f, "  Max ride time: {}", limit)?;
+        }
+        if let Some(profits) = &self.profits {
+            writeln!(f, "  Profits: [{}]", profits.iter()
+                .map(|p| p.to_string())
+                .collect::<Vec<String>>()
+                .join(", "))?;
+        }
+        if let Some(min_profit) = self.min_profit {
+            writeln!(f, "  Min profit: {}", min_profit)?;
+        }
+        if self.is_heterogeneous() {
+            for (k, vehicle) in self.fleet.iter().enumerate() {
+                writeln!(f, "  Vehicle {}: {}", k + 1, vehicle)?;
+            }
+        }
         
         // Demands
         writeln!(f, "  Demands: [{}]", self.demands.iter()
@@ -215,6 +816,9 @@ impl fmt::Display for Instance {
         
         // Locations summary
         writeln!(f, "  Depot: {}", self.depot)?;
+        for (i, depot) in self.extra_depots.iter().enumerate() {
+            writeln!(f, "  Depot-{}: {}", i + 1, depot)?;
+        }
         
         if self.n_reqs > 0 {
             // Pickup locations
@@ -232,4 +836,302 @@ impl fmt::Display for Instance {
         
         Ok(())
     }
-}
\ No newline at end of file
+}
+
+#[cfg(test)]
+pub(crate) mod tests {
+    use super::*;
+    use crate::{BeamSearch, DeterministicConstruction, InsertionConstruction, LocalSearch, LocalSearchConfig, Neighborhood, Solution, Solver};
+
+    /// Load an instance from `text`, written to a file named after the test
+    pub(crate) fn from_text(name: &str, text: &str) -> Instance {
+        let path = std::env::temp_dir().join(format!("scf_pdp_{}_{}.txt", name, std::process::id()));
+        std::fs::write(&path, text).unwrap();
+        let instance = Instance::from_file(path.to_str().unwrap()).unwrap();
+        std::fs::remove_file(&path).unwrap();
+        instance
+    }
+
+    // Two requests on a line: pickups at x = 1, 2 and dropoffs at x = 3, 4
+    const LINE: &str = "\
+2 1 10 2 0
+# demands
+1 1
+# request locations
+0 0
+1 0
+2 0
+3 0
+4 0
+";
+
+    /// `LINE` with the given depot horizon and window of dropoff 1; pickup 2
+    /// opens at 5 and takes 1, all other locations are open
+    fn time_windows(depot: &str, dropoff_0: &str) -> String {
+        format!("{}# time windows\n{}\n0 100\n5 100 1\n{}\n0 100\n", LINE, depot, dropoff_0)
+    }
+
+    #[test]
+    fn vehicles_wait_for_windows_to_open() {
+        let instance = from_text("tw_wait", &time_windows("0 20", "0 100"));
+        // Pickup 2 opens at 5 and takes 1, so dropoff 1 is reached at 7
+        assert_eq!(instance.route_schedule(0, &[1, 2, 3, 4]), Some(vec![1.0, 5.0, 7.0, 8.0]));
+        assert!(Solution::new(instance, vec![vec![1, 2, 3, 4]]).is_valid());
+    }
+
+    #[test]
+    fn late_arrivals_and_returns_miss_their_windows() {
+        let instance = from_text("tw_late", &time_windows("0 20", "0 3"));
+        assert_eq!(instance.route_schedule(0, &[1, 2, 3, 4]), None);
+        assert_eq!(instance.route_schedule(0, &[1, 3, 2, 4]), Some(vec![1.0, 3.0, 5.0, 8.0]));
+        assert!(!Solution::new(instance.clone(), vec![vec![1, 2, 3, 4]]).is_valid());
+
+        // Back at the depot at 12
+        let instance = from_text("tw_horizon", &time_windows("0 11", "0 100"));
+        assert_eq!(instance.route_schedule(0, &[1, 2, 3, 4]), None);
+        assert!(instance.with_open_routes(true).route_schedule(0, &[1, 2, 3, 4]).is_some());
+    }
+
+    #[test]
+    fn insertion_keeps_time_windows() {
+        let instance = from_text("tw_insertion", &time_windows("0 20", "0 3"));
+        let solution = InsertionConstruction::cheapest(&instance).solve();
+        assert!(solution.is_valid());
+        assert_eq!(solution.routes, vec![vec![1, 3, 2, 4]]);
+    }
+
+    #[test]
+    fn routes_start_when_the_home_depot_opens() {
+        let instance = from_text("tw_depot", &time_windows("4 20", "0 100"));
+        assert_eq!(instance.depot_window(0).earliest, 4.0);
+        assert_eq!(instance.route_schedule(0, &[1, 3]), Some(vec![5.0, 7.0]));
+    }
+
+    #[test]
+    fn route_length_includes_the_return_to_the_depot() {
+        // 1 + 1 + 1 + 1 out and 4 back
+        let instance = from_text("route_length", &format!("{}# max route length\n8\n", LINE));
+        assert!(instance.route_limits_feasible(0, &[1, 2, 3, 4]));
+        let instance = instance.with_max_route_length(Some(7.0));
+        assert!(!instance.route_limits_feasible(0, &[1, 2, 3, 4]));
+        assert!(!Solution::new(instance, vec![vec![1, 2, 3, 4]]).is_valid());
+    }
+
+    #[test]
+    fn ride_time_is_distance_from_pickup_to_dropoff() {
+        // In 1 2 3 4 both requests ride 2; in 1 2 4 3 request 1 rides 4
+        let instance = from_text("ride_time", &format!("{}# max ride time\n3\n", LINE));
+        assert!(instance.route_limits_feasible(0, &[1, 2, 3, 4]));
+        assert!(!instance.route_limits_feasible(0, &[1, 2, 4, 3]));
+        assert!(Solution::new(instance.clone(), vec![vec![1, 2, 3, 4]]).is_valid());
+        assert!(!Solution::new(instance, vec![vec![1, 2, 4, 3]]).is_valid());
+    }
+
+    #[test]
+    fn ride_time_limit_is_inclusive() {
+        let instance = from_text("ride_time_inclusive", &format!("{}# max ride time\n4\n", LINE));
+        assert!(instance.route_limits_feasible(0, &[1, 2, 4, 3]));
+    }
+
+    #[test]
+    fn insertion_keeps_ride_times() {
+        let instance = from_text("ride_time_insertion", &format!("{}# max ride time\n3\n", LINE));
+        let solution = InsertionConstruction::cheapest(&instance).solve();
+        assert!(solution.is_valid());
+        assert_eq!(solution.routes, vec![vec![1, 2, 3, 4]]);
+    }
+
+    #[test]
+    fn lifo_vehicles_unload_the_last_request_first() {
+        let instance = from_text("lifo", &format!("{}# lifo\nall\n", LINE));
+        assert!(instance.route_lifo_feasible(0, &[1, 2, 4, 3]));
+        assert!(!instance.route_lifo_feasible(0, &[1, 2, 3, 4]));
+        assert!(instance.route_lifo_feasible(0, &[1, 3, 2, 4]));
+        assert!(!Solution::new(instance.clone(), vec![vec![1, 2, 3, 4]]).is_valid());
+        assert!(Solution::new(instance, vec![vec![1, 2, 4, 3]]).is_valid());
+    }
+
+    #[test]
+    fn other_vehicles_unload_in_any_order() {
+        let instance = from_text("no_lifo", LINE);
+        assert!(instance.route_lifo_feasible(0, &[1, 2, 3, 4]));
+        assert!(instance.route_lifo_feasible(0, &[1, 2, 4, 3]));
+    }
+
+    #[test]
+    fn insertion_keeps_lifo_order() {
+        // 1 2 3 4 and 1 2 4 3 have the same length, only the nested one is LIFO
+        let instance = from_text("lifo_insertion", &format!("{}# lifo\nall\n", LINE));
+        let solution = InsertionConstruction::cheapest(&instance).solve();
+        assert!(solution.is_valid());
+        assert!(instance.route_lifo_feasible(0, &solution.routes[0]));
+    }
+
+    // Request 0 next to depot 0 at x = 0, request 1 next to depot 1 at x = 10
+    const TWO_DEPOTS: &str = "\
+2 2 10 2 0
+# demands
+1 1
+# request locations
+0 0
+1 0
+9 0
+2 0
+8 0
+# depots
+1
+10 0
+0 1
+";
+
+    #[test]
+    fn extra_depots_follow_the_dropoffs() {
+        let instance = from_text("depots", TWO_DEPOTS);
+        assert_eq!(instance.n_nodes(), 6);
+        assert_eq!(instance.depot_node(1), 5);
+        assert_eq!(instance.home_node(1), 5);
+        assert!(instance.is_depot(5) && !instance.is_depot(4));
+        assert_eq!(instance.location(5), Point { x: 10.0, y: 0.0 });
+    }
+
+    #[test]
+    fn routes_start_and_end_at_the_home_depot() {
+        let instance = from_text("depots_distance", TWO_DEPOTS);
+        assert_eq!(instance.route_distance(1, &[2, 4]), 4.0);
+        assert_eq!(instance.route_distance(0, &[2, 4]), 18.0);
+        let solution = Solution::new(instance, vec![vec![1, 3], vec![2, 4]]);
+        assert_eq!(solution.get_route_distances(), vec![4.0, 4.0]);
+    }
+
+    #[test]
+    fn requests_go_to_the_vehicle_of_the_nearest_depot() {
+        let instance = from_text("depots_insertion", TWO_DEPOTS);
+        let solution = InsertionConstruction::cheapest(&instance).solve();
+        assert_eq!(solution.routes, vec![vec![1, 3], vec![2, 4]]);
+
+        // Relocate moves requests between vehicles of different depots
+        let local_search = LocalSearch::new(&instance, LocalSearchConfig {
+            neighborhood: Neighborhood::Relocate,
+            ..LocalSearchConfig::default()
+        });
+        let neighbors = local_search.generate_neighbors(&Solution::new(instance.clone(), vec![vec![1, 3, 2, 4], vec![]]));
+        assert!(neighbors.iter().any(|neighbor| neighbor.routes == vec![vec![1, 3], vec![2, 4]]));
+    }
+
+    /// `LINE` with gamma 1 and the given request profits
+    fn profits(profits: &str) -> String {
+        format!("{}# profits\n{}\n", LINE.replacen("2 1 10 2 0", "2 1 10 1 0", 1), profits)
+    }
+
+    #[test]
+    fn unserved_profit_is_part_of_the_objective() {
+        let instance = from_text("profits_objective", &profits("3 20"));
+        assert_eq!(instance.unserved_profit(&[0]), 20.0);
+        assert_eq!(instance.unserved_profit(&[0, 1]), 0.0);
+        // 6 travelled and 20 left unserved, 8 travelled and 3 left unserved
+        assert_eq!(Solution::new(instance.clone(), vec![vec![1, 3]]).objective_function_value(), 26.0);
+        assert_eq!(Solution::new(instance.clone(), vec![vec![2, 4]]).objective_function_value(), 11.0);
+        assert_eq!(Solution::new(instance, vec![vec![1, 2, 3, 4]]).objective_function_value(), 8.0);
+    }
+
+    #[test]
+    fn min_profit_replaces_gamma() {
+        let instance = from_text("min_profit", &format!("{}# min profit\n10\n", profits("3 20")));
+        assert!(!instance.coverage_met(&[0]));
+        assert_eq!(instance.coverage_shortfall(&[0]), 7.0);
+        assert!(instance.coverage_met(&[1]));
+        assert!(!Solution::new(instance.clone(), vec![vec![1, 3]]).is_valid());
+        assert!(Solution::new(instance, vec![vec![2, 4]]).is_valid());
+    }
+
+    #[test]
+    fn insertion_serves_requests_whose_profit_pays() {
+        // Request 0 rides along for free, it is served once its profit is positive
+        let instance = from_text("profits_insertion", &profits("0 20"));
+        assert_eq!(InsertionConstruction::cheapest(&instance).solve().routes, vec![vec![2, 4]]);
+        let instance = from_text("profits_insertion_paid", &profits("3 20"));
+        let solution = InsertionConstruction::cheapest(&instance).solve();
+        assert!(solution.is_valid());
+        assert_eq!(solution.served_requests().len(), 2);
+    }
+
+    #[test]
+    fn add_drop_keeps_the_min_profit() {
+        let instance = from_text("add_drop", &format!("{}# min profit\n10\n", profits("3 20")));
+        let local_search = LocalSearch::new(&instance, LocalSearchConfig {
+            neighborhood: Neighborhood::AddDrop,
+            ..LocalSearchConfig::default()
+        });
+        // Only request 0 may be dropped
+        let neighbors = local_search.generate_neighbors(&Solution::new(instance.clone(), vec![vec![1, 2, 3, 4]]));
+        let routes: Vec<_> = neighbors.iter().map(|neighbor| neighbor.routes.clone()).collect();
+        assert_eq!(routes, vec![vec![vec![2, 4]]]);
+
+        // and added back
+        let neighbors = local_search.generate_neighbors(&Solution::new(instance.clone(), vec![vec![2, 4]]));
+        assert!(!neighbors.is_empty());
+        assert!(neighbors.iter().all(|neighbor| neighbor.served_requests().len() == 2));
+    }
+
+    #[test]
+    fn open_routes_end_at_the_last_dropoff() {
+        let instance = from_text("open_routes", &format!("{}# open routes\n", LINE));
+        assert!(instance.open_routes());
+        assert_eq!(instance.end_distance(4, 0), 0);
+        assert_eq!(instance.route_distance(0, &[1, 2, 3, 4]), 4.0);
+        assert_eq!(instance.clone().with_open_routes(false).route_distance(0, &[1, 2, 3, 4]), 8.0);
+        let solution = Solution::new(instance, vec![vec![1, 2, 3, 4]]);
+        assert_eq!(solution.get_route_distances(), vec![4.0]);
+        assert_eq!(solution.objective_function_value(), 4.0);
+    }
+
+    #[test]
+    fn fairness_compares_open_route_lengths() {
+        let text = format!("{}# open routes\n", LINE.replacen("2 1 10 2 0", "2 2 10 2 1", 1));
+        let instance = from_text("open_routes_fairness", &text);
+        let solution = Solution::new(instance.clone(), vec![vec![1, 3], vec![2, 4]]);
+        assert_eq!(solution.get_route_distances(), vec![3.0, 4.0]);
+        let penalty = instance.fairness_measure().penalty(&[3.0, 4.0]);
+        assert_eq!(solution.fairness_penalty(), penalty);
+        assert_eq!(solution.objective_function_value(), 7.0 + penalty);
+    }
+
+    // One request is enough: request 0 ends at x = 4, request 1 back at the depot
+    const OPEN_OR_CLOSED: &str = "\
+2 1 10 1 0
+# demands
+1 1
+# request locations
+0 0
+1 0
+3 0
+4 0
+0 0
+";
+
+    #[test]
+    fn constructions_choose_by_the_open_route_length() {
+        // Closed, request 0 costs 8 and request 1 costs 6; open, 4 and 6
+        let closed = from_text("closed_choice", OPEN_OR_CLOSED);
+        let open = from_text("open_choice", &format!("{}# open routes\n", OPEN_OR_CLOSED));
+        for (instance, served) in [(closed, 1), (open, 0)] {
+            let solutions = [
+                InsertionConstruction::cheapest(&instance).solve(),
+                BeamSearch::new(instance.clone()).solve(),
+            ];
+            for solution in solutions {
+                assert!(solution.is_valid());
+                assert_eq!(solution.served_requests(), vec![served]);
+            }
+        }
+    }
+
+    #[test]
+    fn deterministic_construction_scores_open_routes() {
+        // It serves the first request, 4 long without the return
+        let instance = from_text("open_deterministic", &format!("{}# open routes\n", OPEN_OR_CLOSED));
+        let solution = DeterministicConstruction::new(&instance).solve();
+        assert_eq!(solution.routes, vec![vec![1, 3]]);
+        assert_eq!(solution.objective_function_value(), 4.0);
+    }
+}
@@ -1,8 +1,16 @@
 pub mod instance;
 pub mod solution;
+pub mod fairness;
+pub mod time_windows;
+pub mod fleet;
+pub mod stochastic;
+pub mod breakdown;
 pub mod solver;
 pub mod deterministic;
 pub mod random;
+pub mod insertion;
+pub mod savings;
+pub mod cluster;
 pub mod beam_search;
 pub mod executor;
 pub mod local_search;
@@ -10,12 +18,30 @@ pub mod solver_runtime;
 pub mod vnd;
 pub mod grasp;
 pub mod sim_annealing;
+pub mod pareto;
+pub mod dynamic;
+pub mod render;
+pub mod statistics;
+pub mod results;
+pub mod report;
+pub mod compare;
+pub mod bks;
+pub mod benchmark;
+pub mod provenance;
 
 pub use instance::Instance;
 pub use solution::Solution;
-pub use solver::Solver;
+pub use fairness::FairnessMeasure;
+pub use time_windows::{TimeWindow, TimeWindows};
+pub use fleet::Vehicle;
+pub use stochastic::{DemandDistribution, MonteCarloEvaluator, StochasticDemands, StochasticEvaluation};
+pub use breakdown::{ObjectiveBreakdown, RouteBreakdown};
+pub use solver::{Solver, SearchStats};
 pub use deterministic::DeterministicConstruction;
 pub use random::RandomConstruction;
+pub use insertion::{InsertionConstruction, InsertionStrategy};
+pub use savings::SavingsConstruction;
+pub use cluster::{ClusterFirstConstruction, ClusteringMethod};
 pub use beam_search::BeamSearch;
 pub use executor::Executor;
 pub use local_search::LocalSearch;
@@ -29,4 +55,12 @@ pub use grasp::GRASP;
 pub use grasp::GRASPConfig;
 pub use sim_annealing::SimulatedAnnealing;
 pub use sim_annealing::SimulatedAnnealingConfig;
-pub use sim_annealing::SimulatedAnnealingBuilder;
\ No newline at end of file
+pub use sim_annealing::SimulatedAnnealingBuilder;
+pub use pareto::{ParetoFront, ParetoPick, EpsilonConstraint, BiObjectiveConfig};
+pub use dynamic::{OnlineConfig, OnlineRouter, SimulationReport};
+pub use render::{render_svg, write_svg};
+pub use report::write_html_report;
+pub use compare::ComparisonReport;
+pub use bks::BestKnownRegistry;
+pub use benchmark::{Benchmark, BenchmarkResults};
+pub use results::{ResultsError, ResultsWriter};
\ No newline at end of file
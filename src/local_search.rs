@@ -1,11 +1,55 @@
+use std::cell::Cell;
 use std::time::Instant;
-use crate::{DeterministicConstruction, Instance, Solution, Solver};
+use crate::solver::SearchStats;
+use crate::stochastic::MonteCarloEvaluator;
+use crate::{DeterministicConstruction, InsertionConstruction, Instance, Solution, Solver};
+
+/// Longest segment, in stops, that CROSS-exchange moves between routes
+const MAX_CROSS_SEGMENT: usize = 6;
 
 #[derive(Debug, Clone, Copy, PartialEq)]
 pub enum Neighborhood {
-    Relocate,    // Move a request from one route to another
-    Exchange,    // Swap two requests between routes
-    TwoOpt,      // Reverse a segment within a route
+    Relocate,       // Move a request from one route to another
+    Exchange,       // Swap two requests between routes
+    TwoOpt,         // Reverse a segment within a route, keeping precedence
+    OrOpt,          // Shift a single pickup or dropoff within its route
+    TwoOptStar,     // Swap the tails of two routes
+    CrossExchange,  // Swap request-closed segments between two routes
+    BlockRelocate,  // Move a request-closed segment to another position or route
+    AddDrop,        // Serve an unserved request or drop a served one
+    TripRelocate,   // Move a whole trip to another vehicle (multi-trip instances)
+}
+
+impl Neighborhood {
+    pub fn all() -> [Neighborhood; 9] {
+        [
+            Neighborhood::Relocate,
+            Neighborhood::Exchange,
+            Neighborhood::TwoOpt,
+            Neighborhood::OrOpt,
+            Neighborhood::TwoOptStar,
+            Neighborhood::CrossExchange,
+            Neighborhood::BlockRelocate,
+            Neighborhood::AddDrop,
+            Neighborhood::TripRelocate,
+        ]
+    }
+
+    /// All neighborhoods in the order VND explores them by default, cheap
+    /// intra-route moves first
+    pub fn vnd_default() -> [Neighborhood; 9] {
+        [
+            Neighborhood::Relocate,
+            Neighborhood::OrOpt,
+            Neighborhood::Exchange,
+            Neighborhood::TwoOpt,
+            Neighborhood::TwoOptStar,
+            Neighborhood::CrossExchange,
+            Neighborhood::BlockRelocate,
+            Neighborhood::AddDrop,
+            Neighborhood::TripRelocate,
+        ]
+    }
 }
 
 #[derive(Debug, Clone, Copy, PartialEq)]
@@ -45,11 +89,24 @@ impl Default for LocalSearchConfig {
 pub struct LocalSearch<'a> {
     instance: &'a Instance,
     config: LocalSearchConfig,
+    stats: Cell<SearchStats>,
+    // Robust mode: neighbors must keep every vehicle's estimated overload probability at most the bound
+    robustness: Option<(MonteCarloEvaluator<'a>, f64)>,
 }
 
 impl<'a> LocalSearch<'a> {
     pub fn new(instance: &'a Instance, config: LocalSearchConfig) -> Self {
-        Self { instance, config }
+        Self { instance, config, stats: Cell::new(SearchStats::default()), robustness: None }
+    }
+
+    /// Robust mode under stochastic demands: only visit solutions in which no
+    /// vehicle is overloaded with a probability above `max_violation_probability`.
+    /// A start solution that is not robust is repaired by moving requests off
+    /// the overloaded vehicles; if that fails, `solve` returns the empty,
+    /// invalid solution.
+    pub fn with_robustness(mut self, evaluator: MonteCarloEvaluator<'a>, max_violation_probability: f64) -> Self {
+        self.robustness = Some((evaluator, max_violation_probability));
+        self
     }
 
     fn construct_initial_solution(&self) -> Solution {
@@ -57,6 +114,48 @@ impl<'a> LocalSearch<'a> {
         det.solve()
     }
 
+    /// Move requests off every vehicle whose overload probability exceeds the
+    /// robustness bound, each to its cheapest position in a route that stays
+    /// robust, largest booked demand first. None if some vehicle stays overloaded.
+    fn repair_robustness(&self, solution: Solution) -> Option<Solution> {
+        let Some((evaluator, max_probability)) = &self.robustness else {
+            return Some(solution);
+        };
+        let robust = |k: usize, route: &[usize]| evaluator.route_violation_probability(k, route) <= *max_probability;
+        let insertion = InsertionConstruction::cheapest(self.instance);
+        let demands = self.instance.demands();
+        let n_reqs = self.instance.n_reqs();
+
+        let mut routes = solution.routes;
+        for vehicle_id in 0..routes.len() {
+            let mut requests = self.extract_requests_from_route(&routes[vehicle_id]);
+            requests.sort_by_key(|&req_id| std::cmp::Reverse(demands[req_id]));
+            for req_id in requests {
+                if robust(vehicle_id, &routes[vehicle_id]) {
+                    break;
+                }
+                let best = (0..routes.len())
+                    .filter(|&k| k != vehicle_id)
+                    .filter_map(|k| {
+                        let ins = insertion.best_insertion_where(&routes[k], k, req_id, 0, |candidate| {
+                            self.instance.route_constraints_feasible(k, candidate) && robust(k, candidate)
+                        })?;
+                        Some((k, ins))
+                    })
+                    .min_by(|a, b| a.1.delta.total_cmp(&b.1.delta));
+                if let Some((k, ins)) = best {
+                    routes[vehicle_id] = self.remove_request_from_route(&routes[vehicle_id], req_id);
+                    routes[k].insert(ins.dropoff_pos, req_id + 1 + n_reqs);
+                    routes[k].insert(ins.pickup_pos, req_id + 1);
+                }
+            }
+            if !robust(vehicle_id, &routes[vehicle_id]) {
+                return None;
+            }
+        }
+        Some(Solution::new(solution.instance, routes))
+    }
+
     fn relocate_nh(&self, current: &Solution) -> Vec<Solution> {
         let mut neighbors = Vec::new();
         let n_vehicles = current.routes.len();
@@ -77,8 +176,9 @@ impl<'a> LocalSearch<'a> {
                     // Try to insert request into v2 at all possible positions
                     for insert_pos in 0..=current.routes[v2].len() {
                         if let Some(route_v2_with_req) = self.insert_request_into_route(
-                            &current.routes[v2], 
-                            req, 
+                            &current.routes[v2],
+                            v2,
+                            req,
                             insert_pos
                         ) {
                             // Create new solution with modified routes
@@ -119,8 +219,8 @@ impl<'a> LocalSearch<'a> {
                         for pos1 in 0..=route_v2_without_req2.len() {
                             for pos2 in 0..=route_v1_without_req1.len() {
                                 if let (Some(route_v1_with_req2), Some(route_v2_with_req1)) = (
-                                    self.insert_request_into_route(&route_v1_without_req1, req2, pos2),
-                                    self.insert_request_into_route(&route_v2_without_req2, req1, pos1)
+                                    self.insert_request_into_route(&route_v1_without_req1, v1, req2, pos2),
+                                    self.insert_request_into_route(&route_v2_without_req2, v2, req1, pos1)
                                 ) {
                                     // Create new solution with modified routes
                                     let mut new_routes = current.routes.clone();
@@ -143,24 +243,34 @@ impl<'a> LocalSearch<'a> {
         neighbors
     }
 
+    /// Reverses a segment of a route, including segments at either end. A
+    /// reversal that contains both stops of a request would put the dropoff
+    /// first, so such segments are skipped before any solution is built.
     fn two_opt_nh(&self, current: &Solution) -> Vec<Solution> {
         let mut neighbors = Vec::new();
         
         for v in 0..current.routes.len() {
             let route = &current.routes[v];
-            if route.len() < 4 {
-                continue; // Need at least 4 stops for 2-opt to make sense
-            }
+            let partners = self.partner_positions(route);
             
-            for i in 1..route.len() - 2 {
-                for j in i + 1..route.len() - 1 {
+            for i in 0..route.len() {
+                for j in i + 1..route.len() {
+                    // Longer segments starting at i contain the same request too
+                    if partners[j].is_some_and(|partner| partner >= i && partner < j) {
+                        break;
+                    }
+
                     let mut new_route = route[0..i].to_vec();
                     
                     // Reverse the segment between i and j
-                    let reversed_segment: Vec<usize> = route[i..=j].iter().rev().cloned().collect();
-                    new_route.extend(reversed_segment);
+                    new_route.extend(route[i..=j].iter().rev());
                     
                     new_route.extend(&route[j + 1..]);
+
+                    // Reversing pickups and dropoffs of different requests changes the load
+                    if !self.check_route_capacity(v, &new_route) {
+                        continue;
+                    }
                     
                     // Create new solution with modified route
                     let mut new_routes = current.routes.clone();
@@ -178,12 +288,332 @@ impl<'a> LocalSearch<'a> {
         neighbors
     }
 
+    /// Moves one stop to another position of the same route. Only positions that
+    /// keep the pickup before its dropoff are tried.
+    fn or_opt_nh(&self, current: &Solution) -> Vec<Solution> {
+        let mut neighbors = Vec::new();
+
+        for (v, route) in current.routes.iter().enumerate() {
+            let partners = self.partner_positions(route);
+
+            for (i, &partner) in partners.iter().enumerate() {
+                let mut reduced = route.clone();
+                let node = reduced.remove(i);
+
+                for j in 0..=reduced.len() {
+                    if j == i {
+                        continue; // Same route
+                    }
+
+                    // Position of the partner stop once `node` is removed
+                    if let Some(partner) = partner {
+                        let partner = if partner > i { partner - 1 } else { partner };
+                        let is_pickup = node <= self.instance.n_reqs();
+                        if (is_pickup && j > partner) || (!is_pickup && j <= partner) {
+                            continue;
+                        }
+                    }
+
+                    let mut new_route = reduced.clone();
+                    new_route.insert(j, node);
+                    if !self.check_route_capacity(v, &new_route) {
+                        continue;
+                    }
+
+                    let mut new_routes = current.routes.clone();
+                    new_routes[v] = new_route;
+                    let new_solution = Solution::new(current.instance.clone(), new_routes);
+
+                    if new_solution.is_valid() {
+                        neighbors.push(new_solution);
+                    }
+                }
+            }
+        }
+
+        neighbors
+    }
+
+    /// 2-opt*: cuts two routes and exchanges their tails. Only cuts where no
+    /// request is on board are used, so each pickup stays with its dropoff.
+    fn two_opt_star_nh(&self, current: &Solution) -> Vec<Solution> {
+        let mut neighbors = Vec::new();
+        let n_vehicles = current.routes.len();
+
+        let cuts: Vec<Vec<usize>> = current.routes.iter()
+            .map(|route| self.empty_vehicle_cuts(route))
+            .collect();
+
+        for v1 in 0..n_vehicles {
+            for v2 in v1 + 1..n_vehicles {
+                let (route1, route2) = (&current.routes[v1], &current.routes[v2]);
+
+                for &i in &cuts[v1] {
+                    for &j in &cuts[v2] {
+                        // Swapping both full routes or both empty tails changes nothing
+                        if (i == 0 && j == 0) || (i == route1.len() && j == route2.len()) {
+                            continue;
+                        }
+
+                        let mut new_route1 = route1[..i].to_vec();
+                        new_route1.extend(&route2[j..]);
+                        let mut new_route2 = route2[..j].to_vec();
+                        new_route2.extend(&route1[i..]);
+
+                        let mut new_routes = current.routes.clone();
+                        new_routes[v1] = new_route1;
+                        new_routes[v2] = new_route2;
+                        let new_solution = Solution::new(current.instance.clone(), new_routes);
+
+                        if new_solution.is_valid() {
+                            neighbors.push(new_solution);
+                        }
+                    }
+                }
+            }
+        }
+
+        neighbors
+    }
+
+    /// CROSS-exchange: swaps two segments of up to `MAX_CROSS_SEGMENT` stops
+    /// between routes. Segments must contain both stops of each of their
+    /// requests, so precedence holds and only capacity needs checking.
+    fn cross_exchange_nh(&self, current: &Solution) -> Vec<Solution> {
+        let mut neighbors = Vec::new();
+        let n_vehicles = current.routes.len();
+
+        let segments: Vec<Vec<(usize, usize)>> = current.routes.iter()
+            .map(|route| self.closed_segments(route, MAX_CROSS_SEGMENT))
+            .collect();
+
+        for v1 in 0..n_vehicles {
+            for v2 in v1 + 1..n_vehicles {
+                let (route1, route2) = (&current.routes[v1], &current.routes[v2]);
+
+                for &(s1, e1) in &segments[v1] {
+                    for &(s2, e2) in &segments[v2] {
+                        let mut new_route1 = route1[..s1].to_vec();
+                        new_route1.extend(&route2[s2..e2]);
+                        new_route1.extend(&route1[e1..]);
+
+                        let mut new_route2 = route2[..s2].to_vec();
+                        new_route2.extend(&route1[s1..e1]);
+                        new_route2.extend(&route2[e2..]);
+
+                        if !self.check_route_capacity(v1, &new_route1)
+                            || !self.check_route_capacity(v2, &new_route2) {
+                            continue;
+                        }
+
+                        let mut new_routes = current.routes.clone();
+                        new_routes[v1] = new_route1;
+                        new_routes[v2] = new_route2;
+                        let new_solution = Solution::new(current.instance.clone(), new_routes);
+
+                        if new_solution.is_valid() {
+                            neighbors.push(new_solution);
+                        }
+                    }
+                }
+            }
+        }
+
+        neighbors
+    }
+
+    /// Block relocate: moves a segment of up to `MAX_CROSS_SEGMENT` stops that
+    /// contains both stops of each of its requests to any position of any
+    /// route. The block loads and unloads only its own requests, so it keeps
+    /// a LIFO route LIFO wherever it is placed.
+    fn block_relocate_nh(&self, current: &Solution) -> Vec<Solution> {
+        let mut neighbors = Vec::new();
+        let n_vehicles = current.routes.len();
+
+        for v1 in 0..n_vehicles {
+            let route1 = &current.routes[v1];
+            for (s, e) in self.closed_segments(route1, MAX_CROSS_SEGMENT) {
+                let block = &route1[s..e];
+                let mut remaining = route1[..s].to_vec();
+                remaining.extend(&route1[e..]);
+
+                for v2 in 0..n_vehicles {
+                    let target = if v2 == v1 { &remaining } else { &current.routes[v2] };
+                    for pos in 0..=target.len() {
+                        // Putting the block back where it was changes nothing
+                        if v2 == v1 && pos == s {
+                            continue;
+                        }
+                        let mut new_route2 = target[..pos].to_vec();
+                        new_route2.extend(block);
+                        new_route2.extend(&target[pos..]);
+
+                        if !self.check_route_capacity(v2, &new_route2) {
+                            continue;
+                        }
+
+                        let mut new_routes = current.routes.clone();
+                        if v2 != v1 {
+                            new_routes[v1] = remaining.clone();
+                        }
+                        new_routes[v2] = new_route2;
+                        let new_solution = Solution::new(current.instance.clone(), new_routes);
+
+                        if new_solution.is_valid() {
+                            neighbors.push(new_solution);
+                        }
+                    }
+                }
+            }
+        }
+
+        neighbors
+    }
+
+    /// Add/drop: changes which requests are served, the only neighborhood that
+    /// does. Drops keep gamma, or the minimum profit, covered; with request
+    /// profits this trades served profit against distance and fairness.
+    fn add_drop_nh(&self, current: &Solution) -> Vec<Solution> {
+        let mut neighbors = Vec::new();
+        let served = current.served_requests();
+
+        for (v, route) in current.routes.iter().enumerate() {
+            for req in self.extract_requests_from_route(route) {
+                let rest: Vec<usize> = served.iter().copied().filter(|&r| r != req).collect();
+                if !self.instance.coverage_met(&rest) {
+                    continue;
+                }
+                let mut new_routes = current.routes.clone();
+                new_routes[v] = self.remove_request_from_route(route, req);
+                let new_solution = Solution::new(current.instance.clone(), new_routes);
+
+                if new_solution.is_valid() {
+                    neighbors.push(new_solution);
+                }
+            }
+        }
+
+        for req in (0..self.instance.n_reqs()).filter(|r| !served.contains(r)) {
+            for (v, route) in current.routes.iter().enumerate() {
+                for insert_pos in 0..=route.len() {
+                    if let Some(route_with_req) = self.insert_request_into_route(route, v, req, insert_pos) {
+                        let mut new_routes = current.routes.clone();
+                        new_routes[v] = route_with_req;
+                        let new_solution = Solution::new(current.instance.clone(), new_routes);
+
+                        if new_solution.is_valid() {
+                            neighbors.push(new_solution);
+                        }
+                    }
+                }
+            }
+        }
+
+        neighbors
+    }
+
+    /// Trip relocate: moves one trip of a route to any place between the trips
+    /// of another vehicle, which returns to its own depot around it. Only
+    /// multi-trip instances have routes of several trips.
+    fn trip_relocate_nh(&self, current: &Solution) -> Vec<Solution> {
+        let mut neighbors = Vec::new();
+        if !self.instance.multi_trip() {
+            return neighbors;
+        }
+        let n_vehicles = current.routes.len();
+
+        for v1 in 0..n_vehicles {
+            let trips1 = self.instance.trips(&current.routes[v1]);
+            for t in 0..trips1.len() {
+                let mut remaining = trips1.clone();
+                let trip = remaining.remove(t);
+                let new_route1 = self.instance.join_trips(v1, &remaining);
+
+                for v2 in (0..n_vehicles).filter(|&v2| v2 != v1) {
+                    let trips2 = self.instance.trips(&current.routes[v2]);
+                    for pos in 0..=trips2.len() {
+                        let mut new_trips2 = trips2.clone();
+                        new_trips2.insert(pos, trip);
+                        let new_route2 = self.instance.join_trips(v2, &new_trips2);
+
+                        if !self.check_route_capacity(v2, &new_route2) {
+                            continue;
+                        }
+
+                        let mut new_routes = current.routes.clone();
+                        new_routes[v1] = new_route1.clone();
+                        new_routes[v2] = new_route2;
+                        let new_solution = Solution::new(current.instance.clone(), new_routes);
+
+                        if new_solution.is_valid() {
+                            neighbors.push(new_solution);
+                        }
+                    }
+                }
+            }
+        }
+
+        neighbors
+    }
+
+    /// Position of the other stop of each stop's request, if it is in the route
+    fn partner_positions(&self, route: &[usize]) -> Vec<Option<usize>> {
+        let n_reqs = self.instance.n_reqs();
+        let partner_node = |node: usize| if node <= n_reqs { node + n_reqs } else { node - n_reqs };
+        route.iter()
+            .map(|&node| {
+                if self.instance.is_depot(node) {
+                    return None;
+                }
+                route.iter().position(|&other| other == partner_node(node))
+            })
+            .collect()
+    }
+
+    /// Prefix lengths of a route after which no request is on board
+    fn empty_vehicle_cuts(&self, route: &[usize]) -> Vec<usize> {
+        let n_reqs = self.instance.n_reqs();
+        let mut cuts = vec![0];
+        let mut on_board = 0usize;
+        for (i, &node) in route.iter().enumerate() {
+            if self.instance.is_depot(node) {
+                // depot
+            } else if node <= n_reqs {
+                on_board += 1;
+            } else {
+                on_board = on_board.saturating_sub(1);
+            }
+            if on_board == 0 {
+                cuts.push(i + 1);
+            }
+        }
+        cuts
+    }
+
+    /// Non-empty segments `[start, end)` of at most `max_len` stops that
+    /// contain both stops of every request they touch
+    fn closed_segments(&self, route: &[usize], max_len: usize) -> Vec<(usize, usize)> {
+        let partners = self.partner_positions(route);
+        let mut segments = Vec::new();
+        for start in 0..route.len() {
+            for end in start + 1..=(start + max_len).min(route.len()) {
+                let closed = (start..end).all(|pos| {
+                    partners[pos].is_none_or(|partner| partner >= start && partner < end)
+                });
+                if closed {
+                    segments.push((start, end));
+                }
+            }
+        }
+        segments
+    }
+
     fn extract_requests_from_route(&self, route: &[usize]) -> Vec<usize> {
         let mut requests = Vec::new();
         let n_reqs = self.instance.n_reqs();
         
         for &node in route {
-            if node != 0 { // Skip depot
+            if !self.instance.is_depot(node) { // Skip depots
                 if node <= n_reqs {
                     // This is a pickup node, the request ID is node - 1
                     requests.push(node - 1);
@@ -207,11 +637,16 @@ impl<'a> LocalSearch<'a> {
             .collect()
     }
 
-    fn insert_request_into_route(&self, route: &[usize], request_id: usize, position: usize) -> Option<Vec<usize>> {
+    fn insert_request_into_route(
+        &self,
+        route: &[usize],
+        vehicle_id: usize,
+        request_id: usize,
+        position: usize,
+    ) -> Option<Vec<usize>> {
         let pickup_node = request_id + 1;
         let dropoff_node = request_id + 1 + self.instance.n_reqs();
         let _demand = self.instance.demands()[request_id];
-        let capacity = self.instance.cap();
         
         // Check if we can insert both pickup and dropoff while maintaining capacity
         let mut test_route = route.to_vec();
@@ -219,13 +654,21 @@ impl<'a> LocalSearch<'a> {
         // Insert pickup at position
         test_route.insert(position, pickup_node);
         
-        // Find valid positions for dropoff after pickup
-        for dropoff_pos in position + 1..=test_route.len() {
+        // Find valid positions for dropoff after pickup; a rear-loaded vehicle
+        // must unload the request before anything loaded earlier
+        let dropoff_positions: Vec<usize> = if self.instance.vehicle(vehicle_id).lifo {
+            self.instance.lifo_dropoff_positions(route, position).iter().map(|&q| q + 1).collect()
+        } else {
+            (position + 1..=test_route.len()).collect()
+        };
+        for dropoff_pos in dropoff_positions {
             let mut final_route = test_route.clone();
             final_route.insert(dropoff_pos, dropoff_node);
             
-            // Check capacity constraints
-            if self.check_route_capacity(&final_route, capacity) {
+            // Check capacity, time window and route limit constraints
+            if self.check_route_capacity(vehicle_id, &final_route)
+                && self.instance.route_constraints_feasible(vehicle_id, &final_route)
+            {
                 return Some(final_route);
             }
         }
@@ -233,45 +676,37 @@ impl<'a> LocalSearch<'a> {
         None
     }
 
-    fn check_route_capacity(&self, route: &[usize], capacity: usize) -> bool {
-        let mut current_load = 0;
-        let n_reqs = self.instance.n_reqs();
-        let demands = self.instance.demands();
-        
-        for &node in route {
-            if node == 0 {
-                continue; // Depot doesn't affect load
-            }
-            
-            if node <= n_reqs {
-                // Pickup node
-                let req_id = node - 1;
-                current_load += demands[req_id];
-            } else if node <= 2 * n_reqs {
-                // Dropoff node
-                let req_id = node - n_reqs - 1;
-                current_load -= demands[req_id];
-            }
-            
-            if current_load > capacity {
-                return false;
-            }
-        }
-        
-        true
+    fn check_route_capacity(&self, vehicle_id: usize, route: &[usize]) -> bool {
+        self.instance.route_within_capacity(vehicle_id, route)
     }
 
     pub fn generate_neighbors(&self, current: &Solution) -> Vec<Solution> {
-        match self.config.neighborhood {
+        let neighbors = match self.config.neighborhood {
             Neighborhood::Relocate => self.relocate_nh(current),
             Neighborhood::Exchange => self.exchange_nh(current),
             Neighborhood::TwoOpt => self.two_opt_nh(current),
+            Neighborhood::OrOpt => self.or_opt_nh(current),
+            Neighborhood::TwoOptStar => self.two_opt_star_nh(current),
+            Neighborhood::CrossExchange => self.cross_exchange_nh(current),
+            Neighborhood::BlockRelocate => self.block_relocate_nh(current),
+            Neighborhood::AddDrop => self.add_drop_nh(current),
+            Neighborhood::TripRelocate => self.trip_relocate_nh(current),
+        };
+        match &self.robustness {
+            Some((evaluator, max_probability)) => neighbors.into_iter()
+                .filter(|neighbor| evaluator.is_robust(neighbor, *max_probability))
+                .collect(),
+            None => neighbors,
         }
     }
 
     fn search_step(&self, current: &Solution) -> Option<Solution> {
         let neighbors = self.generate_neighbors(current);
         let current_obj = current.objective_function_value();
+
+        let mut stats = self.stats.get();
+        stats.evaluations += neighbors.len();
+        self.stats.set(stats);
         
         match self.config.step_function {
             StepFunction::FirstImprovement => {
@@ -303,9 +738,13 @@ impl<'a> LocalSearch<'a> {
 impl<'a> Solver for LocalSearch<'a> {
     fn solve(&self) -> Solution {
         let start_time = Instant::now();
-        let mut current = self.construct_initial_solution();
+        self.stats.set(SearchStats::default());
+        let Some(mut current) = self.repair_robustness(self.construct_initial_solution()) else {
+            return Solution::empty(self.instance.clone(), self.instance.n_vehicles());
+        };
         let mut best_solution = current.clone();
         let mut best_obj = current.objective_function_value();
+        let mut time_to_best = start_time.elapsed().as_secs_f64();
         
         let mut iterations = 0;
         let mut no_improvement_count = 0;
@@ -321,6 +760,7 @@ impl<'a> Solver for LocalSearch<'a> {
                 if current_obj < best_obj {
                     best_solution = current.clone();
                     best_obj = current_obj;
+                    time_to_best = start_time.elapsed().as_secs_f64();
                     no_improvement_count = 0;
                 } else {
                     no_improvement_count += 1;
@@ -332,7 +772,156 @@ impl<'a> Solver for LocalSearch<'a> {
             
             iterations += 1;
         }
+
+        let mut stats = self.stats.get();
+        stats.iterations = iterations;
+        stats.time_to_best_seconds = Some(time_to_best);
+        self.stats.set(stats);
         
         best_solution
     }
+
+    fn stats(&self) -> SearchStats {
+        self.stats.get()
+    }
+
+    fn config_description(&self) -> String {
+        format!("{:?}", self.config)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::instance::tests::from_text;
+
+    // Pickups at x = 1, 2 and dropoffs at x = 3, 4, two vehicles; sections are appended
+    const LINE: &str = "\
+2 2 10 2 0
+# demands
+1 1
+# request locations
+0 0
+1 0
+2 0
+3 0
+4 0
+";
+
+    fn neighbors(instance: &Instance, neighborhood: Neighborhood, routes: Vec<Vec<usize>>) -> Vec<Vec<Vec<usize>>> {
+        let local_search = LocalSearch::new(instance, LocalSearchConfig { neighborhood, ..LocalSearchConfig::default() });
+        local_search.generate_neighbors(&Solution::new(instance.clone(), routes))
+            .into_iter()
+            .map(|neighbor| neighbor.routes)
+            .collect()
+    }
+
+    /// Every request is dropped off after its pickup on the same route
+    fn precedence_holds(instance: &Instance, routes: &[Vec<usize>]) -> bool {
+        let n_reqs = instance.n_reqs();
+        routes.iter().all(|route| {
+            route.iter().enumerate().all(|(i, &node)| {
+                node <= n_reqs || route[..i].contains(&(node - n_reqs))
+            })
+        })
+    }
+
+    #[test]
+    fn two_opt_reverses_segments_at_both_route_ends() {
+        let instance = from_text("two_opt", LINE);
+        let mut found = neighbors(&instance, Neighborhood::TwoOpt, vec![vec![1, 2, 3, 4], vec![]]);
+        found.sort();
+        // Longer segments hold both stops of a request
+        assert_eq!(found, vec![
+            vec![vec![1, 2, 4, 3], vec![]],
+            vec![vec![1, 3, 2, 4], vec![]],
+            vec![vec![2, 1, 3, 4], vec![]],
+        ]);
+    }
+
+    #[test]
+    fn two_opt_skips_segments_holding_a_whole_request() {
+        let instance = from_text("two_opt_partners", LINE);
+        let local_search = LocalSearch::new(&instance, LocalSearchConfig::default());
+        assert_eq!(local_search.partner_positions(&[1, 3, 2, 4]), vec![Some(1), Some(0), Some(3), Some(2)]);
+        // [1, 3] is one request, so only the segment [3, 2] can be reversed
+        let found = neighbors(&instance, Neighborhood::TwoOpt, vec![vec![1, 3, 2, 4], vec![]]);
+        assert_eq!(found, vec![vec![vec![1, 2, 3, 4], vec![]]]);
+    }
+
+    #[test]
+    fn or_opt_moves_single_stops_within_precedence() {
+        let instance = from_text("or_opt", LINE);
+        let found = neighbors(&instance, Neighborhood::OrOpt, vec![vec![1, 3, 2, 4], vec![]]);
+        assert!(found.contains(&vec![vec![1, 2, 3, 4], vec![]]));
+        assert!(found.contains(&vec![vec![2, 1, 3, 4], vec![]]));
+        assert!(found.iter().all(|routes| precedence_holds(&instance, routes)));
+    }
+
+    #[test]
+    fn two_opt_star_exchanges_route_tails() {
+        let instance = from_text("two_opt_star", LINE);
+        let found = neighbors(&instance, Neighborhood::TwoOptStar, vec![vec![1, 3], vec![2, 4]]);
+        assert!(found.contains(&vec![vec![], vec![2, 4, 1, 3]]));
+        assert!(found.contains(&vec![vec![1, 3, 2, 4], vec![]]));
+        assert!(found.iter().all(|routes| precedence_holds(&instance, routes)));
+    }
+
+    #[test]
+    fn cross_exchange_swaps_closed_segments() {
+        let instance = from_text("cross_exchange", LINE);
+        let found = neighbors(&instance, Neighborhood::CrossExchange, vec![vec![1, 3], vec![2, 4]]);
+        assert_eq!(found, vec![vec![vec![2, 4], vec![1, 3]]]);
+    }
+
+    #[test]
+    fn new_neighborhoods_keep_lifo_routes() {
+        let instance = from_text("lifo_neighborhoods", &format!("{}# lifo\nall\n", LINE));
+        let one_route = vec![vec![1, 2, 4, 3], vec![]];
+        let split = vec![vec![1, 3], vec![2, 4]];
+        for (neighborhood, routes) in [
+            (Neighborhood::OrOpt, one_route.clone()),
+            (Neighborhood::TwoOptStar, split.clone()),
+            (Neighborhood::CrossExchange, split),
+        ] {
+            let found = neighbors(&instance, neighborhood, routes);
+            assert!(!found.is_empty(), "{:?}", neighborhood);
+            for routes in found {
+                assert!(precedence_holds(&instance, &routes), "{:?}", neighborhood);
+                assert!(
+                    routes.iter().enumerate().all(|(k, route)| instance.route_lifo_feasible(k, route)),
+                    "{:?}: {:?}", neighborhood, routes
+                );
+            }
+        }
+        // Without LIFO, or-opt unloads request 0 first
+        let unrestricted = neighbors(&from_text("no_lifo_neighborhoods", LINE), Neighborhood::OrOpt, one_route.clone());
+        assert!(unrestricted.contains(&vec![vec![1, 2, 3, 4], vec![]]));
+        let lifo = neighbors(&instance, Neighborhood::OrOpt, one_route);
+        assert!(!lifo.contains(&vec![vec![1, 2, 3, 4], vec![]]));
+    }
+
+    #[test]
+    fn vnd_default_covers_every_neighborhood() {
+        let mut default = Neighborhood::vnd_default().to_vec();
+        for neighborhood in Neighborhood::all() {
+            assert!(default.contains(&neighborhood), "{:?}", neighborhood);
+        }
+        default.dedup();
+        assert_eq!(default.len(), Neighborhood::all().len());
+    }
+
+    #[test]
+    fn trip_relocate_moves_whole_trips_between_vehicles() {
+        let instance = from_text("trip_relocate", &format!("{}# multi trip\n", LINE.replacen("2 2 10", "2 2 1", 1)));
+        let mut found = neighbors(&instance, Neighborhood::TripRelocate, vec![vec![1, 3, 0, 2, 4], vec![]]);
+        found.sort();
+        assert_eq!(found, vec![
+            vec![vec![1, 3], vec![2, 4]],
+            vec![vec![2, 4], vec![1, 3]],
+        ]);
+
+        let instance = instance.with_multi_trip(false);
+        assert!(neighbors(&instance, Neighborhood::TripRelocate, vec![vec![1, 3], vec![2, 4]]).is_empty());
+    }
 }
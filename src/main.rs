@@ -1,8 +1,12 @@
-use scf_pdp::{BeamSearch, DeterministicConstruction, Instance, RandomConstruction, Solver, LocalSearch, SolverRuntime};
+use scf_pdp::{write_html_report, write_svg, ClusteringMethod, ComparisonReport, FairnessMeasure, Instance, InsertionStrategy, Solution, SolverRuntime};
+use scf_pdp::results::{read_results_csv, split_by_algorithm};
+use scf_pdp::bks::DEFAULT_REGISTRY_PATH;
+use scf_pdp::BestKnownRegistry;
 use scf_pdp::local_search::{LocalSearchConfig, Neighborhood};
-use scf_pdp::vnd::VND;
-use scf_pdp::grasp::{GRASP, GRASPConfig};
-use scf_pdp::sim_annealing::{SimulatedAnnealing, SimulatedAnnealingConfig};
+use scf_pdp::grasp::GRASPConfig;
+use scf_pdp::sim_annealing::SimulatedAnnealingConfig;
+use scf_pdp::pareto::{BiObjectiveConfig, ParetoPick};
+use scf_pdp::dynamic::OnlineConfig;
 use std::fs;
 use std::path::Path;
 
@@ -18,6 +22,8 @@ pub enum InstanceReqSize {
     Size10000,
 }
 
+// Size groups are picked by editing the configuration in main
+#[allow(dead_code)]
 impl InstanceReqSize {
     fn as_str(&self) -> &str {
         match self {
@@ -78,12 +84,31 @@ impl InstanceReqSize {
 pub struct SolverConfig {
     pub run_deterministic: bool,
     pub run_random: bool,
+    /// Cheapest and regret-3 insertion
+    pub run_insertion: bool,
+    pub run_savings: bool,
+    /// Sweep and k-means cluster-first route-second
+    pub run_cluster_first: bool,
     pub run_beam_search: bool,
     pub run_local_search: bool,
     pub run_vnd: bool,
     pub run_grasp: bool,
     pub run_simulated_annealing: bool,
     pub run_metaheuristic_comparison: bool,
+    pub run_bi_objective: bool,
+    /// Point of each Pareto front saved as the chosen solution
+    pub pareto_pick: Option<ParetoPick>,
+    /// Replay instances online at their request release times
+    pub run_online: bool,
+    /// Bound on each vehicle's overload probability for robust local search on
+    /// instances with stochastic demands; None searches with the booked demands
+    pub max_violation_probability: Option<f64>,
+    /// Overrides the fairness measure of every instance; None keeps the one from the file
+    pub fairness_measure: Option<FairnessMeasure>,
+    /// Continue existing results files, skipping runs they already contain
+    pub resume: bool,
+    /// Every solver runs once per seed; deterministic solvers ignore the seed
+    pub seeds: Vec<u64>,
 }
 
 impl Default for SolverConfig {
@@ -91,12 +116,22 @@ impl Default for SolverConfig {
         Self {
             run_deterministic: true,
             run_random: false,
+            run_insertion: false,
+            run_savings: false,
+            run_cluster_first: false,
             run_beam_search: false,
             run_local_search: false,
             run_vnd: false,
             run_grasp: false,
             run_simulated_annealing: false,
             run_metaheuristic_comparison: false,
+            run_bi_objective: false,
+            pareto_pick: None,
+            run_online: false,
+            max_violation_probability: None,
+            fairness_measure: None,
+            resume: false,
+            seeds: vec![0],
         }
     }
 }
@@ -116,12 +151,18 @@ impl SolverConfig {
         Self {
             run_deterministic: true,
             run_random: true,
+            run_insertion: true,
+            run_savings: true,
+            run_cluster_first: true,
             run_beam_search: true,
             run_local_search: true,
             run_vnd: true,
             run_grasp: true,
             run_simulated_annealing: true,
             run_metaheuristic_comparison: true,
+            run_bi_objective: true,
+            run_online: true,
+            ..Default::default()
         }
     }
 
@@ -172,62 +213,104 @@ fn load_instances_from_folder(size: &InstanceReqSize, dataset_type: &str) -> Res
     Ok(instances)
 }
 
-fn run_solvers_on_instances(instances: Vec<Instance>, config: &SolverConfig, dataset_type: &str) {
+fn run_solvers_on_instances(instances: Vec<Instance>, config: &SolverConfig, dataset_type: &str) -> Result<(), Box<dyn std::error::Error>> {
     if instances.is_empty() {
-        return;
+        return Ok(());
     }
 
-    let runtime = SolverRuntime::new(instances);
+    let instances = match config.fairness_measure {
+        Some(measure) => instances.into_iter()
+            .map(|instance| instance.with_fairness_measure(measure))
+            .collect(),
+        None => instances,
+    };
+
+    let runtime = SolverRuntime::new(instances)
+        .with_resume(config.resume)
+        .with_seeds(config.seeds.clone())
+        .with_robustness(config.max_violation_probability);
     
     if config.run_deterministic {
         println!("\n=== Running Deterministic Construction ({}) ===", dataset_type);
-        let _det_solutions = runtime.run_deterministic();
+        let _det_solutions = runtime.run_deterministic()?;
     }
     
     if config.run_random {
         println!("\n=== Running Random Construction ({}) ===", dataset_type);
-        let _rand_solutions = runtime.run_random();
+        let _rand_solutions = runtime.run_random()?;
+    }
+    
+    if config.run_insertion {
+        println!("\n=== Running Cheapest Insertion ({}) ===", dataset_type);
+        let _cheapest_solutions = runtime.run_insertion(InsertionStrategy::Cheapest)?;
+
+        println!("\n=== Running Regret-3 Insertion ({}) ===", dataset_type);
+        let _regret_solutions = runtime.run_insertion(InsertionStrategy::Regret(3))?;
+    }
+    
+    if config.run_savings {
+        println!("\n=== Running Savings Construction ({}) ===", dataset_type);
+        let _savings_solutions = runtime.run_savings()?;
+    }
+    
+    if config.run_cluster_first {
+        println!("\n=== Running Cluster-First Sweep ({}) ===", dataset_type);
+        let _sweep_solutions = runtime.run_cluster_first(ClusteringMethod::Sweep)?;
+
+        println!("\n=== Running Cluster-First K-Means ({}) ===", dataset_type);
+        let _kmeans_solutions = runtime.run_cluster_first(ClusteringMethod::KMeans { iterations: 20 })?;
     }
     
     if config.run_beam_search {
         println!("\n=== Running Beam Search ({}) ===", dataset_type);
-        let _beam_solutions = runtime.run_beam_search(20, 150);
+        let _beam_solutions = runtime.run_beam_search(20, 150)?;
     }
     
     if config.run_local_search {
         println!("\n=== Running Local Search ({}) ===", dataset_type);
-        let _local_solutions = runtime.run_local_search(LocalSearchConfig::default());
+        let _local_solutions = runtime.run_local_search(LocalSearchConfig::default())?;
     }
     
     if config.run_vnd {
         println!("\n=== Running VND ({}) ===", dataset_type);
-        let neighborhoods = vec![
-            Neighborhood::Relocate,
-            Neighborhood::Exchange,
-            Neighborhood::TwoOpt,
-        ];
-        let _vnd_solutions = runtime.run_vnd(&neighborhoods, 100);
+        let _vnd_solutions = runtime.run_vnd(&Neighborhood::vnd_default(), 100)?;
     }
     
     if config.run_grasp {
         println!("\n=== Running GRASP ({}) ===", dataset_type);
         let grasp_config = GRASPConfig::default();
-        let _grasp_solutions = runtime.run_grasp(grasp_config);
+        let _grasp_solutions = runtime.run_grasp(grasp_config)?;
     }
     
     if config.run_simulated_annealing {
         println!("\n=== Running Simulated Annealing ({}) ===", dataset_type);
         let sa_config = SimulatedAnnealingConfig::default();
-        let _sa_solutions = runtime.run_simulated_annealing(sa_config);
+        let _sa_solutions = runtime.run_simulated_annealing(sa_config)?;
     }
     
     if config.run_metaheuristic_comparison {
         println!("\n=== Running Metaheuristic Comparison ({}) ===", dataset_type);
-        let _comparison_results = runtime.run_metaheuristic_comparison();
+        let _comparison_results = runtime.run_metaheuristic_comparison()?;
+    }
+
+    if config.run_bi_objective {
+        println!("\n=== Running Bi-objective Epsilon-Constraint ({}) ===", dataset_type);
+        let bi_objective_config = BiObjectiveConfig {
+            pick: config.pareto_pick,
+            ..BiObjectiveConfig::default()
+        };
+        let _fronts = runtime.run_bi_objective(bi_objective_config);
     }
+
+    if config.run_online {
+        println!("\n=== Running Online Simulation ({}) ===", dataset_type);
+        let _reports = runtime.run_online_simulation(OnlineConfig::default());
+    }
+
+    Ok(())
 }
 
-fn process_size(size: InstanceReqSize, config: &SolverConfig, process_train: bool, process_test: bool) -> Result<(), Box<dyn std::error::Error>> {
+fn process_size(size: InstanceReqSize, config: &SolverConfig, process_train: bool, process_test: bool, process_competition: bool) -> Result<(), Box<dyn std::error::Error>> {
     println!("\n{}", "=".repeat(80));
     println!("Processing instances of size: {}", size.as_str());
     println!("{}\n", "=".repeat(80));
@@ -245,8 +328,14 @@ fn process_size(size: InstanceReqSize, config: &SolverConfig, process_train: boo
         Vec::new()
     };
     
+    let competition_instances = if process_competition {
+        load_instances_from_folder(&size, "competition")?
+    } else {
+        Vec::new()
+    };
+    
     // Skip if no instances found
-    if train_instances.is_empty() && test_instances.is_empty() {
+    if train_instances.is_empty() && test_instances.is_empty() && competition_instances.is_empty() {
         println!("No instances found for size {}, skipping...\n", size.as_str());
         return Ok(());
     }
@@ -254,27 +343,209 @@ fn process_size(size: InstanceReqSize, config: &SolverConfig, process_train: boo
     // Process train instances if available
     if !train_instances.is_empty() {
         println!("\n--- Processing TRAIN instances (size: {}) ---", size.as_str());
-        run_solvers_on_instances(train_instances, config, "train");
+        run_solvers_on_instances(train_instances, config, "train")?;
     }
     
     // Process test instances if available
     if !test_instances.is_empty() {
         println!("\n--- Processing TEST instances (size: {}) ---", size.as_str());
-        run_solvers_on_instances(test_instances, config, "test");
+        run_solvers_on_instances(test_instances, config, "test")?;
     }
     
+    // Process competition instances if available
+    if !competition_instances.is_empty() {
+        println!("\n--- Processing COMPETITION instances (size: {}) ---", size.as_str());
+        run_solvers_on_instances(competition_instances, config, "competition")?;
+    }
+    
+    Ok(())
+}
+
+/// `render <instance> [solution] [--svg <output>]`
+///
+/// Without a solution file only the instance is drawn, with all requests unserved.
+/// The output defaults to the solution (or instance) path with an `.svg` extension.
+fn run_render(args: &[String]) -> Result<(), Box<dyn std::error::Error>> {
+    let mut output = None;
+    let mut positional = Vec::new();
+    let mut iter = args.iter();
+    while let Some(arg) = iter.next() {
+        match arg.as_str() {
+            "--svg" => output = Some(iter.next().ok_or("--svg expects an output file")?.clone()),
+            _ => positional.push(arg.clone()),
+        }
+    }
+
+    let instance_file = positional.first()
+        .ok_or("Usage: render <instance> [solution] [--svg <output>]")?;
+    let instance = Instance::from_file(instance_file)?;
+
+    let solution = match positional.get(1) {
+        Some(solution_file) => Solution::from_file(instance, solution_file)?,
+        None => {
+            let n_vehicles = instance.n_vehicles();
+            Solution::empty(instance, n_vehicles)
+        }
+    };
+
+    let output = output.unwrap_or_else(|| {
+        let source = positional.get(1).unwrap_or(instance_file);
+        Path::new(source).with_extension("svg").to_string_lossy().into_owned()
+    });
+
+    write_svg(&solution, &output)?;
+    println!("SVG written to: {}", output);
+    Ok(())
+}
+
+/// `report [results_dir] [--output <file>]`
+///
+/// Summarizes every `<results_dir>/<size>/<solver>.csv` in a self-contained HTML page,
+/// written to `<results_dir>/report.html` unless an output file is given.
+fn run_report(args: &[String]) -> Result<(), Box<dyn std::error::Error>> {
+    let mut output = None;
+    let mut results_dir = "results".to_string();
+    let mut iter = args.iter();
+    while let Some(arg) = iter.next() {
+        match arg.as_str() {
+            "--output" => output = Some(iter.next().ok_or("--output expects a file")?.clone()),
+            _ => results_dir = arg.clone(),
+        }
+    }
+
+    let output = output.unwrap_or_else(|| {
+        Path::new(&results_dir).join("report.html").to_string_lossy().into_owned()
+    });
+
+    write_html_report(&results_dir, &output)?;
+    println!("Report written to: {}", output);
+    Ok(())
+}
+
+/// `compare [--results-dir <dir>] [--size <size>] [--output <file>] <algorithm|file.csv>...`
+///
+/// Algorithms are read from `<results_dir>/<size>/<algorithm>.csv`, arguments ending
+/// in `.csv` are read directly; benchmark tables contribute all of their algorithms. Prints RPD, win/tie/loss counts, Wilcoxon tests and
+/// for three or more algorithms the Friedman test with Nemenyi post-hoc.
+fn run_compare(args: &[String]) -> Result<(), Box<dyn std::error::Error>> {
+    let usage = "Usage: compare [--results-dir <dir>] [--size <size>] [--output <file>] <algorithm|file.csv>...";
+    let mut results_dir = "results".to_string();
+    let mut size = None;
+    let mut output = None;
+    let mut sources = Vec::new();
+    let mut iter = args.iter();
+    while let Some(arg) = iter.next() {
+        match arg.as_str() {
+            "--results-dir" => results_dir = iter.next().ok_or(usage)?.clone(),
+            "--size" => size = Some(iter.next().ok_or(usage)?.clone()),
+            "--output" => output = Some(iter.next().ok_or(usage)?.clone()),
+            _ => sources.push(arg.clone()),
+        }
+    }
+
+    let mut results = Vec::new();
+    for source in &sources {
+        let (name, path) = if source.ends_with(".csv") {
+            let name = Path::new(source)
+                .file_stem()
+                .and_then(|s| s.to_str())
+                .unwrap_or(source)
+                .to_string();
+            (name, source.clone())
+        } else {
+            let size = size.as_ref().ok_or("--size is required when comparing by algorithm name")?;
+            let path = Path::new(&results_dir).join(size).join(format!("{}.csv", source));
+            (source.clone(), path.to_string_lossy().into_owned())
+        };
+        results.extend(split_by_algorithm(&name, read_results_csv(&path)?));
+    }
+
+    // Objectives are only comparable under one fairness measure
+    let measures: Vec<&str> = results.iter()
+        .flat_map(|(_, rows)| rows.iter().map(|row| row.fairness_measure.as_str()))
+        .collect();
+    let measure: FairnessMeasure = match measures.first() {
+        Some(first) if measures.iter().any(|m| m != first) => {
+            return Err("Cannot compare objectives computed under different fairness measures".into());
+        }
+        Some(first) => first.parse()?,
+        None => FairnessMeasure::default(),
+    };
+
+    // RPD is measured against the best known values under that measure where available
+    let best_known = BestKnownRegistry::load(DEFAULT_REGISTRY_PATH)?.objectives(measure);
+    let report = ComparisonReport::new(&results, Some(&best_known))?;
+    print!("{}", report);
+
+    if let Some(output) = output {
+        fs::write(&output, report.to_csv())?;
+        println!("Comparison written to: {}", output);
+    }
     Ok(())
 }
 
 fn main() -> Result<(), Box<dyn std::error::Error>> {
+    // Subcommands; without one the batch configured below is run.
+    // `--resume` runs the batch but keeps existing results and skips finished runs,
+    // `--pareto-pick <min_fairness:x|max_distance:x|rho:x>` runs the bi-objective
+    // mode and saves the chosen point of every front, `--fairness <measure>`
+    // overrides the fairness measure of every instance, `--robust <probability>`
+    // runs local search bounding every vehicle's overload probability on
+    // instances with stochastic demands.
+    let args: Vec<String> = std::env::args().skip(1).collect();
+    match args.first().map(String::as_str) {
+        Some("render") => return run_render(&args[1..]),
+        Some("report") => return run_report(&args[1..]),
+        Some("compare") => return run_compare(&args[1..]),
+        _ => {}
+    }
+
+    let mut resume = false;
+    let mut pareto_pick = None;
+    let mut fairness_measure = None;
+    let mut max_violation_probability = None;
+    let mut iter = args.iter();
+    while let Some(arg) = iter.next() {
+        match arg.as_str() {
+            "--resume" => resume = true,
+            "--pareto-pick" => {
+                let pick = iter.next().ok_or("--pareto-pick expects min_fairness:x, max_distance:x or rho:x")?;
+                pareto_pick = Some(pick.parse::<ParetoPick>()?);
+            }
+            "--fairness" => {
+                let measure = iter.next().ok_or("--fairness expects a fairness measure, e.g. jain or gini")?;
+                fairness_measure = Some(measure.parse::<FairnessMeasure>()?);
+            }
+            "--robust" => {
+                let probability: f64 = iter.next()
+                    .ok_or("--robust expects a probability between 0 and 1")?
+                    .parse()?;
+                if !(0.0..=1.0).contains(&probability) {
+                    return Err(format!("Overload probability must be between 0 and 1, got {}", probability).into());
+                }
+                max_violation_probability = Some(probability);
+            }
+            other => return Err(format!("Unknown command: {}", other).into()),
+        }
+    }
+
     // ========== CONFIGURATION ==========
     
     let sizes = InstanceReqSize::small();
-    let solver_config = SolverConfig::default();
+    let solver_config = SolverConfig {
+        resume,
+        run_bi_objective: pareto_pick.is_some(),
+        pareto_pick,
+        run_local_search: max_violation_probability.is_some(),
+        max_violation_probability,
+        fairness_measure,
+        ..SolverConfig::default()
+    };
     
-    // Choose whether to process train and/or test sets
+    // Choose whether to process train, test and/or competition sets
     let process_train = true;
     let process_test = false;
+    let process_competition = false;
     
     // ===================================
     
@@ -283,17 +554,35 @@ fn main() -> Result<(), Box<dyn std::error::Error>> {
     println!("Solvers enabled:");
     println!("  - Deterministic: {}", solver_config.run_deterministic);
     println!("  - Random: {}", solver_config.run_random);
+    println!("  - Insertion: {}", solver_config.run_insertion);
+    println!("  - Savings: {}", solver_config.run_savings);
+    println!("  - Cluster-First: {}", solver_config.run_cluster_first);
     println!("  - Beam Search: {}", solver_config.run_beam_search);
     println!("  - Local Search: {}", solver_config.run_local_search);
+    if let Some(probability) = solver_config.max_violation_probability {
+        println!("    Robust, max overload probability: {}", probability);
+    }
     println!("  - VND: {}", solver_config.run_vnd);
     println!("  - GRASP: {}", solver_config.run_grasp);
     println!("  - Simulated Annealing: {}", solver_config.run_simulated_annealing);
     println!("  - Metaheuristic Comparison: {}", solver_config.run_metaheuristic_comparison);
+    println!("  - Bi-objective: {}", solver_config.run_bi_objective);
+    if let Some(pick) = solver_config.pareto_pick {
+        println!("    Pareto pick: {}", pick);
+    }
+    println!("  - Online simulation: {}", solver_config.run_online);
+    println!("Seeds: {:?}", solver_config.seeds);
+    if solver_config.resume {
+        println!("Resuming: existing results are kept");
+    }
+    if let Some(measure) = solver_config.fairness_measure {
+        println!("Fairness measure: {}", measure);
+    }
     println!();
     
     // Process all sizes
     for size in sizes {
-        if let Err(e) = process_size(size, &solver_config, process_train, process_test) {
+        if let Err(e) = process_size(size, &solver_config, process_train, process_test, process_competition) {
             eprintln!("Error processing size {}: {}", size.as_str(), e);
             eprintln!("Continuing with next size...\n");
         }
@@ -0,0 +1,381 @@
+use std::fmt;
+use std::fs::File;
+use std::io::Write;
+use std::str::FromStr;
+use std::time::{Duration, Instant};
+
+use crate::{DeterministicConstruction, Instance, LocalSearch, LocalSearchConfig, Neighborhood, RandomConstruction, Solution, Solver, StepFunction, AcceptanceCriterion};
+
+/// Non-dominated set of solutions trading total travel distance (minimized)
+/// against Jain fairness (maximized)
+#[derive(Debug, Clone, Default)]
+pub struct ParetoFront {
+    // (total distance, jain fairness, solution), kept sorted by distance
+    points: Vec<(f64, f64, Solution)>,
+}
+
+impl ParetoFront {
+    pub fn new() -> Self {
+        Self { points: Vec::new() }
+    }
+
+    /// Insert a solution if it is valid and not dominated.
+    /// Returns true if the front changed.
+    pub fn insert(&mut self, solution: Solution) -> bool {
+        if !solution.is_valid() {
+            return false;
+        }
+
+        let distance = solution.total_travel_distance();
+        let fairness = solution.jain_fairness();
+
+        let dominated = self.points.iter().any(|(d, f, _)| {
+            *d <= distance && *f >= fairness
+        });
+        if dominated {
+            return false;
+        }
+
+        // Remove points the new solution dominates
+        self.points.retain(|(d, f, _)| !(distance <= *d && fairness >= *f));
+
+        let pos = self.points.iter()
+            .position(|(d, _, _)| *d > distance)
+            .unwrap_or(self.points.len());
+        self.points.insert(pos, (distance, fairness, solution));
+        true
+    }
+
+    pub fn len(&self) -> usize {
+        self.points.len()
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.points.is_empty()
+    }
+
+    /// Solutions ordered by increasing distance (and therefore increasing fairness)
+    pub fn solutions(&self) -> Vec<&Solution> {
+        self.points.iter().map(|(_, _, s)| s).collect()
+    }
+
+    /// (total distance, jain fairness) of every point, ordered by distance
+    pub fn objectives(&self) -> Vec<(f64, f64)> {
+        self.points.iter().map(|(d, f, _)| (*d, *f)).collect()
+    }
+
+    /// Shortest solution whose Jain fairness is at least `min_fairness`
+    pub fn pick_min_fairness(&self, min_fairness: f64) -> Option<&Solution> {
+        self.points.iter()
+            .find(|(_, f, _)| *f >= min_fairness)
+            .map(|(_, _, s)| s)
+    }
+
+    /// Fairest solution whose total distance does not exceed `max_distance`
+    pub fn pick_max_distance(&self, max_distance: f64) -> Option<&Solution> {
+        self.points.iter()
+            .rev()
+            .find(|(d, _, _)| *d <= max_distance)
+            .map(|(_, _, s)| s)
+    }
+
+    /// Best point for a given rho under the scalarized objective
+    /// `distance + rho * (1 - jain)`, chosen after seeing the front
+    pub fn pick_by_rho(&self, rho: f64) -> Option<&Solution> {
+        self.points.iter()
+            .min_by(|a, b| {
+                let obj_a = a.0 + rho * (1.0 - a.1);
+                let obj_b = b.0 + rho * (1.0 - b.1);
+                obj_a.partial_cmp(&obj_b).unwrap_or(std::cmp::Ordering::Equal)
+            })
+            .map(|(_, _, s)| s)
+    }
+
+    pub fn pick(&self, pick: ParetoPick) -> Option<&Solution> {
+        match pick {
+            ParetoPick::MinFairness(min_fairness) => self.pick_min_fairness(min_fairness),
+            ParetoPick::MaxDistance(max_distance) => self.pick_max_distance(max_distance),
+            ParetoPick::Rho(rho) => self.pick_by_rho(rho),
+        }
+    }
+
+    /// Write the front as CSV, one row per non-dominated point. The objective
+    /// is the instance's, under the fairness measure named in its own column.
+    pub fn to_csv(&self, filename: &str) -> Result<(), Box<dyn std::error::Error>> {
+        let mut file = File::create(filename)?;
+        writeln!(file, "point,total_distance,jain_fairness,fairness_measure,objective_value")?;
+        for (i, (distance, fairness, solution)) in self.points.iter().enumerate() {
+            writeln!(
+                file,
+                "{},{:.6},{:.6},{},{:.6}",
+                i,
+                distance,
+                fairness,
+                solution.instance.fairness_measure(),
+                solution.objective_function_value()
+            )?;
+        }
+        Ok(())
+    }
+}
+
+/// How a single solution is chosen from a Pareto front
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum ParetoPick {
+    /// `ParetoFront::pick_min_fairness`
+    MinFairness(f64),
+    /// `ParetoFront::pick_max_distance`
+    MaxDistance(f64),
+    /// `ParetoFront::pick_by_rho`
+    Rho(f64),
+}
+
+impl fmt::Display for ParetoPick {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        match self {
+            ParetoPick::MinFairness(value) => write!(f, "min_fairness:{}", value),
+            ParetoPick::MaxDistance(value) => write!(f, "max_distance:{}", value),
+            ParetoPick::Rho(value) => write!(f, "rho:{}", value),
+        }
+    }
+}
+
+impl FromStr for ParetoPick {
+    type Err = String;
+
+    /// Parses the names produced by `Display`, e.g. `min_fairness:0.9` or `rho:100`
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        let (kind, value) = s.trim().split_once(':')
+            .ok_or(format!("Expected <kind>:<value>, got {}", s))?;
+        let value: f64 = value.parse()
+            .map_err(|_| format!("Invalid value in {}", s))?;
+        match kind.to_lowercase().as_str() {
+            "min_fairness" => Ok(ParetoPick::MinFairness(value)),
+            "max_distance" => Ok(ParetoPick::MaxDistance(value)),
+            "rho" => Ok(ParetoPick::Rho(value)),
+            other => Err(format!("Unknown Pareto pick: {}", other)),
+        }
+    }
+}
+
+/// Configuration for the epsilon-constraint bi-objective search
+#[derive(Debug, Clone)]
+pub struct BiObjectiveConfig {
+    /// Number of fairness thresholds between 1/k and 1
+    pub epsilon_steps: usize,
+    pub neighborhoods: Vec<Neighborhood>,
+    /// Maximum descent steps per threshold
+    pub max_iterations: usize,
+    /// Number of random constructions used to seed the front
+    pub random_starts: usize,
+    /// Point of every front to save as the chosen solution, if any
+    pub pick: Option<ParetoPick>,
+    /// Time budget of the whole front; the descent stops once it is used up
+    pub time_limit_seconds: u64,
+}
+
+impl Default for BiObjectiveConfig {
+    fn default() -> Self {
+        Self {
+            epsilon_steps: 10,
+            neighborhoods: vec![
+                Neighborhood::Relocate,
+                Neighborhood::Exchange,
+                Neighborhood::TwoOpt,
+            ],
+            max_iterations: 100,
+            random_starts: 5,
+            pick: None,
+            time_limit_seconds: 60,
+        }
+    }
+}
+
+/// Epsilon-constraint method over the existing neighborhoods:
+/// minimizes total distance subject to Jain fairness >= epsilon for a range of
+/// thresholds and archives every valid solution it visits in a Pareto front
+pub struct EpsilonConstraint<'a> {
+    instance: &'a Instance,
+    config: BiObjectiveConfig,
+    // Random start i is seeded with seed + i; None draws fresh seeds from the OS
+    seed: Option<u64>,
+}
+
+impl<'a> EpsilonConstraint<'a> {
+    pub fn new(instance: &'a Instance, config: BiObjectiveConfig) -> Self {
+        Self { instance, config, seed: None }
+    }
+
+    /// Seed the random starts, so the same seed gives the same front
+    pub fn with_seed(mut self, seed: u64) -> Self {
+        self.seed = Some(seed);
+        self
+    }
+
+    pub fn solve_front(&self) -> ParetoFront {
+        let deadline = Instant::now() + Duration::from_secs(self.config.time_limit_seconds);
+        let mut front = ParetoFront::new();
+
+        front.insert(DeterministicConstruction::new(self.instance).solve());
+        for i in 0..self.config.random_starts {
+            let construction = RandomConstruction::new(self.instance, i % 2 == 0);
+            let construction = match self.seed {
+                Some(seed) => construction.with_seed(seed.wrapping_add(i as u64)),
+                None => construction,
+            };
+            front.insert(construction.solve());
+        }
+
+        if front.is_empty() {
+            return front;
+        }
+
+        let k = self.instance.n_vehicles().max(1) as f64;
+        let min_jain = 1.0 / k;
+        let steps = self.config.epsilon_steps.max(1);
+
+        for step in 0..=steps {
+            let epsilon = min_jain + (1.0 - min_jain) * step as f64 / steps as f64;
+
+            // Start from the shortest archived solution meeting the threshold,
+            // or from the fairest one if none does
+            let start = front.pick_min_fairness(epsilon)
+                .or_else(|| front.solutions().last().copied())
+                .cloned();
+
+            if let Some(start) = start {
+                self.constrained_descent(start, epsilon, &mut front, deadline);
+            }
+        }
+
+        front
+    }
+
+    /// VND on total distance, only accepting neighbors with Jain >= epsilon
+    fn constrained_descent(&self, mut current: Solution, epsilon: f64, front: &mut ParetoFront, deadline: Instant) {
+        let mut iterations = 0;
+
+        while iterations < self.config.max_iterations && Instant::now() < deadline {
+            let mut improved = false;
+            let current_distance = current.total_travel_distance();
+
+            for &neighborhood in &self.config.neighborhoods {
+                let local_search = LocalSearch::new(self.instance, LocalSearchConfig {
+                    neighborhood,
+                    step_function: StepFunction::BestImprovement,
+                    acceptance: AcceptanceCriterion::ImprovingOnly,
+                    max_iterations: 1,
+                    max_no_improvement: 1,
+                    time_limit_seconds: self.config.time_limit_seconds,
+                });
+
+                let neighbors = local_search.generate_neighbors(&current);
+                let mut best_index = None;
+                let mut best_distance = current_distance;
+                for (i, neighbor) in neighbors.iter().enumerate() {
+                    let distance = neighbor.total_travel_distance();
+                    if distance < best_distance && neighbor.jain_fairness() >= epsilon {
+                        best_distance = distance;
+                        best_index = Some(i);
+                    }
+                }
+
+                let mut best_neighbor = None;
+                for (i, neighbor) in neighbors.into_iter().enumerate() {
+                    if Some(i) == best_index {
+                        best_neighbor = Some(neighbor.clone());
+                    }
+                    front.insert(neighbor);
+                }
+
+                if let Some(better) = best_neighbor {
+                    current = better;
+                    improved = true;
+                    // Reset to first neighborhood when improvement found
+                    break;
+                }
+            }
+
+            if !improved {
+                break;
+            }
+
+            iterations += 1;
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::instance::tests::from_text;
+
+    // Requests 1 and 2 one after another on a line, two vehicles
+    const LINE: &str = "\
+2 2 10 2 0
+# demands
+1 1
+# request locations
+0 0
+0 1
+0 3
+0 2
+0 4
+";
+
+    #[test]
+    fn front_keeps_only_non_dominated_points() {
+        let instance = from_text("pareto", LINE);
+        let solution = |routes: Vec<Vec<usize>>| Solution::new(instance.clone(), routes);
+        // distance 12, jain 0.5
+        let detour = solution(vec![vec![2, 1, 4, 3], vec![]]);
+        // distance 8, jain 0.5
+        let one_vehicle = solution(vec![vec![1, 3, 2, 4], vec![]]);
+        // distance 4 + 8, jain 144 / 160
+        let split = solution(vec![vec![1, 3], vec![2, 4]]);
+
+        let mut front = ParetoFront::new();
+        assert!(front.insert(detour.clone()));
+        // Shorter at the same fairness replaces the detour
+        assert!(front.insert(one_vehicle));
+        assert_eq!(front.objectives(), vec![(8.0, 0.5)]);
+        assert!(!front.insert(detour));
+        // Longer but fairer is kept next to it
+        assert!(front.insert(split));
+        assert_eq!(front.objectives(), vec![(8.0, 0.5), (12.0, 0.9)]);
+        // The same point again changes nothing
+        assert!(!front.insert(solution(vec![vec![2, 4], vec![1, 3]])));
+        assert_eq!(front.len(), 2);
+    }
+
+    #[test]
+    fn front_rejects_invalid_solutions() {
+        let instance = from_text("pareto_invalid", LINE);
+        let mut front = ParetoFront::new();
+        // Serves one request although gamma is two
+        assert!(!front.insert(Solution::new(instance, vec![vec![1, 3], vec![]])));
+        assert!(front.is_empty());
+    }
+
+    #[test]
+    fn picks_follow_the_bounds() {
+        let instance = from_text("pareto_pick", LINE);
+        let mut front = ParetoFront::new();
+        front.insert(Solution::new(instance.clone(), vec![vec![1, 3, 2, 4], vec![]]));
+        front.insert(Solution::new(instance, vec![vec![1, 3], vec![2, 4]]));
+        let distance = |solution: Option<&Solution>| solution.map(|s| s.total_travel_distance());
+        assert_eq!(distance(front.pick_min_fairness(0.8)), Some(12.0));
+        assert_eq!(distance(front.pick_min_fairness(0.95)), None);
+        assert_eq!(distance(front.pick_max_distance(10.0)), Some(8.0));
+    }
+
+    #[test]
+    fn same_seed_gives_the_same_front() {
+        let instance = from_text("pareto_seed", LINE);
+        let config = BiObjectiveConfig { time_limit_seconds: 5, ..BiObjectiveConfig::default() };
+        let front = |seed| EpsilonConstraint::new(&instance, config.clone()).with_seed(seed).solve_front().objectives();
+        let first = front(7);
+        assert!(!first.is_empty());
+        assert_eq!(first, front(7));
+    }
+}
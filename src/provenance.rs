@@ -0,0 +1,80 @@
+use std::fs;
+use std::process::Command;
+
+use crate::results::csv_safe;
+
+/// Where and with which code a result was produced
+#[derive(Debug, Clone, PartialEq)]
+pub struct Provenance {
+    /// Commit hash of the working tree, with a `-dirty` suffix for uncommitted changes
+    pub git_revision: String,
+    pub crate_version: String,
+    pub hostname: String,
+    pub cpu: String,
+}
+
+impl Provenance {
+    /// Inspect the current environment; values that cannot be determined are "unknown"
+    pub fn detect() -> Self {
+        Self {
+            git_revision: git_revision().unwrap_or_else(unknown),
+            crate_version: env!("CARGO_PKG_VERSION").to_string(),
+            hostname: hostname().unwrap_or_else(unknown),
+            cpu: cpu_model().unwrap_or_else(unknown),
+        }
+    }
+
+    pub fn csv_header() -> &'static str {
+        "git_revision,crate_version,hostname,cpu"
+    }
+
+    /// Fields matching `csv_header`
+    pub fn csv_fields(&self) -> String {
+        format!(
+            "{},{},{},{}",
+            csv_safe(&self.git_revision),
+            csv_safe(&self.crate_version),
+            csv_safe(&self.hostname),
+            csv_safe(&self.cpu)
+        )
+    }
+}
+
+fn unknown() -> String {
+    "unknown".to_string()
+}
+
+fn command_output(program: &str, args: &[&str]) -> Option<String> {
+    let output = Command::new(program).args(args).output().ok()?;
+    if !output.status.success() {
+        return None;
+    }
+    let text = String::from_utf8(output.stdout).ok()?;
+    Some(text.trim().to_string())
+}
+
+fn git_revision() -> Option<String> {
+    let revision = command_output("git", &["rev-parse", "--short=12", "HEAD"])?;
+    let dirty = command_output("git", &["status", "--porcelain", "--untracked-files=no"])
+        .map(|status| !status.is_empty())
+        .unwrap_or(false);
+    Some(if dirty { format!("{}-dirty", revision) } else { revision })
+}
+
+fn hostname() -> Option<String> {
+    fs::read_to_string("/etc/hostname")
+        .ok()
+        .map(|name| name.trim().to_string())
+        .filter(|name| !name.is_empty())
+        .or_else(|| std::env::var("HOSTNAME").ok())
+        .or_else(|| std::env::var("COMPUTERNAME").ok())
+        .or_else(|| command_output("hostname", &[]))
+}
+
+fn cpu_model() -> Option<String> {
+    let cpuinfo = fs::read_to_string("/proc/cpuinfo").ok()?;
+    cpuinfo.lines()
+        .find(|line| line.starts_with("model name"))
+        .and_then(|line| line.split_once(':'))
+        .map(|(_, model)| model.trim().to_string())
+}
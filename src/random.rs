@@ -2,38 +2,47 @@ use rand::prelude::*;
 use crate::{Instance, Solution, Solver};
 
 /*
-Samples gamma requests at random and assigns them to vehicles uniformally random if they have spare capacity.
+Samples gamma requests (or enough for the minimum profit) at random and assigns them to vehicles uniformally random if they have spare capacity.
 Can be biased towards routes closer to the depot.
 */
 pub struct RandomConstruction<'a> {
     instance: &'a Instance,
     biased: bool,
+    // None draws a fresh seed from the OS on every solve
+    seed: Option<u64>,
 }
 
 impl<'a> RandomConstruction<'a> {
     
     pub fn new(instance: &'a Instance, biased: bool) -> Self {
-        Self { instance, biased }
+        Self { instance, biased, seed: None }
+    }
+
+    /// Make the construction reproducible
+    pub fn with_seed(mut self, seed: u64) -> Self {
+        self.seed = Some(seed);
+        self
     }
 
     fn construct_solution(&self) -> Solution {
-        let mut rng = thread_rng();
+        let mut rng = match self.seed {
+            Some(seed) => StdRng::seed_from_u64(seed),
+            None => StdRng::from_entropy(),
+        };
         let n_reqs = self.instance.n_reqs();
         let n_vehicles = self.instance.n_vehicles();
-        let capacity = self.instance.cap();
-        let gamma = self.instance.gamma();
         let demands = self.instance.demands().clone();
         
         let mut solution = Solution::empty(self.instance.clone(), n_vehicles);
         
         let mut all_requests: Vec<usize> = (0..n_reqs).collect();
         all_requests.shuffle(&mut rng);
-        let selected_requests: Vec<usize> = all_requests.into_iter().take(gamma).collect();
+        let selected_requests = self.instance.requests_to_cover(all_requests);
         
         if self.biased {
-            self.assign_requests_biased(&mut solution, &selected_requests, &demands, capacity, &mut rng);
+            self.assign_requests_biased(&mut solution, &selected_requests, &demands);
         } else {
-            self.assign_requests_uniform(&mut solution, &selected_requests, &demands, capacity, &mut rng);
+            self.assign_requests_uniform(&mut solution, &selected_requests, &demands, &mut rng);
         }
         
         solution
@@ -44,8 +53,7 @@ impl<'a> RandomConstruction<'a> {
         solution: &mut Solution,
         selected_requests: &[usize],
         demands: &[usize],
-        capacity: usize,
-        rng: &mut ThreadRng,
+        rng: &mut StdRng,
     ) {
         let n_vehicles = solution.routes.len();
         
@@ -56,7 +64,7 @@ impl<'a> RandomConstruction<'a> {
             
             // Try random assignment
             for &vehicle_id in &vehicle_order {
-                if self.can_assign_request(vehicle_id, req_id, solution, demands, capacity) {
+                if self.can_assign_request(vehicle_id, req_id, solution, demands) {
                     self.assign_request_to_vehicle(vehicle_id, req_id, solution);
                     assigned = true;
                     break;
@@ -77,26 +85,21 @@ impl<'a> RandomConstruction<'a> {
         solution: &mut Solution,
         selected_requests: &[usize],
         demands: &[usize],
-        capacity: usize,
-        rng: &mut ThreadRng,
     ) {
         let n_vehicles = solution.routes.len();
-        let n_reqs = self.instance.n_reqs();
-        let dist_matrix = self.instance.compute_distance_matrix();
         
         // Calculate depot proximity scores for each vehicle based on current routes
         let mut vehicle_scores = vec![0.0; n_vehicles];
         
-        for vehicle_id in 0..n_vehicles {
-            let route = &solution.routes[vehicle_id];
+        for (vehicle_id, route) in solution.routes.iter().enumerate() {
             if route.is_empty() {
                 // Empty route gets high score (prefer to use empty vehicles)
                 vehicle_scores[vehicle_id] = 1.0;
             } else {
-                // Calculate average distance from depot to current stops
+                // Calculate average distance from the vehicle's depot to current stops
                 let mut total_dist = 0.0;
                 for &stop in route {
-                    total_dist += dist_matrix[0][stop] as f64;
+                    total_dist += self.instance.start_distance(vehicle_id, stop) as f64;
                 }
                 let avg_dist = total_dist / route.len() as f64;
                 // Lower distance = higher score (closer to depot)
@@ -127,7 +130,7 @@ impl<'a> RandomConstruction<'a> {
             
             let mut assigned = false;
             for &(vehicle_id, _) in &vehicle_probs {
-                if self.can_assign_request(vehicle_id, req_id, solution, demands, capacity) {
+                if self.can_assign_request(vehicle_id, req_id, solution, demands) {
                     self.assign_request_to_vehicle(vehicle_id, req_id, solution);
                     
                     // Update score for this vehicle (penalize for adding more work)
@@ -153,7 +156,6 @@ impl<'a> RandomConstruction<'a> {
         req_id: usize,
         solution: &Solution,
         demands: &[usize],
-        capacity: usize,
     ) -> bool {
         let route = &solution.routes[vehicle_id];
         let demand = demands[req_id];
@@ -176,7 +178,18 @@ impl<'a> RandomConstruction<'a> {
                 current_load -= demands[stop_req_id];
             }
         }
-        current_load + demand <= capacity
+        if !self.instance.within_capacity(vehicle_id, current_load + demand) {
+            return false;
+        }
+
+        // Appended at the end, the request must still meet all route constraints
+        if self.instance.has_route_constraints() {
+            let mut extended = route.clone();
+            extended.push(req_id + 1);
+            extended.push(req_id + 1 + self.instance.n_reqs());
+            return self.instance.route_constraints_feasible(vehicle_id, &extended);
+        }
+        true
     }
 
     fn assign_request_to_vehicle(
@@ -201,4 +214,8 @@ impl<'a> Solver for RandomConstruction<'a> {
     fn solve(&self) -> Solution {
         self.construct_solution()
     }
+
+    fn config_description(&self) -> String {
+        format!("biased={}", self.biased)
+    }
 }
\ No newline at end of file
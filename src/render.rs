@@ -0,0 +1,279 @@
+use std::fmt::Write as FmtWrite;
+use std::fs::File;
+use std::io::Write;
+
+use crate::instance::Point;
+use crate::Solution;
+
+const WIDTH: f64 = 900.0;
+const HEIGHT: f64 = 700.0;
+const MARGIN: f64 = 30.0;
+const LEGEND_WIDTH: f64 = 200.0;
+const UNSERVED_COLOR: &str = "#c8c8c8";
+
+/// Distinct colour for each route, spaced by the golden angle on the hue wheel
+fn route_color(vehicle_id: usize) -> String {
+    let hue = (vehicle_id as f64 * 137.508) % 360.0;
+    format!("hsl({:.0}, 70%, 42%)", hue)
+}
+
+/// Maps instance coordinates into the drawing area (y axis pointing up)
+struct Projection {
+    min_x: f64,
+    min_y: f64,
+    scale: f64,
+}
+
+impl Projection {
+    fn new(points: &[Point]) -> Self {
+        let min_x = points.iter().map(|p| p.x).fold(f64::INFINITY, f64::min);
+        let max_x = points.iter().map(|p| p.x).fold(f64::NEG_INFINITY, f64::max);
+        let min_y = points.iter().map(|p| p.y).fold(f64::INFINITY, f64::min);
+        let max_y = points.iter().map(|p| p.y).fold(f64::NEG_INFINITY, f64::max);
+
+        let plot_width = WIDTH - LEGEND_WIDTH - 2.0 * MARGIN;
+        let plot_height = HEIGHT - 2.0 * MARGIN;
+        let span_x = (max_x - min_x).max(1e-9);
+        let span_y = (max_y - min_y).max(1e-9);
+        let scale = (plot_width / span_x).min(plot_height / span_y);
+
+        Self { min_x, min_y, scale }
+    }
+
+    fn project(&self, p: Point) -> (f64, f64) {
+        let x = MARGIN + (p.x - self.min_x) * self.scale;
+        let y = HEIGHT - MARGIN - (p.y - self.min_y) * self.scale;
+        (x, y)
+    }
+}
+
+/// Renders the depots, all pickup and dropoff points and every vehicle's route
+/// as a standalone SVG document. Unserved requests are drawn in grey and the
+/// legend lists the distance of each route.
+pub fn render_svg(solution: &Solution) -> String {
+    let instance = &solution.instance;
+    let n_reqs = instance.n_reqs();
+    let locations = instance.all_locations();
+    let vehicle_depots: Vec<Point> = instance.fleet().iter()
+        .flat_map(|vehicle| [vehicle.start_depot, vehicle.end_depot])
+        .flatten()
+        .collect();
+    let projection = Projection::new(&[locations.as_slice(), &vehicle_depots].concat());
+    let route_distances = solution.get_route_distances();
+
+    // Which vehicle serves each request
+    let mut served_by = vec![None; n_reqs];
+    for (vehicle_id, route) in solution.routes.iter().enumerate() {
+        for &node in route {
+            if node >= 1 && node <= n_reqs {
+                served_by[node - 1] = Some(vehicle_id);
+            }
+        }
+    }
+
+    let mut svg = String::new();
+    // Writing into a String cannot fail
+    let _ = writeln!(
+        svg,
+        r#"<svg xmlns="http://www.w3.org/2000/svg" width="{w}" height="{h}" viewBox="0 0 {w} {h}" font-family="sans-serif" font-size="12">"#,
+        w = WIDTH,
+        h = HEIGHT
+    );
+    let _ = writeln!(svg, r#"<rect width="100%" height="100%" fill="white"/>"#);
+
+    // One arrow head marker per route colour
+    let _ = writeln!(svg, "<defs>");
+    for vehicle_id in 0..solution.routes.len() {
+        let _ = writeln!(
+            svg,
+            r#"<marker id="arrow{}" viewBox="0 0 10 10" refX="9" refY="5" markerWidth="6" markerHeight="6" orient="auto"><path d="M0,0 L10,5 L0,10 z" fill="{}"/></marker>"#,
+            vehicle_id,
+            route_color(vehicle_id)
+        );
+    }
+    let _ = writeln!(svg, "</defs>");
+
+    // Routes, starting and ending at the vehicle's depots unless routes are open
+    for (vehicle_id, route) in solution.routes.iter().enumerate() {
+        if route.iter().all(|&node| instance.is_depot(node)) {
+            continue;
+        }
+        let color = route_color(vehicle_id);
+        let mut path = vec![instance.start_location(vehicle_id)];
+        path.extend(route.iter().map(|&node| locations[node]));
+        if !instance.open_routes() {
+            path.push(instance.end_location(vehicle_id));
+        }
+        path.dedup();
+
+        let _ = writeln!(svg, r#"<g id="route{}">"#, vehicle_id);
+        for pair in path.windows(2) {
+            let (x1, y1) = projection.project(pair[0]);
+            let (x2, y2) = projection.project(pair[1]);
+            let _ = writeln!(
+                svg,
+                r#"<line x1="{:.1}" y1="{:.1}" x2="{:.1}" y2="{:.1}" stroke="{}" stroke-width="1.5" marker-end="url(#arrow{})"/>"#,
+                x1, y1, x2, y2, color, vehicle_id
+            );
+        }
+        let _ = writeln!(svg, "</g>");
+    }
+
+    // Pickups as circles, dropoffs as squares
+    for req_id in 0..n_reqs {
+        let color = match served_by[req_id] {
+            Some(vehicle_id) => route_color(vehicle_id),
+            None => UNSERVED_COLOR.to_string(),
+        };
+        let (px, py) = projection.project(locations[1 + req_id]);
+        let (dx, dy) = projection.project(locations[1 + n_reqs + req_id]);
+        let _ = writeln!(
+            svg,
+            r#"<circle cx="{:.1}" cy="{:.1}" r="3.5" fill="{}"><title>Pickup-{}</title></circle>"#,
+            px, py, color, req_id + 1
+        );
+        let _ = writeln!(
+            svg,
+            r#"<rect x="{:.1}" y="{:.1}" width="7" height="7" fill="white" stroke="{}" stroke-width="1.5"><title>Dropoff-{}</title></rect>"#,
+            dx - 3.5, dy - 3.5, color, req_id + 1
+        );
+    }
+
+    // Depots, and the vehicles' own start and end locations in outline
+    for depot in 0..instance.n_depots() {
+        let node = instance.depot_node(depot);
+        let (depot_x, depot_y) = projection.project(locations[node]);
+        let _ = writeln!(
+            svg,
+            r#"<rect x="{:.1}" y="{:.1}" width="12" height="12" fill="black"><title>{}</title></rect>"#,
+            depot_x - 6.0, depot_y - 6.0, instance.location_description(node)
+        );
+    }
+    for &point in &vehicle_depots {
+        let (x, y) = projection.project(point);
+        let _ = writeln!(
+            svg,
+            r#"<rect x="{:.1}" y="{:.1}" width="12" height="12" fill="white" stroke="black" stroke-width="2"><title>Vehicle depot</title></rect>"#,
+            x - 6.0, y - 6.0
+        );
+    }
+
+    // Legend
+    let legend_x = WIDTH - LEGEND_WIDTH + 10.0;
+    let mut legend_y = MARGIN;
+    let _ = writeln!(
+        svg,
+        r#"<text x="{:.1}" y="{:.1}" font-weight="bold">{}</text>"#,
+        legend_x, legend_y, escape(instance.name())
+    );
+    legend_y += 18.0;
+    let _ = writeln!(
+        svg,
+        r#"<text x="{:.1}" y="{:.1}">Objective: {:.2}</text>"#,
+        legend_x, legend_y, solution.objective_function_value()
+    );
+    legend_y += 16.0;
+    let _ = writeln!(
+        svg,
+        r#"<text x="{:.1}" y="{:.1}">Jain fairness: {:.4}</text>"#,
+        legend_x, legend_y, solution.jain_fairness()
+    );
+    legend_y += 22.0;
+    for (vehicle_id, distance) in route_distances.iter().enumerate() {
+        let color = route_color(vehicle_id);
+        let _ = writeln!(
+            svg,
+            r#"<line x1="{:.1}" y1="{:.1}" x2="{:.1}" y2="{:.1}" stroke="{}" stroke-width="3"/>"#,
+            legend_x, legend_y - 4.0, legend_x + 20.0, legend_y - 4.0, color
+        );
+        let _ = writeln!(
+            svg,
+            r#"<text x="{:.1}" y="{:.1}">Vehicle {}: {:.2}</text>"#,
+            legend_x + 26.0, legend_y, vehicle_id + 1, distance
+        );
+        legend_y += 16.0;
+    }
+    if served_by.iter().any(|v| v.is_none()) {
+        let _ = writeln!(
+            svg,
+            r#"<circle cx="{:.1}" cy="{:.1}" r="3.5" fill="{}"/><text x="{:.1}" y="{:.1}">Unserved</text>"#,
+            legend_x + 10.0, legend_y - 4.0, UNSERVED_COLOR, legend_x + 26.0, legend_y
+        );
+    }
+
+    let _ = writeln!(svg, "</svg>");
+    svg
+}
+
+/// Write `render_svg(solution)` to a file
+pub fn write_svg(solution: &Solution, filename: &str) -> Result<(), Box<dyn std::error::Error>> {
+    let mut file = File::create(filename)?;
+    file.write_all(render_svg(solution).as_bytes())?;
+    Ok(())
+}
+
+/// Escape text for use inside SVG or HTML markup
+pub(crate) fn escape(text: &str) -> String {
+    text.replace('&', "&amp;")
+        .replace('<', "&lt;")
+        .replace('>', "&gt;")
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::instance::tests::from_text;
+
+    // Two requests, two vehicles, one request is enough
+    const INSTANCE: &str = "\
+2 2 10 1 0
+# demands
+1 1
+# request locations
+0 0
+0 1
+0 3
+0 2
+0 4
+";
+
+    #[test]
+    fn draws_used_routes_with_arrows_and_greys_out_unserved_requests() {
+        let instance = from_text("render", INSTANCE);
+        let svg = render_svg(&Solution::new(instance, vec![vec![1, 3], vec![]]));
+
+        assert!(svg.starts_with("<svg") && svg.trim_end().ends_with("</svg>"));
+        assert!(svg.contains(r#"<g id="route0">"#));
+        assert!(!svg.contains(r#"<g id="route1">"#));
+        assert!(svg.contains(r#"marker-end="url(#arrow0)""#));
+        // Depot, pickup, dropoff and back: three legs
+        assert_eq!(svg.matches(r#"marker-end="url(#arrow0)""#).count(), 3);
+
+        let unserved_pickup = format!(r#"fill="{}"><title>Pickup-2</title>"#, UNSERVED_COLOR);
+        assert!(svg.contains(&unserved_pickup));
+        assert!(svg.contains(&format!(r#"fill="{}"><title>Pickup-1</title>"#, route_color(0))));
+        assert!(svg.contains("Unserved</text>"));
+    }
+
+    #[test]
+    fn open_routes_are_drawn_without_the_return_leg() {
+        let instance = from_text("render_open", &format!("{}# open routes\n", INSTANCE));
+        let svg = render_svg(&Solution::new(instance, vec![vec![1, 3], vec![]]));
+        assert_eq!(svg.matches(r#"marker-end="url(#arrow0)""#).count(), 2);
+        assert!(svg.contains("Vehicle 1: 2.00</text>"));
+    }
+
+    #[test]
+    fn legend_lists_every_route_distance() {
+        let instance = from_text("render_legend", INSTANCE);
+        let svg = render_svg(&Solution::new(instance, vec![vec![1, 3], vec![2, 4]]));
+        assert!(svg.contains("Vehicle 1: 4.00</text>"));
+        assert!(svg.contains("Vehicle 2: 8.00</text>"));
+        assert!(!svg.contains("Unserved"));
+    }
+
+    #[test]
+    fn escapes_markup() {
+        assert_eq!(escape("a<b> & c"), "a&lt;b&gt; &amp; c");
+    }
+}
@@ -0,0 +1,358 @@
+use std::collections::BTreeMap;
+use std::fmt::Write as FmtWrite;
+use std::fs::File;
+use std::io::Write;
+
+use crate::render::escape;
+use crate::results::{load_results_dir, AlgorithmResults};
+use crate::statistics::Summary;
+
+const PLOT_WIDTH: f64 = 640.0;
+const LABEL_WIDTH: f64 = 150.0;
+const ROW_HEIGHT: f64 = 28.0;
+
+/// Read `results/<size>/<solver>.csv` files and write a self-contained HTML report
+pub fn write_html_report(results_dir: &str, output: &str) -> Result<(), Box<dyn std::error::Error>> {
+    let results = load_results_dir(results_dir)?;
+    if results.is_empty() {
+        return Err(format!("No results found in {}", results_dir).into());
+    }
+
+    let mut file = File::create(output)?;
+    file.write_all(generate_html_report(&results).as_bytes())?;
+    Ok(())
+}
+
+/// HTML page with summary tables, box plots of objective, runtime and fairness
+/// and the best algorithm per instance, one section per instance size
+pub fn generate_html_report(results: &[AlgorithmResults]) -> String {
+    // Group by size, keeping the (numeric) order of `load_results_dir`
+    let mut sizes: Vec<&str> = Vec::new();
+    let mut by_size: BTreeMap<&str, Vec<&AlgorithmResults>> = BTreeMap::new();
+    for result in results {
+        if !sizes.contains(&result.size.as_str()) {
+            sizes.push(&result.size);
+        }
+        by_size.entry(&result.size).or_default().push(result);
+    }
+
+    let mut html = String::new();
+    // Writing into a String cannot fail
+    let _ = writeln!(html, "<!DOCTYPE html>");
+    let _ = writeln!(html, "<html><head><meta charset=\"utf-8\"><title>SCF-PDP experiment report</title>");
+    let _ = writeln!(html, "<style>");
+    let _ = writeln!(html, "body {{ font-family: sans-serif; margin: 2em; }}");
+    let _ = writeln!(html, "table {{ border-collapse: collapse; margin-bottom: 1.5em; }}");
+    let _ = writeln!(html, "th, td {{ border: 1px solid #ccc; padding: 4px 8px; text-align: right; }}");
+    let _ = writeln!(html, "th:first-child, td:first-child {{ text-align: left; }}");
+    let _ = writeln!(html, "tr.best td {{ font-weight: bold; }}");
+    let _ = writeln!(html, "</style></head><body>");
+    let _ = writeln!(html, "<h1>SCF-PDP experiment report</h1>");
+
+    let _ = writeln!(html, "<h2>Overview</h2>");
+    let _ = writeln!(html, "<table><tr><th>Size</th><th>Algorithms</th><th>Rows</th></tr>");
+    for size in &sizes {
+        let group = &by_size[size];
+        let algorithms: Vec<&str> = group.iter().map(|r| r.algorithm.as_str()).collect();
+        let rows: usize = group.iter().map(|r| r.rows.len()).sum();
+        let _ = writeln!(
+            html,
+            "<tr><td>{}</td><td>{}</td><td>{}</td></tr>",
+            size, algorithms.join(", "), rows
+        );
+    }
+    let _ = writeln!(html, "</table>");
+
+    for size in &sizes {
+        let group = &by_size[size];
+        let _ = writeln!(html, "<h2>Instance size {}</h2>", size);
+
+        html.push_str(&summary_table(group));
+
+        let objectives: Vec<(String, Vec<f64>)> = group.iter()
+            .map(|r| (r.algorithm.clone(), r.objectives()))
+            .collect();
+        let times: Vec<(String, Vec<f64>)> = group.iter()
+            .map(|r| (r.algorithm.clone(), r.times()))
+            .collect();
+        let fairness: Vec<(String, Vec<f64>)> = group.iter()
+            .map(|r| (r.algorithm.clone(), r.fairness()))
+            .collect();
+
+        let _ = writeln!(html, "<h3>Objective value</h3>");
+        html.push_str(&box_plot_svg(&objectives, false));
+        let _ = writeln!(html, "<h3>Runtime (seconds, log scale)</h3>");
+        html.push_str(&box_plot_svg(&times, true));
+        let _ = writeln!(html, "<h3>Jain fairness</h3>");
+        html.push_str(&box_plot_svg(&fairness, false));
+
+        let _ = writeln!(html, "<h3>Best per instance</h3>");
+        html.push_str(&best_per_instance_table(group));
+    }
+
+    let _ = writeln!(html, "</body></html>");
+    html
+}
+
+fn summary_table(group: &[&AlgorithmResults]) -> String {
+    let mut html = String::new();
+    let _ = writeln!(
+        html,
+        "<table><tr><th>Algorithm</th><th>Instances</th><th>Objective mean</th><th>Objective std</th>\
+         <th>Objective min</th><th>Objective max</th><th>Time mean (s)</th><th>Time max (s)</th>\
+         <th>Jain mean</th><th>Jain min</th></tr>"
+    );
+
+    let best_mean = group.iter()
+        .filter_map(|r| Summary::of(&r.objectives()))
+        .map(|s| s.mean)
+        .fold(f64::INFINITY, f64::min);
+
+    for result in group {
+        let (Some(objective), Some(time), Some(fairness)) = (
+            Summary::of(&result.objectives()),
+            Summary::of(&result.times()),
+            Summary::of(&result.fairness()),
+        ) else {
+            continue;
+        };
+
+        let class = if objective.mean == best_mean { " class=\"best\"" } else { "" };
+        let _ = writeln!(
+            html,
+            "<tr{}><td>{}</td><td>{}</td><td>{:.2}</td><td>{:.2}</td><td>{:.2}</td><td>{:.2}</td>\
+             <td>{:.4}</td><td>{:.4}</td><td>{:.4}</td><td>{:.4}</td></tr>",
+            class,
+            escape(&result.algorithm),
+            objective.count,
+            objective.mean,
+            objective.std,
+            objective.min,
+            objective.max,
+            time.mean,
+            time.max,
+            fairness.mean,
+            fairness.min
+        );
+    }
+
+    let _ = writeln!(html, "</table>");
+    html
+}
+
+fn best_per_instance_table(group: &[&AlgorithmResults]) -> String {
+    // instance -> (algorithm, objective) of every algorithm that ran it
+    let mut per_instance: BTreeMap<&str, Vec<(&str, f64)>> = BTreeMap::new();
+    for result in group {
+        for row in &result.rows {
+            per_instance.entry(&row.instance_name)
+                .or_default()
+                .push((&result.algorithm, row.objective_value));
+        }
+    }
+
+    let mut html = String::new();
+    let _ = writeln!(
+        html,
+        "<table><tr><th>Instance</th><th>Best algorithm</th><th>Best objective</th><th>Runner-up</th><th>Gap to runner-up</th></tr>"
+    );
+
+    for (instance, mut entries) in per_instance {
+        entries.sort_by(|a, b| a.1.partial_cmp(&b.1).unwrap_or(std::cmp::Ordering::Equal));
+        let (best_algorithm, best_objective) = entries[0];
+        let (runner_up, gap) = match entries.get(1) {
+            Some(&(algorithm, objective)) if best_objective.abs() > 0.0 => (
+                escape(algorithm),
+                format!("{:.2}%", 100.0 * (objective - best_objective) / best_objective.abs()),
+            ),
+            Some(&(algorithm, _)) => (escape(algorithm), "-".to_string()),
+            None => ("-".to_string(), "-".to_string()),
+        };
+        let _ = writeln!(
+            html,
+            "<tr><td>{}</td><td>{}</td><td>{:.2}</td><td>{}</td><td>{}</td></tr>",
+            escape(instance),
+            escape(best_algorithm),
+            best_objective,
+            runner_up,
+            gap
+        );
+    }
+
+    let _ = writeln!(html, "</table>");
+    html
+}
+
+/// Horizontal box plot, one box per series, drawn as inline SVG.
+/// Whiskers span min to max; the individual values are drawn as dots.
+fn box_plot_svg(series: &[(String, Vec<f64>)], log_scale: bool) -> String {
+    let transform = |v: f64| if log_scale { v.max(1e-9).log10() } else { v };
+
+    let all: Vec<f64> = series.iter()
+        .flat_map(|(_, values)| values.iter().map(|&v| transform(v)))
+        .collect();
+    let mut svg = String::new();
+    if all.is_empty() {
+        return svg;
+    }
+
+    let mut lo = all.iter().cloned().fold(f64::INFINITY, f64::min);
+    let mut hi = all.iter().cloned().fold(f64::NEG_INFINITY, f64::max);
+    if (hi - lo).abs() < 1e-12 {
+        lo -= 0.5;
+        hi += 0.5;
+    }
+    let x_of = |v: f64| LABEL_WIDTH + (transform(v) - lo) / (hi - lo) * (PLOT_WIDTH - LABEL_WIDTH - 20.0);
+
+    let height = ROW_HEIGHT * series.len() as f64 + 30.0;
+    let _ = writeln!(
+        svg,
+        r#"<svg xmlns="http://www.w3.org/2000/svg" width="{}" height="{}" font-family="sans-serif" font-size="11">"#,
+        PLOT_WIDTH, height
+    );
+
+    for (i, (name, values)) in series.iter().enumerate() {
+        let Some(summary) = Summary::of(values) else {
+            continue;
+        };
+        let y = ROW_HEIGHT * i as f64 + ROW_HEIGHT / 2.0;
+        let _ = writeln!(
+            svg,
+            r#"<text x="{:.1}" y="{:.1}" text-anchor="end">{}</text>"#,
+            LABEL_WIDTH - 8.0, y + 4.0, escape(name)
+        );
+        let _ = writeln!(
+            svg,
+            r##"<line x1="{:.1}" y1="{:.1}" x2="{:.1}" y2="{:.1}" stroke="#555"/>"##,
+            x_of(summary.min), y, x_of(summary.max), y
+        );
+        let _ = writeln!(
+            svg,
+            r##"<rect x="{:.1}" y="{:.1}" width="{:.1}" height="{:.1}" fill="#9ecae1" stroke="#3182bd"/>"##,
+            x_of(summary.q1),
+            y - ROW_HEIGHT / 3.0,
+            (x_of(summary.q3) - x_of(summary.q1)).max(1.0),
+            2.0 * ROW_HEIGHT / 3.0
+        );
+        let _ = writeln!(
+            svg,
+            r##"<line x1="{:.1}" y1="{:.1}" x2="{:.1}" y2="{:.1}" stroke="#08306b" stroke-width="2"/>"##,
+            x_of(summary.median), y - ROW_HEIGHT / 3.0, x_of(summary.median), y + ROW_HEIGHT / 3.0
+        );
+        for &v in values {
+            let _ = writeln!(
+                svg,
+                r##"<circle cx="{:.1}" cy="{:.1}" r="1.5" fill="#333" fill-opacity="0.5"/>"##,
+                x_of(v), y
+            );
+        }
+    }
+
+    // Axis with the range of the data
+    let axis_y = ROW_HEIGHT * series.len() as f64 + 10.0;
+    let (lo_label, hi_label) = if log_scale {
+        (10f64.powf(lo), 10f64.powf(hi))
+    } else {
+        (lo, hi)
+    };
+    let _ = writeln!(
+        svg,
+        r##"<line x1="{:.1}" y1="{:.1}" x2="{:.1}" y2="{:.1}" stroke="#999"/>"##,
+        LABEL_WIDTH, axis_y, PLOT_WIDTH - 20.0, axis_y
+    );
+    let _ = writeln!(
+        svg,
+        r#"<text x="{:.1}" y="{:.1}">{}</text><text x="{:.1}" y="{:.1}" text-anchor="end">{}</text>"#,
+        LABEL_WIDTH, axis_y + 14.0, format_number(lo_label),
+        PLOT_WIDTH - 20.0, axis_y + 14.0, format_number(hi_label)
+    );
+
+    let _ = writeln!(svg, "</svg>");
+    svg
+}
+
+fn format_number(v: f64) -> String {
+    if v.abs() >= 100.0 {
+        format!("{:.0}", v)
+    } else if v.abs() >= 1.0 {
+        format!("{:.2}", v)
+    } else {
+        format!("{:.4}", v)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::results::ResultRow;
+    use std::collections::HashMap;
+
+    fn results(algorithm: &str, size: &str, rows: &[(&str, f64)]) -> AlgorithmResults {
+        AlgorithmResults {
+            algorithm: algorithm.to_string(),
+            size: size.to_string(),
+            rows: rows.iter()
+                .map(|&(instance, objective)| ResultRow {
+                    instance_name: instance.to_string(),
+                    time_seconds: 0.5,
+                    objective_value: objective,
+                    jain_fairness: 0.9,
+                    fairness_measure: "jain".to_string(),
+                    fields: HashMap::new(),
+                })
+                .collect(),
+        }
+    }
+
+    #[test]
+    fn report_has_a_section_with_plots_per_size() {
+        let html = generate_html_report(&[
+            results("grasp", "50", &[("a", 100.0)]),
+            results("grasp", "100", &[("b", 200.0)]),
+        ]);
+        assert!(html.starts_with("<!DOCTYPE html>"));
+        assert!(html.trim_end().ends_with("</body></html>"));
+        let first = html.find("<h2>Instance size 50</h2>").unwrap();
+        let second = html.find("<h2>Instance size 100</h2>").unwrap();
+        assert!(first < second);
+        // Objective, runtime and fairness box plot for each size
+        assert_eq!(html.matches("<svg").count(), 6);
+    }
+
+    #[test]
+    fn summary_marks_the_best_mean_objective() {
+        let html = generate_html_report(&[
+            results("grasp", "50", &[("a", 100.0), ("b", 120.0)]),
+            results("vnd", "50", &[("a", 90.0), ("b", 110.0)]),
+        ]);
+        assert!(html.contains("<tr class=\"best\"><td>vnd</td><td>2</td><td>100.00</td>"));
+        assert!(html.contains("<tr><td>grasp</td><td>2</td><td>110.00</td>"));
+    }
+
+    #[test]
+    fn best_per_instance_shows_the_gap_to_the_runner_up() {
+        let html = generate_html_report(&[
+            results("grasp", "50", &[("a", 110.0)]),
+            results("vnd", "50", &[("a", 100.0)]),
+        ]);
+        assert!(html.contains("<tr><td>a</td><td>vnd</td><td>100.00</td><td>grasp</td><td>10.00%</td></tr>"));
+    }
+
+    #[test]
+    fn writes_the_report_of_a_results_directory() {
+        let dir = std::env::temp_dir().join(format!("scf_pdp_report_{}", std::process::id()));
+        std::fs::create_dir_all(dir.join("50")).unwrap();
+        std::fs::write(
+            dir.join("50").join("grasp.csv"),
+            "instance_name,time_seconds,objective_value,jain_fairness\na,0.5,100,0.9\n",
+        ).unwrap();
+        let output = dir.join("report.html");
+
+        write_html_report(&dir.to_string_lossy(), &output.to_string_lossy()).unwrap();
+        let html = std::fs::read_to_string(&output).unwrap();
+        assert!(html.contains("<td>50</td><td>grasp</td><td>1</td>"));
+
+        assert!(write_html_report(&dir.join("50").to_string_lossy(), &output.to_string_lossy()).is_err());
+        let _ = std::fs::remove_dir_all(&dir);
+    }
+}
@@ -0,0 +1,442 @@
+use std::collections::{HashMap, HashSet};
+use std::fmt;
+use std::fs::{self, File, OpenOptions};
+use std::io::{BufRead, BufReader, Write};
+use std::path::Path;
+
+use crate::FairnessMeasure;
+
+/// One row of a `results/<size>/<solver>.csv` file
+#[derive(Debug, Clone, PartialEq)]
+pub struct ResultRow {
+    pub instance_name: String,
+    pub time_seconds: f64,
+    pub objective_value: f64,
+    pub jain_fairness: f64,
+    /// Measure under which `objective_value` was computed; `jain` for files
+    /// written before the column existed
+    pub fairness_measure: String,
+    /// All columns of the row by header name, including the ones above
+    pub fields: HashMap<String, String>,
+}
+
+impl ResultRow {
+    /// Raw value of an arbitrary column
+    pub fn field(&self, column: &str) -> Option<&str> {
+        self.fields.get(column).map(|s| s.as_str())
+    }
+}
+
+/// All rows produced by one solver on one instance size
+#[derive(Debug, Clone, PartialEq)]
+pub struct AlgorithmResults {
+    pub algorithm: String,
+    pub size: String,
+    pub rows: Vec<ResultRow>,
+}
+
+impl AlgorithmResults {
+    pub fn objectives(&self) -> Vec<f64> {
+        self.rows.iter().map(|r| r.objective_value).collect()
+    }
+
+    pub fn times(&self) -> Vec<f64> {
+        self.rows.iter().map(|r| r.time_seconds).collect()
+    }
+
+    pub fn fairness(&self) -> Vec<f64> {
+        self.rows.iter().map(|r| r.jain_fairness).collect()
+    }
+}
+
+/// Read a per-solver results CSV. Columns are matched by header name, so
+/// files with additional columns are read as well.
+pub fn read_results_csv(filename: &str) -> Result<Vec<ResultRow>, Box<dyn std::error::Error>> {
+    let file = File::open(filename)?;
+    let reader = BufReader::new(file);
+    let mut lines = reader.lines();
+
+    let header_line = lines.next().ok_or("Empty results file")??;
+    let header: Vec<String> = header_line.split(',').map(|s| s.trim().to_string()).collect();
+
+    for required in ["instance_name", "time_seconds", "objective_value", "jain_fairness"] {
+        if !header.iter().any(|h| h == required) {
+            return Err(format!("Missing column {} in {}", required, filename).into());
+        }
+    }
+
+    let mut rows = Vec::new();
+    for (line_number, line) in lines.enumerate() {
+        let line = line?;
+        if line.trim().is_empty() {
+            continue;
+        }
+
+        let values: Vec<&str> = line.split(',').collect();
+        if values.len() != header.len() {
+            return Err(format!(
+                "Line {} of {} has {} columns, expected {}",
+                line_number + 2, filename, values.len(), header.len()
+            ).into());
+        }
+
+        let fields: HashMap<String, String> = header.iter()
+            .cloned()
+            .zip(values.iter().map(|v| v.trim().to_string()))
+            .collect();
+
+        rows.push(ResultRow {
+            instance_name: fields["instance_name"].clone(),
+            time_seconds: fields["time_seconds"].parse()?,
+            objective_value: fields["objective_value"].parse()?,
+            jain_fairness: fields["jain_fairness"].parse()?,
+            fairness_measure: fields.get("fairness_measure")
+                .cloned()
+                .unwrap_or_else(|| FairnessMeasure::default().to_string()),
+            fields,
+        });
+    }
+
+    Ok(rows)
+}
+
+/// Load every `<results_dir>/<size>/<solver>.csv`. Benchmark tables with an
+/// `algorithm` column are split into one entry per algorithm, named
+/// `<benchmark>:<algorithm>`. Files in other formats are skipped.
+pub fn load_results_dir(results_dir: &str) -> Result<Vec<AlgorithmResults>, Box<dyn std::error::Error>> {
+    let mut results = Vec::new();
+
+    for size_entry in fs::read_dir(results_dir)? {
+        let size_path = size_entry?.path();
+        if !size_path.is_dir() {
+            continue;
+        }
+        let size = match size_path.file_name().and_then(|s| s.to_str()) {
+            Some(size) => size.to_string(),
+            None => continue,
+        };
+
+        for entry in fs::read_dir(&size_path)? {
+            let path = entry?.path();
+            if path.extension().and_then(|s| s.to_str()) != Some("csv") {
+                continue;
+            }
+            let algorithm = match path.file_stem().and_then(|s| s.to_str()) {
+                Some(algorithm) => algorithm.to_string(),
+                None => continue,
+            };
+
+            let rows = match read_results_csv(&path.to_string_lossy()) {
+                Ok(rows) => rows,
+                Err(e) => {
+                    eprintln!("Skipping {:?}: {}", path, e);
+                    continue;
+                }
+            };
+
+            for (algorithm, rows) in split_by_algorithm(&algorithm, rows) {
+                results.push(AlgorithmResults { algorithm, size: size.clone(), rows });
+            }
+        }
+    }
+
+    // Numeric size order, then algorithm name
+    results.sort_by(|a, b| {
+        size_key(&a.size).cmp(&size_key(&b.size)).then_with(|| a.algorithm.cmp(&b.algorithm))
+    });
+    Ok(results)
+}
+
+/// Split rows of a benchmark table by their `algorithm` column into groups
+/// named `<name>:<algorithm>`. Rows of a per-solver file form a single group.
+/// Objectives under different fairness measures are not comparable, so rows
+/// not computed with Jain get their own group with the measure appended,
+/// e.g. `<name>[gini]`.
+pub fn split_by_algorithm(name: &str, rows: Vec<ResultRow>) -> Vec<(String, Vec<ResultRow>)> {
+    // Single-solver files record their own name as the algorithm
+    let single = rows.iter().all(|row| row.field("algorithm").is_none_or(|algorithm| algorithm == name));
+    let default_measure = FairnessMeasure::default().to_string();
+
+    let mut groups: Vec<(String, Vec<ResultRow>)> = Vec::new();
+    for row in rows {
+        let mut group_name = if single {
+            name.to_string()
+        } else {
+            format!("{}:{}", name, row.field("algorithm").unwrap_or(""))
+        };
+        if row.fairness_measure != default_measure {
+            group_name = format!("{}[{}]", group_name, row.fairness_measure);
+        }
+        match groups.iter_mut().find(|(n, _)| *n == group_name) {
+            Some((_, group)) => group.push(row),
+            None => groups.push((group_name, vec![row])),
+        }
+    }
+    groups
+}
+
+/// Make free text safe for the comma-separated, unquoted result files
+pub fn csv_safe(text: &str) -> String {
+    text.replace(',', ";").replace(['\n', '\r'], " ")
+}
+
+fn size_key(size: &str) -> (usize, String) {
+    (size.parse().unwrap_or(usize::MAX), size.to_string())
+}
+
+/// Errors while writing result files
+#[derive(Debug)]
+pub enum ResultsError {
+    Io { path: String, source: std::io::Error },
+    /// An existing file cannot be resumed because its columns differ
+    HeaderMismatch { path: String, expected: String, found: String },
+}
+
+impl ResultsError {
+    fn io(path: &str, source: std::io::Error) -> Self {
+        ResultsError::Io { path: path.to_string(), source }
+    }
+}
+
+impl fmt::Display for ResultsError {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        match self {
+            ResultsError::Io { path, source } => write!(f, "I/O error on {}: {}", path, source),
+            ResultsError::HeaderMismatch { path, expected, found } => write!(
+                f,
+                "Cannot resume {}: header is '{}', expected '{}'",
+                path, found, expected
+            ),
+        }
+    }
+}
+
+impl std::error::Error for ResultsError {
+    fn source(&self) -> Option<&(dyn std::error::Error + 'static)> {
+        match self {
+            ResultsError::Io { source, .. } => Some(source),
+            ResultsError::HeaderMismatch { .. } => None,
+        }
+    }
+}
+
+/// Identifies one run in a results file. Names are kept as they appear in
+/// the file, i.e. passed through `csv_safe`.
+#[derive(Debug, Clone, PartialEq, Eq, Hash)]
+pub struct JobKey {
+    pub instance_name: String,
+    pub algorithm: String,
+    /// Empty for runs without a seed
+    pub seed: String,
+}
+
+impl JobKey {
+    pub fn new(instance_name: &str, algorithm: &str, seed: Option<u64>) -> Self {
+        Self {
+            instance_name: csv_safe(instance_name),
+            algorithm: csv_safe(algorithm),
+            seed: seed.map(|s| s.to_string()).unwrap_or_default(),
+        }
+    }
+}
+
+/// Appends result rows to a CSV file, flushing each row to disk so an
+/// interrupted sweep keeps everything finished so far
+pub struct ResultsWriter {
+    path: String,
+    file: File,
+    completed: HashSet<JobKey>,
+}
+
+impl ResultsWriter {
+    /// Start a new file, replacing any existing one
+    pub fn create(path: &str, header: &str) -> Result<Self, ResultsError> {
+        let mut file = File::create(path).map_err(|e| ResultsError::io(path, e))?;
+        writeln!(file, "{}", header).map_err(|e| ResultsError::io(path, e))?;
+        file.sync_data().map_err(|e| ResultsError::io(path, e))?;
+        Ok(Self { path: path.to_string(), file, completed: HashSet::new() })
+    }
+
+    /// Continue an existing file with the same header, remembering which jobs
+    /// it already contains. `algorithm` names the jobs of files without an
+    /// `algorithm` column. Creates the file if it does not exist. A row cut
+    /// short by a crash is removed, so the file stays readable.
+    pub fn resume(path: &str, header: &str, algorithm: &str) -> Result<Self, ResultsError> {
+        if !Path::new(path).exists() {
+            return Self::create(path, header);
+        }
+
+        let content = fs::read_to_string(path).map_err(|e| ResultsError::io(path, e))?;
+        let mut lines = content.lines();
+        let found = lines.next().unwrap_or("").trim();
+        if found.is_empty() {
+            return Self::create(path, header);
+        }
+        if found != header {
+            return Err(ResultsError::HeaderMismatch {
+                path: path.to_string(),
+                expected: header.to_string(),
+                found: found.to_string(),
+            });
+        }
+
+        let columns: Vec<&str> = header.split(',').collect();
+        let column = |name: &str| columns.iter().position(|c| *c == name);
+        let (instance_col, algorithm_col, seed_col) = (column("instance_name"), column("algorithm"), column("seed"));
+
+        // Byte offset just past the last complete row, or the header
+        let mut complete_len = content.find('\n').map_or(content.len(), |end| end + 1);
+        let mut offset = 0;
+        let mut completed = HashSet::new();
+        for (i, line) in content.split_inclusive('\n').enumerate() {
+            offset += line.len();
+            let values: Vec<&str> = line.trim_end_matches(['\r', '\n']).split(',').collect();
+            // Rows are written whole, so only the last one can be cut short
+            if i == 0 || values.len() != columns.len() {
+                continue;
+            }
+            complete_len = offset;
+            let value = |col: Option<usize>| col.map(|c| values[c].to_string());
+            completed.insert(JobKey {
+                instance_name: value(instance_col).unwrap_or_default(),
+                algorithm: value(algorithm_col).unwrap_or_else(|| csv_safe(algorithm)),
+                seed: value(seed_col).unwrap_or_default(),
+            });
+        }
+
+        // Drop a cut-short row and continue on a fresh line
+        let mut file = OpenOptions::new()
+            .append(true)
+            .open(path)
+            .map_err(|e| ResultsError::io(path, e))?;
+        file.set_len(complete_len as u64).map_err(|e| ResultsError::io(path, e))?;
+        if !content[..complete_len].ends_with('\n') {
+            writeln!(file).map_err(|e| ResultsError::io(path, e))?;
+        }
+
+        Ok(Self { path: path.to_string(), file, completed })
+    }
+
+    pub fn path(&self) -> &str {
+        &self.path
+    }
+
+    /// Whether the file already contains a row for this job
+    pub fn is_done(&self, key: &JobKey) -> bool {
+        self.completed.contains(key)
+    }
+
+    /// Append one row and flush it to disk
+    pub fn append(&mut self, key: JobKey, row: &str) -> Result<(), ResultsError> {
+        writeln!(self.file, "{}", row).map_err(|e| ResultsError::io(&self.path, e))?;
+        self.file.sync_data().map_err(|e| ResultsError::io(&self.path, e))?;
+        self.completed.insert(key);
+        Ok(())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    const HEADER: &str = "instance_name,algorithm,seed,objective";
+
+    fn temp_path(name: &str) -> String {
+        let path = std::env::temp_dir().join(format!("scf_pdp_{}_{}.csv", name, std::process::id()));
+        path.to_str().unwrap().to_string()
+    }
+
+    #[test]
+    fn resume_skips_rows_cut_short() {
+        let path = temp_path("resume_truncated");
+        // The second row lost its last field and newline in a crash
+        fs::write(&path, format!("{}\ninst_a,cheapest,0,12.5\ninst_b,cheapest,0", HEADER)).unwrap();
+
+        let mut writer = ResultsWriter::resume(&path, HEADER, "cheapest").unwrap();
+        assert!(writer.is_done(&JobKey::new("inst_a", "cheapest", Some(0))));
+        assert!(!writer.is_done(&JobKey::new("inst_b", "cheapest", Some(0))));
+
+        writer.append(JobKey::new("inst_b", "cheapest", Some(0)), "inst_b,cheapest,0,7.25").unwrap();
+        assert!(writer.is_done(&JobKey::new("inst_b", "cheapest", Some(0))));
+        drop(writer);
+
+        // The broken row is gone and the new row follows the last complete one
+        let content = fs::read_to_string(&path).unwrap();
+        fs::remove_file(&path).unwrap();
+        assert_eq!(content, format!("{}\ninst_a,cheapest,0,12.5\ninst_b,cheapest,0,7.25\n", HEADER));
+    }
+
+    #[test]
+    fn resumed_file_can_be_read() {
+        let path = temp_path("resume_readable");
+        let header = "instance_name,time_seconds,objective_value,jain_fairness";
+        fs::write(&path, format!("{}\ninst_a,1.5,12.5,0.9\ninst_b,2.0", header)).unwrap();
+
+        let mut writer = ResultsWriter::resume(&path, header, "cheapest").unwrap();
+        writer.append(JobKey::new("inst_b", "cheapest", None), "inst_b,2.5,7.25,0.8").unwrap();
+        drop(writer);
+
+        let rows = read_results_csv(&path).unwrap();
+        fs::remove_file(&path).unwrap();
+        let names: Vec<&str> = rows.iter().map(|row| row.instance_name.as_str()).collect();
+        assert_eq!(names, vec!["inst_a", "inst_b"]);
+        assert_eq!(rows[1].objective_value, 7.25);
+    }
+
+    #[test]
+    fn resume_completes_a_last_row_without_newline() {
+        let path = temp_path("resume_no_newline");
+        fs::write(&path, format!("{}\ninst_a,cheapest,0,12.5", HEADER)).unwrap();
+        let mut writer = ResultsWriter::resume(&path, HEADER, "cheapest").unwrap();
+        assert!(writer.is_done(&JobKey::new("inst_a", "cheapest", Some(0))));
+        writer.append(JobKey::new("inst_b", "cheapest", Some(0)), "inst_b,cheapest,0,7.25").unwrap();
+        drop(writer);
+        let content = fs::read_to_string(&path).unwrap();
+        fs::remove_file(&path).unwrap();
+        assert_eq!(content, format!("{}\ninst_a,cheapest,0,12.5\ninst_b,cheapest,0,7.25\n", HEADER));
+    }
+
+    #[test]
+    fn resume_keeps_complete_files_unchanged() {
+        let path = temp_path("resume_complete");
+        let content = format!("{}\ninst_a,cheapest,0,12.5\n", HEADER);
+        fs::write(&path, &content).unwrap();
+
+        let writer = ResultsWriter::resume(&path, HEADER, "cheapest").unwrap();
+        assert!(writer.is_done(&JobKey::new("inst_a", "cheapest", Some(0))));
+        drop(writer);
+        assert_eq!(fs::read_to_string(&path).unwrap(), content);
+        fs::remove_file(&path).unwrap();
+    }
+
+    #[test]
+    fn resume_rejects_another_header() {
+        let path = temp_path("resume_header");
+        fs::write(&path, "instance_name,objective\n").unwrap();
+        let result = ResultsWriter::resume(&path, HEADER, "cheapest");
+        fs::remove_file(&path).unwrap();
+        assert!(matches!(result, Err(ResultsError::HeaderMismatch { .. })));
+    }
+
+    #[test]
+    fn resume_creates_missing_and_empty_files() {
+        let path = temp_path("resume_missing");
+        let _ = fs::remove_file(&path);
+        ResultsWriter::resume(&path, HEADER, "cheapest").unwrap();
+        assert_eq!(fs::read_to_string(&path).unwrap(), format!("{}\n", HEADER));
+
+        fs::write(&path, "").unwrap();
+        ResultsWriter::resume(&path, HEADER, "cheapest").unwrap();
+        assert_eq!(fs::read_to_string(&path).unwrap(), format!("{}\n", HEADER));
+        fs::remove_file(&path).unwrap();
+    }
+
+    #[test]
+    fn resume_names_jobs_of_files_without_algorithm_column() {
+        let path = temp_path("resume_no_algorithm");
+        fs::write(&path, "instance_name,seed,objective\ninst_a,1,3.0\n").unwrap();
+        let writer = ResultsWriter::resume(&path, "instance_name,seed,objective", "regret, k=3").unwrap();
+        fs::remove_file(&path).unwrap();
+        assert!(writer.is_done(&JobKey::new("inst_a", "regret, k=3", Some(1))));
+    }
+}
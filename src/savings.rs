@@ -0,0 +1,483 @@
+use std::cmp::Ordering;
+use std::collections::BinaryHeap;
+
+use crate::insertion::InsertionConstruction;
+use crate::{Instance, Solution, Solver};
+
+/// Candidate merges per request: its dropoff is only linked to this many nearest pickups
+const NEIGHBORS: usize = 30;
+
+/// Appending the route that starts with request `head` to the route that ends
+/// with request `tail`
+#[derive(Debug, Clone, Copy)]
+struct Merge {
+    score: f64,
+    tail: usize,
+    head: usize,
+}
+
+impl PartialEq for Merge {
+    fn eq(&self, other: &Self) -> bool {
+        self.cmp(other) == Ordering::Equal
+    }
+}
+
+impl Eq for Merge {}
+
+impl PartialOrd for Merge {
+    fn partial_cmp(&self, other: &Self) -> Option<Ordering> {
+        Some(self.cmp(other))
+    }
+}
+
+impl Ord for Merge {
+    fn cmp(&self, other: &Self) -> Ordering {
+        self.score.total_cmp(&other.score)
+            .then(other.tail.cmp(&self.tail))
+            .then(other.head.cmp(&self.head))
+    }
+}
+
+#[derive(Debug, Clone)]
+struct PartialRoute {
+    requests: Vec<usize>,
+    distance: f64,
+    /// Base the route starts and ends at
+    base: usize,
+}
+
+/*
+Clarke-Wright savings adapted to pickup and delivery pairs.
+Selects the gamma requests that are cheapest to serve on their own, starts
+with one route (depot, pickup, dropoff, depot) per request and merges routes
+until no base has more routes than vehicles. A merge appends one route to
+another, so every pickup stays before its dropoff on the same route and the
+load is zero between the merged parts; capacity holds as long as each request
+fits alone. With time windows or route limits, merges that would violate them
+are skipped.
+
+Vehicles that start and end at the same locations form a base. Every request
+is anchored to the base where its standalone route is shortest, savings are
+computed with that base's start and end location, and only routes of the same
+base are merged. Vehicles are assigned to the finished routes, so with a
+heterogeneous fleet routes with large requests get the large vehicles. Routes
+no free vehicle can serve are dissolved and their requests inserted at their
+cheapest feasible position; requests that fit nowhere stay unserved, which
+shows as unmet coverage.
+
+Merges are ranked by their saving minus rho times the relative amount by which
+the merged route exceeds an even share of the total distance, so long routes
+are merged less eagerly. Distances are computed on the fly, which keeps memory
+linear in the number of requests.
+*/
+pub struct SavingsConstruction<'a> {
+    instance: &'a Instance,
+}
+
+impl<'a> SavingsConstruction<'a> {
+    pub fn new(instance: &'a Instance) -> Self {
+        Self { instance }
+    }
+
+    fn pickup_node(&self, req_id: usize) -> usize {
+        req_id + 1
+    }
+
+    fn dropoff_node(&self, req_id: usize) -> usize {
+        req_id + 1 + self.instance.n_reqs()
+    }
+
+    fn d(&self, u: usize, v: usize) -> f64 {
+        self.instance.distance(u, v) as f64
+    }
+
+    /// Distance saved by driving from the dropoff of `tail` to the pickup of `head`
+    /// instead of returning to the base of `vehicle_id` in between
+    fn saving(&self, vehicle_id: usize, tail: usize, head: usize) -> f64 {
+        let dropoff = self.dropoff_node(tail);
+        let pickup = self.pickup_node(head);
+        (self.instance.end_distance(dropoff, vehicle_id) + self.instance.start_distance(vehicle_id, pickup)) as f64
+            - self.d(dropoff, pickup)
+    }
+
+    /// Saving of a merge, reduced by rho times the share by which the merged
+    /// route would exceed `target` (the average route length)
+    fn score(&self, saving: f64, first: &PartialRoute, second: &PartialRoute, target: f64) -> f64 {
+        let merged = first.distance + second.distance - saving;
+        let excess = if target > 0.0 { ((merged - target) / target).max(0.0) } else { 0.0 };
+        saving - self.instance.rho() * excess
+    }
+
+    /// Base of every vehicle and one vehicle of every base
+    fn bases(&self) -> (Vec<usize>, Vec<usize>) {
+        let mut base_of = Vec::with_capacity(self.instance.n_vehicles());
+        let mut base_vehicle: Vec<usize> = Vec::new();
+        for k in 0..self.instance.n_vehicles() {
+            let locations = (self.instance.start_location(k), self.instance.end_location(k));
+            let base = base_vehicle.iter()
+                .position(|&v| (self.instance.start_location(v), self.instance.end_location(v)) == locations)
+                .unwrap_or_else(|| {
+                    base_vehicle.push(k);
+                    base_vehicle.len() - 1
+                });
+            base_of.push(base);
+        }
+        (base_of, base_vehicle)
+    }
+
+    /// Base with the shortest standalone route of a request among the bases
+    /// with a vehicle that can serve it alone
+    fn anchor(&self, req_id: usize, base_of: &[usize], base_vehicle: &[usize]) -> usize {
+        let stops = self.stops(&[req_id]);
+        let feasible = |base: usize| {
+            (0..self.instance.n_vehicles()).any(|k| base_of[k] == base && vehicle_feasible(self.instance, k, &stops))
+        };
+        (0..base_vehicle.len())
+            .filter(|&base| feasible(base))
+            .min_by(|&a, &b| {
+                standalone_distance(self.instance, base_vehicle[a], req_id)
+                    .total_cmp(&standalone_distance(self.instance, base_vehicle[b], req_id))
+            })
+            .unwrap_or(0)
+    }
+
+    fn construct_solution(&self) -> Solution {
+        let n_vehicles = self.instance.n_vehicles();
+        let selected = cheapest_standalone_requests(self.instance);
+        let (base_of, base_vehicle) = self.bases();
+
+        // Routes each base may keep, and how many it has
+        let mut limit = vec![0usize; base_vehicle.len()];
+        for &base in &base_of {
+            limit[base] += 1;
+        }
+        let mut count = vec![0usize; base_vehicle.len()];
+
+        let mut routes: Vec<Option<PartialRoute>> = selected.iter()
+            .map(|&req_id| {
+                let base = self.anchor(req_id, &base_of, &base_vehicle);
+                count[base] += 1;
+                Some(PartialRoute {
+                    requests: vec![req_id],
+                    distance: standalone_distance(self.instance, base_vehicle[base], req_id),
+                    base,
+                })
+            })
+            .collect();
+        // Route index of every selected request
+        let mut route_of = vec![usize::MAX; self.instance.n_reqs()];
+        for (r, &req_id) in selected.iter().enumerate() {
+            route_of[req_id] = r;
+        }
+        let mut total_distance: f64 = routes.iter().flatten().map(|r| r.distance).sum();
+        let mut excess: usize = count.iter().zip(&limit).map(|(c, l)| c.saturating_sub(*l)).sum();
+
+        let target = |total: f64| total / n_vehicles.max(1) as f64;
+
+        let mut heap = BinaryHeap::new();
+        for &tail in &selected {
+            let dropoff = self.dropoff_node(tail);
+            let base = routes[route_of[tail]].as_ref().expect("route exists").base;
+            let mut nearest: Vec<(f64, usize)> = selected.iter()
+                .filter(|&&head| head != tail)
+                .filter(|&&head| routes[route_of[head]].as_ref().is_some_and(|r| r.base == base))
+                .map(|&head| (self.d(dropoff, self.pickup_node(head)), head))
+                .collect();
+            if nearest.len() > NEIGHBORS {
+                nearest.select_nth_unstable_by(NEIGHBORS, |a, b| a.0.total_cmp(&b.0));
+                nearest.truncate(NEIGHBORS);
+            }
+            for (_, head) in nearest {
+                let first = routes[route_of[tail]].as_ref().expect("route exists");
+                let second = routes[route_of[head]].as_ref().expect("route exists");
+                let saving = self.saving(base_vehicle[base], tail, head);
+                let score = self.score(saving, first, second, target(total_distance));
+                heap.push(Merge { score, tail, head });
+            }
+        }
+
+        while excess > 0 {
+            let Some(merge) = heap.pop() else {
+                break;
+            };
+            let (a, b) = (route_of[merge.tail], route_of[merge.head]);
+            if a == b {
+                continue;
+            }
+            let (first, second) = match (&routes[a], &routes[b]) {
+                (Some(first), Some(second)) => (first, second),
+                _ => continue,
+            };
+            // Only the last request of a route can be followed, only the first preceded
+            if first.requests.last() != Some(&merge.tail) || second.requests.first() != Some(&merge.head) {
+                continue;
+            }
+            let base = first.base;
+            if count[base] <= limit[base] || !self.merge_feasible(first, second) {
+                continue;
+            }
+
+            // Scores drop as routes grow, so a re-scored merge that is still on
+            // top of the heap is taken
+            let saving = self.saving(base_vehicle[base], merge.tail, merge.head);
+            let score = self.score(saving, first, second, target(total_distance));
+            if score < merge.score - 1e-9 {
+                heap.push(Merge { score, ..merge });
+                continue;
+            }
+
+            self.merge_routes(&mut routes, &mut route_of, a, b, saving);
+            total_distance -= saving;
+            count[base] -= 1;
+            excess -= 1;
+        }
+
+        // Too few candidate pairs: merge the best remaining pair of whole routes of a base
+        for base in 0..base_vehicle.len() {
+            while count[base] > limit[base] {
+                let alive: Vec<usize> = (0..routes.len())
+                    .filter(|&r| routes[r].as_ref().is_some_and(|route| route.base == base))
+                    .collect();
+                let mut best: Option<(f64, usize, usize, f64)> = None;
+                for &a in &alive {
+                    for &b in &alive {
+                        if a == b {
+                            continue;
+                        }
+                        let (first, second) = (routes[a].as_ref().unwrap(), routes[b].as_ref().unwrap());
+                        if !self.merge_feasible(first, second) {
+                            continue;
+                        }
+                        let saving = self.saving(base_vehicle[base], *first.requests.last().unwrap(), second.requests[0]);
+                        let score = self.score(saving, first, second, target(total_distance));
+                        if best.is_none_or(|(best_score, ..)| score > best_score) {
+                            best = Some((score, a, b, saving));
+                        }
+                    }
+                }
+                let Some((_, a, b, saving)) = best else {
+                    break;
+                };
+                self.merge_routes(&mut routes, &mut route_of, a, b, saving);
+                total_distance -= saving;
+                count[base] -= 1;
+            }
+        }
+
+        let vehicle_routes: Vec<Vec<usize>> = routes.into_iter()
+            .flatten()
+            .map(|route| self.stops(&route.requests))
+            .collect();
+
+        let (mut assigned, leftover) = self.assign_vehicles(vehicle_routes);
+        self.insert_leftover(&mut assigned, &leftover);
+        Solution::new(self.instance.clone(), assigned)
+    }
+
+    /// Stops of a route serving `requests` one after another
+    fn stops(&self, requests: &[usize]) -> Vec<usize> {
+        requests.iter()
+            .flat_map(|&req_id| [self.pickup_node(req_id), self.dropoff_node(req_id)])
+            .collect()
+    }
+
+    /// Whether some vehicle can serve `second` appended to `first` within all
+    /// time windows and route limits
+    fn merge_feasible(&self, first: &PartialRoute, second: &PartialRoute) -> bool {
+        if !self.instance.has_route_constraints() {
+            return true;
+        }
+        let mut stops = self.stops(&first.requests);
+        stops.extend(self.stops(&second.requests));
+        any_vehicle_feasible(self.instance, &stops)
+    }
+
+    /// Give every route a vehicle, routes with the largest request first. Each
+    /// route takes the cheapest free vehicle that can serve it; routes without
+    /// one are returned as leftovers.
+    fn assign_vehicles(&self, routes: Vec<Vec<usize>>) -> (Vec<Vec<usize>>, Vec<Vec<usize>>) {
+        let n_vehicles = self.instance.n_vehicles();
+        let demands = self.instance.demands();
+        let largest_demand = |route: &[usize]| {
+            route.iter()
+                .filter(|&&node| node <= self.instance.n_reqs())
+                .map(|&node| demands[node - 1])
+                .max()
+                .unwrap_or(0)
+        };
+
+        let mut routes = routes;
+        routes.sort_by_key(|route| std::cmp::Reverse(largest_demand(route)));
+
+        let mut assigned = vec![Vec::new(); n_vehicles];
+        let mut free = vec![true; n_vehicles];
+        let mut leftover = Vec::new();
+        for route in routes {
+            let cost = |k: usize| {
+                self.instance.vehicle(k).route_cost(self.instance.route_distance(k, &route), true)
+            };
+            let vehicle = (0..n_vehicles)
+                .filter(|&k| free[k] && vehicle_feasible(self.instance, k, &route))
+                .min_by(|&a, &b| cost(a).total_cmp(&cost(b)));
+            match vehicle {
+                Some(vehicle) => {
+                    free[vehicle] = false;
+                    assigned[vehicle] = route;
+                }
+                None => leftover.push(route),
+            }
+        }
+        (assigned, leftover)
+    }
+
+    /// Insert the requests of routes without a vehicle one by one at their
+    /// cheapest feasible position in any route
+    fn insert_leftover(&self, routes: &mut [Vec<usize>], leftover: &[Vec<usize>]) {
+        let insertion = InsertionConstruction::cheapest(self.instance);
+        let requests = leftover.iter()
+            .flatten()
+            .filter(|&&node| node <= self.instance.n_reqs())
+            .map(|&node| node - 1);
+        for req_id in requests {
+            let best = routes.iter()
+                .enumerate()
+                .filter_map(|(k, route)| {
+                    let ins = insertion.best_insertion(route, k, req_id, 0)?;
+                    let vehicle = self.instance.vehicle(k);
+                    Some((vehicle.route_cost(ins.delta, route.is_empty()), k, ins))
+                })
+                .min_by(|a, b| a.0.total_cmp(&b.0));
+            if let Some((_, k, ins)) = best {
+                routes[k].insert(ins.dropoff_pos, self.dropoff_node(req_id));
+                routes[k].insert(ins.pickup_pos, self.pickup_node(req_id));
+            }
+        }
+    }
+
+    /// Append route `b` to route `a`
+    fn merge_routes(
+        &self,
+        routes: &mut [Option<PartialRoute>],
+        route_of: &mut [usize],
+        a: usize,
+        b: usize,
+        saving: f64,
+    ) {
+        let second = routes[b].take().expect("merged route exists");
+        for &req_id in &second.requests {
+            route_of[req_id] = a;
+        }
+        let first = routes[a].as_mut().expect("merged route exists");
+        first.distance += second.distance - saving;
+        first.requests.extend(second.requests);
+    }
+}
+
+/// Distance of serving a request with its own route on a vehicle
+pub(crate) fn standalone_distance(instance: &Instance, vehicle_id: usize, req_id: usize) -> f64 {
+    let pickup = req_id + 1;
+    let dropoff = req_id + 1 + instance.n_reqs();
+    (instance.start_distance(vehicle_id, pickup) + instance.distance(pickup, dropoff)
+        + instance.end_distance(dropoff, vehicle_id)) as f64
+}
+
+/// Whether the vehicle can serve the stops within its capacity, time windows and route limits
+fn vehicle_feasible(instance: &Instance, vehicle_id: usize, stops: &[usize]) -> bool {
+    instance.route_within_capacity(vehicle_id, stops) && instance.route_constraints_feasible(vehicle_id, stops)
+}
+
+/// Whether at least one vehicle serves the stops within all time windows and route limits
+fn any_vehicle_feasible(instance: &Instance, stops: &[usize]) -> bool {
+    (0..instance.n_vehicles()).any(|k| instance.route_constraints_feasible(k, stops))
+}
+
+/// The requests with the shortest standalone routes on a vehicle that can
+/// serve them alone, as many as gamma or the minimum profit requires. With
+/// profits, standalone routes are net of the profit, and every request whose
+/// profit pays for its standalone route is added beyond the requirement.
+pub(crate) fn cheapest_standalone_requests(instance: &Instance) -> Vec<usize> {
+    let profit = |req_id: usize| instance.profits().map_or(0.0, |profits| profits[req_id]);
+    let mut requests: Vec<(f64, usize)> = (0..instance.n_reqs())
+        .filter_map(|req_id| {
+            let stops = [req_id + 1, req_id + 1 + instance.n_reqs()];
+            (0..instance.n_vehicles())
+                .filter(|&k| vehicle_feasible(instance, k, &stops))
+                .map(|k| standalone_distance(instance, k, req_id))
+                .min_by(|a, b| a.total_cmp(b))
+                .map(|distance| (distance - profit(req_id), req_id))
+        })
+        .collect();
+    requests.sort_by(|a, b| a.0.total_cmp(&b.0).then(a.1.cmp(&b.1)));
+
+    let mut selected = instance.requests_to_cover(requests.iter().map(|&(_, req_id)| req_id));
+    selected.extend(requests.iter()
+        .skip(selected.len())
+        .take_while(|&&(net_cost, _)| net_cost < 0.0)
+        .map(|&(_, req_id)| req_id));
+    selected
+}
+
+impl<'a> Solver for SavingsConstruction<'a> {
+    fn solve(&self) -> Solution {
+        self.construct_solution()
+    }
+
+    fn config_description(&self) -> String {
+        format!("neighbors={}", NEIGHBORS)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::instance::tests::from_text;
+
+    // Requests 0 and 1 next to each other far out, request 2 by the depot
+    fn instance(name: &str, header: &str) -> Instance {
+        from_text(name, &format!("{}\n{}", header, "\
+# demands
+1 1 1
+# request locations
+0 0
+10 0
+12 0
+0 1
+11 0
+13 0
+0 2
+"))
+    }
+
+    #[test]
+    fn merges_the_largest_saving_first() {
+        let instance = instance("savings", "3 2 10 3 0");
+        let solution = SavingsConstruction::new(&instance).solve();
+        assert!(solution.is_valid());
+        assert!(solution.routes.contains(&vec![1, 4, 2, 5]));
+        assert!(solution.routes.contains(&vec![3, 6]));
+    }
+
+    #[test]
+    fn keeps_at_most_one_route_per_vehicle_and_serves_gamma() {
+        let instance = instance("savings_gamma", "3 1 10 2 0");
+        let solution = SavingsConstruction::new(&instance).solve();
+        assert!(solution.is_valid());
+        assert_eq!(solution.routes.len(), 1);
+        // The two requests with the shortest standalone routes
+        let mut served = solution.served_requests();
+        served.sort();
+        assert_eq!(served, vec![0, 2]);
+    }
+
+    #[test]
+    fn fairness_weight_lowers_the_score_of_long_merges() {
+        let instance = instance("savings_rho", "3 2 10 3 10");
+        let construction = SavingsConstruction::new(&instance);
+        let route = |req_id: usize, distance: f64| PartialRoute { requests: vec![req_id], distance, base: 0 };
+        // Merged length 22 + 26 - 22 = 26, 30% above the target of 20
+        let score = construction.score(22.0, &route(0, 22.0), &route(1, 26.0), 20.0);
+        assert!((score - (22.0 - 10.0 * 0.3)).abs() < 1e-9);
+        // Within the target only the saving counts
+        assert_eq!(construction.score(22.0, &route(0, 22.0), &route(1, 26.0), 30.0), 22.0);
+    }
+}
@@ -1,6 +1,7 @@
 use std::time::Instant;
-use std::cell::RefCell;
+use std::cell::{Cell, RefCell};
 use rand::prelude::*;
+use crate::solver::SearchStats;
 use crate::{Instance, LocalSearch, LocalSearchConfig, Neighborhood, RandomConstruction, Solution, Solver, StepFunction, AcceptanceCriterion};
 
 /// Cooling schedule for Simulated Annealing
@@ -61,7 +62,8 @@ impl Default for SimulatedAnnealingConfig {
 pub struct SimulatedAnnealing<'a> {
     instance: &'a Instance,
     config: SimulatedAnnealingConfig,
-    rng: RefCell<ThreadRng>,
+    rng: RefCell<StdRng>,
+    stats: Cell<SearchStats>,
 }
 
 impl<'a> SimulatedAnnealing<'a> {
@@ -69,13 +71,22 @@ impl<'a> SimulatedAnnealing<'a> {
         Self {
             instance,
             config,
-            rng: RefCell::new(thread_rng()),
+            rng: RefCell::new(StdRng::from_entropy()),
+            stats: Cell::new(SearchStats::default()),
         }
     }
 
+    /// Make the run reproducible; the initial construction is seeded from the same generator
+    pub fn with_seed(self, seed: u64) -> Self {
+        self.rng.replace(StdRng::seed_from_u64(seed));
+        self
+    }
+
     /// Construct initial solution
     fn construct_initial_solution(&self) -> Solution {
-        let random_construction = RandomConstruction::new(self.instance, self.config.biased_construction);
+        let seed = self.rng.borrow_mut().gen();
+        let random_construction = RandomConstruction::new(self.instance, self.config.biased_construction)
+            .with_seed(seed);
         random_construction.solve()
     }
 
@@ -93,6 +104,10 @@ impl<'a> SimulatedAnnealing<'a> {
         let local_search = LocalSearch::new(self.instance, local_search_config);
         let neighbors = local_search.generate_neighbors(current);
 
+        let mut stats = self.stats.get();
+        stats.evaluations += neighbors.len();
+        self.stats.set(stats);
+
         if neighbors.is_empty() {
             return None;
         }
@@ -151,11 +166,13 @@ impl<'a> SimulatedAnnealing<'a> {
 impl<'a> Solver for SimulatedAnnealing<'a> {
     fn solve(&self) -> Solution {
         let start_time = Instant::now();
+        self.stats.set(SearchStats::default());
         
         // Initialize with a random solution
         let mut current = self.construct_initial_solution();
         let mut best_solution = current.clone();
         let mut best_obj = current.objective_function_value();
+        let mut time_to_best = start_time.elapsed().as_secs_f64();
         
         let mut temperature = self.config.initial_temperature;
         let initial_temperature = self.config.initial_temperature;
@@ -185,6 +202,7 @@ impl<'a> Solver for SimulatedAnnealing<'a> {
                         if current_obj < best_obj {
                             best_solution = current.clone();
                             best_obj = current_obj;
+                            time_to_best = start_time.elapsed().as_secs_f64();
                         }
                     } else {
                         _rejected_count += 1;
@@ -209,8 +227,21 @@ impl<'a> Solver for SimulatedAnnealing<'a> {
             );
         }
 
+        let mut stats = self.stats.get();
+        stats.iterations = iteration;
+        stats.time_to_best_seconds = Some(time_to_best);
+        self.stats.set(stats);
+
         best_solution
     }
+
+    fn stats(&self) -> SearchStats {
+        self.stats.get()
+    }
+
+    fn config_description(&self) -> String {
+        format!("{:?}", self.config)
+    }
 }
 
 /// Builder for SimulatedAnnealing to make configuration easier
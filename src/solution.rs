@@ -1,8 +1,10 @@
 use std::fmt;
 use std::fs::File;
-use std::io::{Write};
+use std::io::{BufRead, BufReader, Write};
 use std::path::Path;
 
+use crate::fairness::{jain_index, FairnessMeasure};
+use crate::breakdown::{ObjectiveBreakdown, RouteBreakdown};
 use crate::instance::Instance;
 
 #[derive(Debug, Clone)]
@@ -28,25 +30,89 @@ impl Solution {
     }
 
     pub fn jain_fairness(&self) -> f64 {
-        let distances = self.get_route_distances();
-        let sum: f64 = distances.iter().sum();
-        let sum_sq: f64 = distances.iter().map(|d| d * d).sum();
-        let k = distances.len() as f64;
-
-        // numeric safety
-        let eps = 1e-12;
-        if sum_sq <= eps {
-            return 1.0; // all routes empty (or numerically zero) → perfectly fair
-        }
+        jain_index(&self.get_route_distances())
+    }
 
-        (sum * sum) / (k * sum_sq)
+    /// Fairness score under the instance's fairness measure (higher is fairer)
+    pub fn fairness(&self) -> f64 {
+        self.instance.fairness_measure().fairness(&self.get_route_distances())
     }
 
+    /// Unfairness term of the objective, without the rho factor
+    pub fn fairness_penalty(&self) -> f64 {
+        self.instance.fairness_measure().penalty(&self.get_route_distances())
+    }
 
+    /// Vehicle costs + rho * fairness penalty + unserved profit; with the
+    /// default fleet the vehicle costs equal the total travel distance, and
+    /// without profits nothing is added for unserved requests
     pub fn objective_function_value(&self) -> f64 {
-        let dist_sum: f64 = self.get_route_distances().iter().sum();
-        let fairness = self.jain_fairness();
-        dist_sum + self.instance.rho() * (1.0 - fairness)
+        let distances = self.get_route_distances();
+        let cost_sum: f64 = self.get_route_costs().iter().sum();
+        let penalty = self.instance.fairness_measure().penalty(&distances);
+        let unserved_profit = self.instance.unserved_profit(&self.served_requests());
+        cost_sum + self.instance.rho() * penalty + unserved_profit
+    }
+
+    /// Requests whose dropoff is in some route
+    pub fn served_requests(&self) -> Vec<usize> {
+        let n_reqs = self.instance.n_reqs();
+        self.routes.iter()
+            .flatten()
+            .filter(|&&node| node > n_reqs && node <= 2 * n_reqs)
+            .map(|&node| node - n_reqs - 1)
+            .collect()
+    }
+
+    /// Per-route view of the objective: distances, loads, served requests and
+    /// each route's contribution to the fairness penalty
+    pub fn objective_breakdown(&self) -> ObjectiveBreakdown {
+        let n_reqs = self.instance.n_reqs();
+        let demands = self.instance.demands();
+        let measure = self.instance.fairness_measure();
+        let distances = self.get_route_distances();
+        let fairness_penalty = measure.penalty(&distances);
+        let contributions = measure.contributions(&distances);
+
+        let routes: Vec<RouteBreakdown> = self.routes.iter()
+            .zip(&distances)
+            .zip(contributions)
+            .map(|((route, &distance), unfairness_contribution)| {
+                let mut load = 0usize;
+                let mut load_profile = Vec::with_capacity(route.len());
+                let mut served_requests = 0;
+                for &node in route {
+                    if node >= 1 && node <= n_reqs {
+                        load += demands[node - 1];
+                    } else if node > n_reqs && node <= 2 * n_reqs {
+                        load = load.saturating_sub(demands[node - n_reqs - 1]);
+                        served_requests += 1;
+                    }
+                    load_profile.push(load);
+                }
+
+                RouteBreakdown {
+                    distance,
+                    stops: route.iter().filter(|&&node| !self.instance.is_depot(node)).count(),
+                    max_load: load_profile.iter().copied().max().unwrap_or(0),
+                    load_profile,
+                    served_requests,
+                    unfairness_contribution,
+                }
+            })
+            .collect();
+
+        let distance_term: f64 = distances.iter().sum();
+        ObjectiveBreakdown {
+            served_requests: routes.iter().map(|r| r.served_requests).sum(),
+            idle_vehicles: routes.iter().filter(|r| r.is_idle()).count(),
+            routes,
+            distance_term,
+            fleet_cost_term: self.get_route_costs().iter().sum::<f64>() - distance_term,
+            fairness_penalty,
+            fairness_term: self.instance.rho() * fairness_penalty,
+            unserved_profit_term: self.instance.unserved_profit(&self.served_requests()),
+        }
     }
 
     pub fn total_travel_distance(&self) -> f64 {
@@ -54,34 +120,21 @@ impl Solution {
     }
 
     pub fn get_route_distances(&self) -> Vec<f64> {
-        let dist_matrix = self.instance.compute_distance_matrix();
-        
+        // Distances on the fly: the full matrix does not fit in memory for the largest instances
         self.routes.iter()
-            .map(|route| {
-                if route.is_empty() {
-                    return 0.0;
-                }
-                
-                let mut distance = 0.0;
-                
-                // Distance from depot to first stop
-                if route[0] != 0 { // if first stop is not depot
-                    distance += dist_matrix[0][route[0]] as f64;
-                }
-                
-                // Distance between consecutive stops
-                for i in 0..route.len() - 1 {
-                    distance += dist_matrix[route[i]][route[i + 1]] as f64;
-                }
-                
-                // Distance from last stop back to depot
-                if let Some(&last_stop) = route.last() {
-                    if last_stop != 0 { // if last stop is not depot
-                        distance += dist_matrix[last_stop][0] as f64;
-                    }
-                }
-                
-                distance
+            .enumerate()
+            .map(|(vehicle_id, route)| self.instance.route_distance(vehicle_id, route))
+            .collect()
+    }
+
+    /// Fixed and per-distance cost of every vehicle, zero for unused vehicles
+    pub fn get_route_costs(&self) -> Vec<f64> {
+        self.routes.iter()
+            .zip(self.get_route_distances())
+            .enumerate()
+            .map(|(vehicle_id, (route, distance))| {
+                let used = route.iter().any(|&node| !self.instance.is_depot(node));
+                self.instance.vehicle(vehicle_id).route_cost(distance, used)
             })
             .collect()
     }
@@ -95,43 +148,95 @@ impl Solution {
             .unwrap_or(self.instance.name());
         writeln!(file, "{}", clean_name)?;
 
-        // Write each vehicle's route (only request location indices, no depot)
+        // Write each vehicle's route; depot visits between trips are kept, the
+        // start and end depots are implicit
         for route in &self.routes {
             if route.is_empty() {
                 writeln!(file)?;
                 continue;
             }
             
-            // Filter out depot (index 0) and write only request locations
-            let request_stops: Vec<String> = route
+            let stops: Vec<String> = route
                 .iter()
-                .filter(|&&stop| stop != 0)
                 .map(|stop| stop.to_string())
                 .collect();
                 
-            writeln!(file, "{}", request_stops.join(" "))?;
+            writeln!(file, "{}", stops.join(" "))?;
         }
 
         Ok(())
     }
 
+    /// Read a solution written by `to_file`: the instance name followed by one
+    /// line of stops per vehicle. Depot visits are only accepted for multi-trip
+    /// instances.
+    pub fn from_file(instance: Instance, filename: &str) -> Result<Self, Box<dyn std::error::Error>> {
+        let file = File::open(filename)?;
+        let reader = BufReader::new(file);
+        let mut lines = reader.lines();
+
+        // First line holds the instance name
+        lines.next().ok_or("Empty solution file")??;
+
+        let mut routes = Vec::new();
+        for line in lines {
+            let route: Vec<usize> = line?
+                .split_whitespace()
+                .map(|s| s.parse())
+                .collect::<Result<_, _>>()?;
+            routes.push(route);
+        }
+
+        if let Some(&node) = routes.iter().flatten().find(|&&node| node >= instance.n_nodes()) {
+            return Err(format!("Invalid location index {} in solution", node).into());
+        }
+
+        // Depot visits between trips need a multi-trip instance
+        if !instance.multi_trip() {
+            if let Some(vehicle_id) = routes.iter().position(|route| route.iter().any(|&node| instance.is_depot(node))) {
+                return Err(format!(
+                    "Route of vehicle {} visits a depot, but {} is not a multi-trip instance",
+                    vehicle_id + 1,
+                    instance.name()
+                ).into());
+            }
+        }
+
+        if routes.len() > instance.n_vehicles() {
+            return Err(format!("Solution has {} routes but only {} vehicles", routes.len(), instance.n_vehicles()).into());
+        }
+
+        // Missing trailing lines are vehicles without a route
+        if routes.len() < instance.n_vehicles() {
+            routes.resize(instance.n_vehicles(), Vec::new());
+        }
+
+        Ok(Self::new(instance, routes))
+    }
+
     pub fn is_valid(&self) -> bool {
         let n_reqs = self.instance.n_reqs();
-        let capacity = self.instance.cap();
         let demands = self.instance.demands();
-        let gamma = self.instance.gamma();
+
+        if self.routes.len() > self.instance.n_vehicles() {
+            return false;
+        }
 
         // Track which requests are served & by which vehicle
         let mut served_by = vec![None; n_reqs];
-        let mut served_count = 0;
+        let mut served = Vec::new();
 
         for (vehicle_id, route) in self.routes.iter().enumerate() {
             let mut load = 0usize;
             let mut picked_up = vec![false; n_reqs]; // Track pickups within this route
 
             for &node in route {
-                // Skip depot nodes for request tracking (but still important for capacity)
-                if node == 0 {
+                // A depot visit ends a trip: only multi-trip instances allow it,
+                // and only with an empty vehicle
+                if self.instance.is_depot(node) {
+                    if !self.instance.multi_trip() || load != 0 {
+                        return false;
+                    }
                     continue;
                 }
                 
@@ -145,7 +250,7 @@ impl Solution {
                     }
                     
                     // Check capacity
-                    if load + demands[req_id] > capacity {
+                    if !self.instance.within_capacity(vehicle_id, load + demands[req_id]) {
                         return false;
                     }
                     
@@ -168,7 +273,7 @@ impl Solution {
                     }
                     
                     load -= demands[req_id];
-                    served_count += 1;
+                    served.push(req_id);
                 }
             }
             
@@ -176,10 +281,14 @@ impl Solution {
             if load != 0 {
                 return false;
             }
+
+            if !self.instance.route_constraints_feasible(vehicle_id, route) {
+                return false;
+            }
         }
 
-        // Check if we served at least gamma requests
-        served_count >= gamma
+        // Check if we served at least gamma requests, or the minimum profit
+        self.instance.coverage_met(&served)
     }
 }
 
@@ -187,8 +296,17 @@ impl fmt::Display for Solution {
     fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
         writeln!(f, "Solution for instance: {}", self.instance.name())?;
         writeln!(f, "Number of routes: {}", self.routes.len())?;
+        let idle_vehicles = self.routes.iter()
+            .filter(|route| route.iter().all(|&node| self.instance.is_depot(node)))
+            .count();
+        if idle_vehicles > 0 {
+            writeln!(f, "Idle vehicles: {}", idle_vehicles)?;
+        }
         writeln!(f, "Total travel distance: {:.2}", self.total_travel_distance())?;
         writeln!(f, "Jain fairness: {:.4}", self.jain_fairness())?;
+        if self.instance.fairness_measure() != FairnessMeasure::Jain {
+            writeln!(f, "Fairness ({}): {:.4}", self.instance.fairness_measure(), self.fairness())?;
+        }
         writeln!(f, "Objective value: {:.2}", self.objective_function_value())?;
         writeln!(f, "Valid solution: {}", self.is_valid())?;
         
@@ -204,7 +322,64 @@ impl fmt::Display for Solution {
             writeln!(f, "  Vehicle {} (distance: {:.2}): {}", 
                      i + 1, distance, route_with_depot.join(" → "))?;
         }
+
+        // `{:#}` additionally prints the objective breakdown
+        if f.alternate() {
+            write!(f, "{}", self.objective_breakdown())?;
+        }
         
         Ok(())
     }
-}
\ No newline at end of file
+}
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::instance::tests::from_text;
+
+    // Two requests on a line, one unit of capacity per vehicle: both fit only
+    // into one route as two trips
+    const MULTI_TRIP: &str = "\
+2 2 1 2 0
+# demands
+1 1
+# request locations
+0 0
+1 0
+2 0
+3 0
+4 0
+# multi trip
+";
+
+    #[test]
+    fn depot_visits_end_trips_of_empty_vehicles() {
+        let instance = from_text("multi_trip", MULTI_TRIP);
+        let solution = Solution::new(instance.clone(), vec![vec![1, 3, 0, 2, 4], vec![]]);
+        assert!(solution.is_valid());
+        // 1 + 2 + 3 out and back, 2 + 2 + 4 the second time
+        assert_eq!(solution.get_route_distances(), vec![14.0, 0.0]);
+        assert!(!Solution::new(instance, vec![vec![1, 0, 3, 2, 4], vec![]]).is_valid());
+    }
+
+    #[test]
+    fn depot_visits_need_a_multi_trip_instance() {
+        let instance = from_text("multi_trip_off", MULTI_TRIP).with_multi_trip(false);
+        assert!(!Solution::new(instance.clone(), vec![vec![1, 3, 0, 2, 4], vec![]]).is_valid());
+        assert!(Solution::new(instance, vec![vec![1, 3], vec![2, 4]]).is_valid());
+    }
+
+    #[test]
+    fn solution_files_keep_trip_boundaries() {
+        let instance = from_text("multi_trip_file", MULTI_TRIP);
+        let solution = Solution::new(instance.clone(), vec![vec![1, 3, 0, 2, 4], vec![]]);
+        let path = std::env::temp_dir().join(format!("scf_pdp_multi_trip_solution_{}.txt", std::process::id()));
+        let path = path.to_str().unwrap();
+        solution.to_file(path).unwrap();
+
+        let read = Solution::from_file(instance.clone(), path).unwrap();
+        assert_eq!(read.routes, solution.routes);
+        let error = Solution::from_file(instance.with_multi_trip(false), path).unwrap_err();
+        assert!(error.to_string().contains("not a multi-trip instance"));
+        std::fs::remove_file(path).unwrap();
+    }
+}
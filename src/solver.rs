@@ -1,6 +1,27 @@
 use crate::{Solution};
 
+/// Counters of a solver's last `solve` call
+#[derive(Debug, Clone, Copy, Default, PartialEq)]
+pub struct SearchStats {
+    /// Iterations of the main loop
+    pub iterations: usize,
+    /// Candidate solutions whose objective was evaluated
+    pub evaluations: usize,
+    /// Seconds from the start of `solve` until the returned solution was found
+    pub time_to_best_seconds: Option<f64>,
+}
+
 pub trait Solver {
     /// Solve the instance and return a solution
     fn solve(&self) -> Solution;
-}
\ No newline at end of file
+
+    /// Counters of the last `solve` call; constructive solvers report none
+    fn stats(&self) -> SearchStats {
+        SearchStats::default()
+    }
+
+    /// Parameters the solver was configured with, for recording next to results
+    fn config_description(&self) -> String {
+        String::new()
+    }
+}
@@ -1,394 +1,536 @@
+use std::cell::RefCell;
 use std::time::Instant;
-use std::fs::{self, File};
-use std::io::Write;
+use std::fs;
 use std::path::Path;
-use crate::{BeamSearch, DeterministicConstruction, Instance, Solution, RandomConstruction, Solver, LocalSearch};
+use crate::{BeamSearch, DeterministicConstruction, InsertionConstruction, InsertionStrategy, SavingsConstruction, ClusterFirstConstruction, ClusteringMethod, Instance, Solution, RandomConstruction, Solver, LocalSearch};
 use crate::local_search::{LocalSearchConfig, Neighborhood};
+use crate::stochastic::MonteCarloEvaluator;
 use crate::vnd::VND;
 use crate::grasp::{GRASP, GRASPConfig};
 use crate::sim_annealing::{SimulatedAnnealing, SimulatedAnnealingConfig};
+use crate::bks::{BestKnownRegistry, DEFAULT_REGISTRY_PATH};
+use crate::pareto::{BiObjectiveConfig, EpsilonConstraint, ParetoFront};
+use crate::dynamic::{simulate, OnlineConfig, SimulationReport};
+use crate::benchmark::{Benchmark, BenchmarkEntry, BenchmarkResults};
+use crate::results::{JobKey, ResultsError, ResultsWriter};
+use crate::provenance::Provenance;
+
+/// Demand scenarios robust local search checks its neighbors against
+const ROBUST_SEARCH_SAMPLES: usize = 200;
+/// Demand scenarios every solution of an instance with stochastic demands is
+/// evaluated on; the same scenarios for all solvers and seeds
+const EVALUATION_SAMPLES: usize = 1000;
+const EVALUATION_SEED: u64 = 0;
 
 pub struct SolverRuntime {
     instances: Vec<Instance>,
+    // Updated whenever a run finds a valid solution better than the best known
+    registry: RefCell<Option<BestKnownRegistry>>,
+    // Continue existing results files instead of overwriting them
+    resume: bool,
+    // Every solver runs once per seed and instance
+    seeds: Vec<u64>,
+    // Recorded in every results row
+    provenance: Provenance,
+    // Bound on each vehicle's overload probability for local search on
+    // instances with stochastic demands
+    max_violation_probability: Option<f64>,
 }
 
 impl SolverRuntime {
     pub fn new(instances: Vec<Instance>) -> Self {
-        Self { instances }
+        let registry = match BestKnownRegistry::load(DEFAULT_REGISTRY_PATH) {
+            Ok(registry) => Some(registry),
+            Err(e) => {
+                eprintln!("Failed to load best known solutions from {}: {}", DEFAULT_REGISTRY_PATH, e);
+                None
+            }
+        };
+        Self {
+            instances,
+            registry: RefCell::new(registry),
+            resume: false,
+            seeds: vec![0],
+            provenance: Provenance::detect(),
+            max_violation_probability: None,
+        }
+    }
+
+    /// Use a different best-known-solutions registry, or none at all
+    pub fn with_registry(mut self, registry: Option<BestKnownRegistry>) -> Self {
+        self.registry = RefCell::new(registry);
+        self
+    }
+
+    /// Append to existing results files and skip the runs they already contain,
+    /// e.g. to continue an interrupted sweep
+    pub fn with_resume(mut self, resume: bool) -> Self {
+        self.resume = resume;
+        self
+    }
+
+    /// Seeds to run every solver with; one results row per instance and seed.
+    /// Defaults to the single seed 0, so repeated sweeps give the same results.
+    pub fn with_seeds(mut self, seeds: Vec<u64>) -> Self {
+        self.seeds = seeds;
+        self
+    }
+
+    /// Run local search in robust mode on instances with stochastic demands,
+    /// keeping every vehicle's overload probability at most the bound
+    pub fn with_robustness(mut self, max_violation_probability: Option<f64>) -> Self {
+        self.max_violation_probability = max_violation_probability;
+        self
+    }
+
+    /// Update the registry with `solution` and return its gap to the best known
+    /// value; invalid solutions have no gap
+    fn record_best_known(&self, solution: &Solution, solver_name: &str) -> Option<f64> {
+        if !solution.is_valid() {
+            return None;
+        }
+
+        let mut registry = self.registry.borrow_mut();
+        let registry = registry.as_mut()?;
+
+        match registry.update(solution, solver_name) {
+            Ok(true) => println!("  New best known solution for {}", solution.instance.name()),
+            Ok(false) => {}
+            Err(e) => eprintln!("Failed to update best known solutions: {}", e),
+        }
+
+        registry.gap(solution.instance.name(), solution.instance.fairness_measure(), solution.objective_function_value())
+    }
+
+    pub fn run_deterministic(&self) -> Result<Vec<Solution>, ResultsError> {
+        self.run_generic("deterministic", |instance, _seed| {
+            Box::new(DeterministicConstruction::new(instance))
+        })
     }
 
-    pub fn run_deterministic(&self) -> Vec<Solution> {
-        self.run_generic("deterministic", |instance| {
-            let solver = DeterministicConstruction::new(instance);
-            solver.solve()
+    pub fn run_random(&self) -> Result<Vec<Solution>, ResultsError> {
+        self.run_generic("random", |instance, seed| {
+            Box::new(RandomConstruction::new(instance, false).with_seed(seed))
         })
     }
 
-    pub fn run_random(&self) -> Vec<Solution> {
-        self.run_generic("random", |instance| {
-            let solver = RandomConstruction::new(instance, false);
-            solver.solve()
+    pub fn run_insertion(&self, strategy: InsertionStrategy) -> Result<Vec<Solution>, ResultsError> {
+        self.run_generic(&strategy.name(), move |instance, _seed| {
+            Box::new(InsertionConstruction::new(instance, strategy))
         })
     }
 
-    pub fn run_beam_search(&self, beam_width: usize, max_depth: usize) -> Vec<Solution> {
-        self.run_generic("beam_search", |instance| {
-            let solver = BeamSearch::new(instance.clone())
+    pub fn run_savings(&self) -> Result<Vec<Solution>, ResultsError> {
+        self.run_generic("savings", |instance, _seed| {
+            Box::new(SavingsConstruction::new(instance))
+        })
+    }
+
+    pub fn run_cluster_first(&self, method: ClusteringMethod) -> Result<Vec<Solution>, ResultsError> {
+        self.run_generic(method.name(), move |instance, _seed| {
+            Box::new(ClusterFirstConstruction::new(instance, method))
+        })
+    }
+
+    pub fn run_beam_search(&self, beam_width: usize, max_depth: usize) -> Result<Vec<Solution>, ResultsError> {
+        self.run_generic("beam_search", move |instance, _seed| {
+            Box::new(BeamSearch::new(instance.clone())
                 .with_beam_width(beam_width)
-                .with_max_depth(max_depth);
-            solver.solve()
+                .with_max_depth(max_depth))
         })
     }
 
-    pub fn run_local_search(&self, config: LocalSearchConfig) -> Vec<Solution> {
-        self.run_generic("local_search", |instance| {
-            let solver = LocalSearch::new(instance, config.clone());
-            solver.solve()
+    pub fn run_local_search(&self, config: LocalSearchConfig) -> Result<Vec<Solution>, ResultsError> {
+        let max_violation_probability = self.max_violation_probability;
+        self.run_generic("local_search", move |instance, seed| {
+            let local_search = LocalSearch::new(instance, config.clone());
+            match max_violation_probability {
+                Some(max_probability) if instance.stochastic_demands().is_some() => {
+                    let evaluator = MonteCarloEvaluator::new(instance, ROBUST_SEARCH_SAMPLES, seed);
+                    Box::new(local_search.with_robustness(evaluator, max_probability))
+                }
+                _ => Box::new(local_search),
+            }
         })
     }
 
-    pub fn run_vnd(&self, neighborhoods: &[Neighborhood], max_iterations: usize) -> Vec<Solution> {
-        self.run_generic("vnd", |instance| {
-            let solver = VND::new(instance, neighborhoods.to_vec())
-                .with_max_iterations(max_iterations);
-            solver.solve()
+    pub fn run_vnd(&self, neighborhoods: &[Neighborhood], max_iterations: usize) -> Result<Vec<Solution>, ResultsError> {
+        let neighborhoods = neighborhoods.to_vec();
+        self.run_generic("vnd", move |instance, seed| {
+            Box::new(VND::new(instance, neighborhoods.clone())
+                .with_max_iterations(max_iterations)
+                .with_seed(seed))
         })
     }
 
-    pub fn run_grasp(&self, config: GRASPConfig) -> Vec<Solution> {
-        self.run_generic("grasp", |instance| {
-            let solver = GRASP::new(instance, config.clone());
-            solver.solve()
+    pub fn run_grasp(&self, config: GRASPConfig) -> Result<Vec<Solution>, ResultsError> {
+        self.run_generic("grasp", move |instance, seed| {
+            Box::new(GRASP::new(instance, config.clone()).with_seed(seed))
         })
     }
 
-    pub fn run_simulated_annealing(&self, config: SimulatedAnnealingConfig) -> Vec<Solution> {
-        self.run_generic("simulated_annealing", |instance| {
-            let solver = SimulatedAnnealing::new(instance, config.clone());
-            solver.solve()
+    pub fn run_simulated_annealing(&self, config: SimulatedAnnealingConfig) -> Result<Vec<Solution>, ResultsError> {
+        self.run_generic("simulated_annealing", move |instance, seed| {
+            Box::new(SimulatedAnnealing::new(instance, config.clone()).with_seed(seed))
         })
     }
 
-    fn run_generic<F>(&self, solver_name: &str, solver_fn: F) -> Vec<Solution> 
-    where 
-        F: Fn(&Instance) -> Solution,
-    {
-        let mut solutions = Vec::new();
-        let mut csv_data = Vec::new();
-        
-        if let Some(first_instance) = self.instances.first() {
-            let instance_size = first_instance.n_reqs().to_string();
-            let results_dir = format!("results/{}", instance_size);
-            let solutions_dir = format!("solutions/{}/{}", instance_size, solver_name);
-            fs::create_dir_all(&results_dir).expect("Failed to create results directory");
-            fs::create_dir_all(&solutions_dir).expect("Failed to create solutions directory");
-            
-            csv_data.push("instance_name,time_seconds,objective_value,jain_fairness,num_vehicles".to_string());
+    /// Computes a distance/fairness Pareto front per instance and seed and writes
+    /// each front to `results/<size>/pareto/<instance>.csv`. With `config.pick`
+    /// the chosen point is saved to `solutions/<size>/pareto/<instance>.sol`.
+    /// With several seeds both file names end in `_s<seed>`.
+    pub fn run_bi_objective(&self, config: BiObjectiveConfig) -> Vec<ParetoFront> {
+        let mut fronts = Vec::new();
+
+        let Some(first_instance) = self.instances.first() else {
+            return fronts;
+        };
+        let pareto_dir = format!("results/{}/pareto", first_instance.n_reqs());
+        let solutions_dir = format!("solutions/{}/pareto", first_instance.n_reqs());
+        for dir in [&pareto_dir, &solutions_dir] {
+            if let Err(e) = fs::create_dir_all(dir) {
+                eprintln!("Failed to create directory {}: {}", dir, e);
+            }
         }
-        
+
         for instance in &self.instances {
-            println!("Solving instance: {}", instance.name());
-            
-            let start_time = Instant::now();
-            let solution = solver_fn(instance);
-            let duration = start_time.elapsed();
-            let time_seconds = duration.as_secs_f64();
-            
-            println!("  Solved in {:.2?}", duration);
-            println!("  Objective value: {:.2}", solution.objective_function_value());
-            println!("  Total distance: {:.2}", solution.total_travel_distance());
-            println!("  Jain fairness: {:.4}", solution.jain_fairness());
-            println!("  Valid solution: {}", solution.is_valid());
-            println!();
-            
-            // Add CSV row
-            let csv_row = format!(
-                "{},{:.6},{:.6},{:.6},{}",
-                instance.name(),
-                time_seconds,
-                solution.objective_function_value(),
-                solution.jain_fairness(),
-                solution.routes.len()
-            );
-            csv_data.push(csv_row);
-            
-            // Write solution file
-            if let Some(first_instance) = self.instances.first() {
-                let instance_size = first_instance.n_reqs().to_string();
-                let solution_filename = format!(
-                    "solutions/{}/{}/{}.sol",
-                    instance_size,
-                    solver_name,
-                    Path::new(instance.name())
-                        .file_stem()
-                        .and_then(|s| s.to_str())
-                        .unwrap_or(instance.name())
-                );
-                
-                if let Err(e) = solution.to_file(&solution_filename) {
-                    eprintln!("Failed to write solution file {}: {}", solution_filename, e);
+            for &seed in &self.seeds {
+                println!("Computing Pareto front for instance: {} (seed {})", instance.name(), seed);
+
+                let start_time = Instant::now();
+                let solver = EpsilonConstraint::new(instance, config.clone()).with_seed(seed);
+                let front = solver.solve_front();
+                let duration = start_time.elapsed();
+
+                println!("  Solved in {:.2?}", duration);
+                println!("  Non-dominated points: {}", front.len());
+                for (distance, fairness) in front.objectives() {
+                    println!("    distance {:.2}, jain fairness {:.4}", distance, fairness);
+                }
+
+                let csv_filename = self.run_filename(&pareto_dir, instance, "", seed, "csv");
+                if let Err(e) = front.to_csv(&csv_filename) {
+                    eprintln!("Failed to write Pareto front {}: {}", csv_filename, e);
                 } else {
-                    println!("  Solution written to: {}", solution_filename);
+                    println!("  Pareto front written to: {}", csv_filename);
                 }
+
+                if let Some(pick) = config.pick {
+                    match front.pick(pick) {
+                        Some(solution) => {
+                            println!(
+                                "  Picked by {}: distance {:.2}, jain fairness {:.4}",
+                                pick,
+                                solution.total_travel_distance(),
+                                solution.jain_fairness()
+                            );
+                            let sol_filename = self.solution_filename(&solutions_dir, instance, "", seed);
+                            if let Err(e) = solution.to_file(&sol_filename) {
+                                eprintln!("Failed to write solution {}: {}", sol_filename, e);
+                            }
+                        }
+                        None => println!("  No point satisfies {}", pick),
+                    }
+                }
+                println!();
+
+                fronts.push(front);
             }
-            
-            solutions.push(solution);
         }
-        
-        // Write CSV file
-        if let Some(first_instance) = self.instances.first() {
-            let instance_size = first_instance.n_reqs().to_string();
-            let csv_filename = format!("results/{}/{}.csv", instance_size, solver_name);
-            
-            let mut file = File::create(&csv_filename).expect("Failed to create CSV file");
-            for line in csv_data {
-                writeln!(file, "{}", line).expect("Failed to write to CSV file");
-            }
-            println!("Results written to: {}", csv_filename);
+
+        fronts
+    }
+
+    /// Replay every instance online, revealing requests at their release
+    /// times, and compare with offline regret-3 insertion
+    pub fn run_online_simulation(&self, config: OnlineConfig) -> Vec<SimulationReport> {
+        let mut reports = Vec::new();
+        for instance in &self.instances {
+            println!("Simulating online routing for instance: {}", instance.name());
+
+            let offline = InsertionConstruction::regret(instance, 3).solve();
+            let start_time = Instant::now();
+            let report = simulate(instance, offline.objective_function_value(), config.clone());
+            println!("  Simulated in {:.2?}", start_time.elapsed());
+            print!("{}", report);
+            println!();
+
+            reports.push(report);
         }
-        
-        solutions
+        reports
     }
 
-    pub fn run_comparison(&self) -> Vec<(String, Solution, Solution, Solution, Solution)> {
-        let mut results = Vec::new();
-        let mut csv_data = Vec::new();
-        
-        // Create results directory structure based on instance size
-        if let Some(first_instance) = self.instances.first() {
-            let instance_size = first_instance.n_reqs().to_string();
-            let results_dir = format!("results/{}", instance_size);
-            let solutions_dir = format!("solutions/{}/comparison", instance_size);
-            fs::create_dir_all(&results_dir).expect("Failed to create results directory");
-            fs::create_dir_all(&solutions_dir).expect("Failed to create solutions directory");
-            
-            // Add CSV header for comparison
-            csv_data.push("instance_name,det_time,det_objective,det_fairness,det_vehicles,rand_time,rand_objective,rand_fairness,rand_vehicles,beam_time,beam_objective,beam_fairness,beam_vehicles,local_time,local_objective,local_fairness,local_vehicles".to_string());
+    /// Open `results/<size>/<name>.csv` with the given header, continuing an
+    /// existing file in resume mode
+    fn open_results(&self, instance_size: &str, name: &str, header: &str) -> Result<ResultsWriter, ResultsError> {
+        let results_dir = format!("results/{}", instance_size);
+        fs::create_dir_all(&results_dir).map_err(|e| ResultsError::Io { path: results_dir.clone(), source: e })?;
+
+        let csv_filename = format!("{}/{}.csv", results_dir, name);
+        if self.resume {
+            ResultsWriter::resume(&csv_filename, header, name)
+        } else {
+            ResultsWriter::create(&csv_filename, header)
         }
+    }
+
+    /// Header of every results file: the run, the solution, the gap to the
+    /// best known solution and where the run happened
+    fn results_header() -> String {
+        format!("{},gap_to_bks,{}", BenchmarkEntry::csv_header(), Provenance::csv_header())
+    }
+
+    fn results_row(&self, entry: &BenchmarkEntry, gap_to_bks: Option<f64>) -> String {
+        format!(
+            "{},{},{}",
+            entry.csv_fields(),
+            gap_to_bks.map(|gap| format!("{:.6}", gap)).unwrap_or_default(),
+            self.provenance.csv_fields()
+        )
+    }
+
+    /// Solution file name of a run; the seed is only part of it with several seeds
+    fn solution_filename(&self, dir: &str, instance: &Instance, suffix: &str, seed: u64) -> String {
+        self.run_filename(dir, instance, suffix, seed, "sol")
+    }
+
+    /// `<dir>/<instance><suffix>.<extension>`, with `_s<seed>` before the
+    /// extension when running several seeds
+    fn run_filename(&self, dir: &str, instance: &Instance, suffix: &str, seed: u64, extension: &str) -> String {
+        let instance_stem = Path::new(instance.name())
+            .file_stem()
+            .and_then(|s| s.to_str())
+            .unwrap_or(instance.name());
+        if self.seeds.len() > 1 {
+            format!("{}/{}{}_s{}.{}", dir, instance_stem, suffix, seed, extension)
+        } else {
+            format!("{}/{}{}.{}", dir, instance_stem, suffix, extension)
+        }
+    }
+
+    /// Solves every instance once per seed and appends one row per run to
+    /// `results/<size>/<solver>.csv`. In resume mode runs already in the
+    /// file are skipped and not part of the returned solutions.
+    fn run_generic<F>(&self, solver_name: &str, factory: F) -> Result<Vec<Solution>, ResultsError>
+    where 
+        F: for<'a> Fn(&'a Instance, u64) -> Box<dyn Solver + 'a> + 'static,
+    {
+        let mut solutions = Vec::new();
+
+        let Some(first_instance) = self.instances.first() else {
+            return Ok(solutions);
+        };
+        let instance_size = first_instance.n_reqs().to_string();
+        let solutions_dir = format!("solutions/{}/{}", instance_size, solver_name);
+        fs::create_dir_all(&solutions_dir).map_err(|e| ResultsError::Io { path: solutions_dir.clone(), source: e })?;
+
+        let mut writer = self.open_results(&instance_size, solver_name, &Self::results_header())?;
+        let benchmark = Benchmark::new().with_algorithm(solver_name, factory);
         
         for instance in &self.instances {
-            println!("Comparing solvers for instance: {}", instance.name());
-            
-            let det_start = Instant::now();
-            let det_solver = DeterministicConstruction::new(instance);
-            let det_solution = det_solver.solve();
-            let det_time = det_start.elapsed().as_secs_f64();
-            
-            let rand_start = Instant::now();
-            let rand_solver = RandomConstruction::new(instance, false);
-            let rand_solution = rand_solver.solve();
-            let rand_time = rand_start.elapsed().as_secs_f64();
-            
-            let beam_start = Instant::now();
-            let beam_solver = BeamSearch::new(instance.clone()).with_beam_width(20).with_max_depth(150);
-            let beam_solution = beam_solver.solve();
-            let beam_time = beam_start.elapsed().as_secs_f64();
-            
-            let local_start = Instant::now();
-            let local_solver = LocalSearch::new(instance, LocalSearchConfig::default());
-            let local_solution = local_solver.solve();
-            let local_time = local_start.elapsed().as_secs_f64();
-            
-            // Write solution files for each method
-            if let Some(first_instance) = self.instances.first() {
-                let instance_size = first_instance.n_reqs().to_string();
-                let instance_stem = Path::new(instance.name())
-                    .file_stem()
-                    .and_then(|s| s.to_str())
-                    .unwrap_or(instance.name());
+            for &seed in &self.seeds {
+                let job = JobKey::new(instance.name(), solver_name, Some(seed));
+                if writer.is_done(&job) {
+                    println!("Skipping instance {} (seed {}): already in {}", instance.name(), seed, writer.path());
+                    continue;
+                }
+
+                println!("Solving instance: {} (seed {})", instance.name(), seed);
                 
-                let methods = [
-                    ("deterministic", &det_solution),
-                    ("random", &rand_solution),
-                    ("beam_search", &beam_solution),
-                    ("local_search", &local_solution),
-                ];
+                let entry = benchmark.run_one(0, instance, seed);
+                let solution = &entry.solution;
                 
-                for (method_name, solution) in methods {
-                    let solution_filename = format!(
-                        "solutions/{}/comparison/{}_{}.sol",
-                        instance_size, instance_stem, method_name
+                println!("  Solved in {:.2?}", std::time::Duration::from_secs_f64(entry.time_seconds));
+                println!("  Objective value: {:.2}", solution.objective_function_value());
+                println!("  Total distance: {:.2}", solution.total_travel_distance());
+                println!("  Jain fairness: {:.4}", solution.jain_fairness());
+                println!("  Valid solution: {}", solution.is_valid());
+                let breakdown = solution.objective_breakdown();
+                if breakdown.idle_vehicles > 0 {
+                    println!("  Idle vehicles: {}", breakdown.idle_vehicles);
+                }
+                if entry.stats.iterations > 0 {
+                    println!(
+                        "  Iterations: {}, evaluations: {}",
+                        entry.stats.iterations, entry.stats.evaluations
                     );
-                    
-                    if let Err(e) = solution.to_file(&solution_filename) {
-                        eprintln!("Failed to write solution file {}: {}", solution_filename, e);
+                }
+                let gap_to_bks = self.record_best_known(solution, solver_name);
+                if let Some(gap) = gap_to_bks {
+                    println!("  Gap to best known: {:.3}%", gap);
+                }
+                if instance.stochastic_demands().is_some() {
+                    let evaluation = MonteCarloEvaluator::new(instance, EVALUATION_SAMPLES, EVALUATION_SEED)
+                        .evaluate(solution);
+                    println!("  Capacity violation probability: {:.4}", evaluation.violation_probability);
+                    println!("  Expected recourse distance: {:.2}", evaluation.expected_recourse_distance);
+                }
+                println!();
+                
+                // Write solution file before the row, so a row always has its
+                // solution; without one the row is left out and a resumed sweep
+                // runs the job again
+                let solution_filename = self.solution_filename(&solutions_dir, instance, "", seed);
+                match solution.to_file(&solution_filename) {
+                    Ok(()) => {
+                        println!("  Solution written to: {}", solution_filename);
+                        writer.append(job, &self.results_row(&entry, gap_to_bks))?;
                     }
+                    Err(e) => eprintln!("Failed to write solution file {}, leaving out its row: {}", solution_filename, e),
                 }
+                
+                solutions.push(entry.solution);
             }
-            
-            // Add CSV row for comparison
-            let csv_row = format!(
-                "{},{:.6},{:.6},{:.6},{},{:.6},{:.6},{:.6},{},{:.6},{:.6},{:.6},{},{:.6},{:.6},{:.6},{}",
-                instance.name(),
-                det_time,
-                det_solution.objective_function_value(),
-                det_solution.jain_fairness(),
-                det_solution.routes.len(),
-                rand_time,
-                rand_solution.objective_function_value(),
-                rand_solution.jain_fairness(),
-                rand_solution.routes.len(),
-                beam_time,
-                beam_solution.objective_function_value(),
-                beam_solution.jain_fairness(),
-                beam_solution.routes.len(),
-                local_time,
-                local_solution.objective_function_value(),
-                local_solution.jain_fairness(),
-                local_solution.routes.len()
-            );
-            csv_data.push(csv_row);
-            
-            results.push((instance.name().to_string(), det_solution, rand_solution, beam_solution, local_solution));
-        }
-        
-        // Write comparison CSV file
-        if let Some(first_instance) = self.instances.first() {
-            let instance_size = first_instance.n_reqs().to_string();
-            let csv_filename = format!("results/{}/comparison.csv", instance_size);
-            
-            let mut file = File::create(&csv_filename).expect("Failed to create comparison CSV file");
-            for line in csv_data {
-                writeln!(file, "{}", line).expect("Failed to write to comparison CSV file");
-            }
-            println!("Comparison results written to: {}", csv_filename);
         }
         
-        results
+        println!("Results written to: {}", writer.path());
+        Ok(solutions)
     }
 
-    /// Extended comparison including all metaheuristics
-    pub fn run_metaheuristic_comparison(&self) -> Vec<(String, Solution, Solution, Solution, Solution)> {
-        let mut results = Vec::new();
-        let mut csv_data = Vec::new();
-        
-        // Create results directory structure based on instance size
-        if let Some(first_instance) = self.instances.first() {
-            let instance_size = first_instance.n_reqs().to_string();
-            let results_dir = format!("results/{}", instance_size);
-            let solutions_dir = format!("solutions/{}/metaheuristic_comparison", instance_size);
-            fs::create_dir_all(&results_dir).expect("Failed to create results directory");
-            fs::create_dir_all(&solutions_dir).expect("Failed to create solutions directory");
-            
-            // Add CSV header for metaheuristic comparison
-            csv_data.push("instance_name,vnd_time,vnd_objective,vnd_fairness,vnd_vehicles,grasp_time,grasp_objective,grasp_fairness,grasp_vehicles,sa_time,sa_objective,sa_fairness,sa_vehicles,local_time,local_objective,local_fairness,local_vehicles".to_string());
-        }
-        
-        let neighborhoods = vec![
-            Neighborhood::Relocate,
-            Neighborhood::Exchange,
-            Neighborhood::TwoOpt,
-        ];
+    /// Runs every algorithm of `benchmark` on all instances and seeds. Writes a
+    /// long-format table to `results/<size>/<name>.csv` and the solutions to
+    /// `solutions/<size>/<name>/<instance>_<algorithm>.sol`.
+    /// In resume mode runs already in the file are skipped.
+    pub fn run_benchmark(&self, name: &str, benchmark: &Benchmark) -> Result<BenchmarkResults, ResultsError> {
+        let mut results = BenchmarkResults {
+            algorithms: benchmark.algorithm_names(),
+            entries: Vec::new(),
+        };
+
+        let Some(first_instance) = self.instances.first() else {
+            return Ok(results);
+        };
+        let instance_size = first_instance.n_reqs().to_string();
+        let solutions_dir = format!("solutions/{}/{}", instance_size, name);
+        fs::create_dir_all(&solutions_dir).map_err(|e| ResultsError::Io { path: solutions_dir.clone(), source: e })?;
+
+        let mut writer = self.open_results(&instance_size, name, &Self::results_header())?;
 
         for instance in &self.instances {
-            println!("Comparing metaheuristics for instance: {}", instance.name());
-            
-            // VND
-            let vnd_start = Instant::now();
-            let vnd_solver = VND::new(instance, neighborhoods.clone())
-                .with_max_iterations(100);
-            let vnd_solution = vnd_solver.solve();
-            let vnd_time = vnd_start.elapsed().as_secs_f64();
-            println!("  VND completed in {:.2}s, objective: {:.2}", vnd_time, vnd_solution.objective_function_value());
-            
-            // GRASP
-            let grasp_start = Instant::now();
-            let grasp_config = GRASPConfig::default();
-            let grasp_solver = GRASP::new(instance, grasp_config);
-            let grasp_solution = grasp_solver.solve();
-            let grasp_time = grasp_start.elapsed().as_secs_f64();
-            println!("  GRASP completed in {:.2}s, objective: {:.2}", grasp_time, grasp_solution.objective_function_value());
-            
-            // Simulated Annealing
-            let sa_start = Instant::now();
-            let sa_config = SimulatedAnnealingConfig::default();
-            let sa_solver = SimulatedAnnealing::new(instance, sa_config);
-            let sa_solution = sa_solver.solve();
-            let sa_time = sa_start.elapsed().as_secs_f64();
-            println!("  SA completed in {:.2}s, objective: {:.2}", sa_time, sa_solution.objective_function_value());
-            
-            // Local Search (baseline)
-            let local_start = Instant::now();
-            let local_solver = LocalSearch::new(instance, LocalSearchConfig::default());
-            let local_solution = local_solver.solve();
-            let local_time = local_start.elapsed().as_secs_f64();
-            println!("  Local Search completed in {:.2}s, objective: {:.2}", local_time, local_solution.objective_function_value());
-            
-            // Write solution files for each metaheuristic
-            if let Some(first_instance) = self.instances.first() {
-                let instance_size = first_instance.n_reqs().to_string();
-                let instance_stem = Path::new(instance.name())
-                    .file_stem()
-                    .and_then(|s| s.to_str())
-                    .unwrap_or(instance.name());
-                
-                let methods = [
-                    ("vnd", &vnd_solution),
-                    ("grasp", &grasp_solution),
-                    ("simulated_annealing", &sa_solution),
-                    ("local_search", &local_solution),
-                ];
-                
-                for (method_name, solution) in methods {
-                    let solution_filename = format!(
-                        "solutions/{}/metaheuristic_comparison/{}_{}.txt",
-                        instance_size, instance_stem, method_name
+            println!("Running {} for instance: {}", name, instance.name());
+
+            for &seed in &self.seeds {
+                for algorithm in 0..results.algorithms.len() {
+                    let job = JobKey::new(instance.name(), &results.algorithms[algorithm], Some(seed));
+                    if writer.is_done(&job) {
+                        println!("  {} (seed {}) skipped: already in {}", job.algorithm, seed, writer.path());
+                        continue;
+                    }
+
+                    let entry = benchmark.run_one(algorithm, instance, seed);
+                    println!(
+                        "  {} (seed {}) completed in {:.2}s, objective: {:.2}",
+                        entry.algorithm,
+                        seed,
+                        entry.time_seconds,
+                        entry.solution.objective_function_value()
                     );
-                    
-                    if let Err(e) = solution.to_file(&solution_filename) {
-                        eprintln!("Failed to write solution file {}: {}", solution_filename, e);
-                    } else {
-                        println!("  {} solution written to: {}", method_name, solution_filename);
+
+                    let gap_to_bks = self.record_best_known(&entry.solution, &entry.algorithm);
+
+                    let suffix = format!("_{}", entry.algorithm);
+                    let solution_filename = self.solution_filename(&solutions_dir, instance, &suffix, seed);
+                    match entry.solution.to_file(&solution_filename) {
+                        Ok(()) => writer.append(job, &self.results_row(&entry, gap_to_bks))?,
+                        Err(e) => eprintln!("Failed to write solution file {}, leaving out its row: {}", solution_filename, e),
                     }
+                    results.entries.push(entry);
                 }
             }
-            
-            // Add CSV row for comparison
-            let csv_row = format!(
-                "{},{:.6},{:.6},{:.6},{},{:.6},{:.6},{:.6},{},{:.6},{:.6},{:.6},{},{:.6},{:.6},{:.6},{}",
-                instance.name(),
-                vnd_time,
-                vnd_solution.objective_function_value(),
-                vnd_solution.jain_fairness(),
-                vnd_solution.routes.len(),
-                grasp_time,
-                grasp_solution.objective_function_value(),
-                grasp_solution.jain_fairness(),
-                grasp_solution.routes.len(),
-                sa_time,
-                sa_solution.objective_function_value(),
-                sa_solution.jain_fairness(),
-                sa_solution.routes.len(),
-                local_time,
-                local_solution.objective_function_value(),
-                local_solution.jain_fairness(),
-                local_solution.routes.len()
-            );
-            csv_data.push(csv_row);
-            
-            results.push((
-                instance.name().to_string(),
-                vnd_solution,
-                grasp_solution,
-                sa_solution,
-                local_solution
-            ));
-            
             println!();
         }
-        
-        // Write comparison CSV file
-        if let Some(first_instance) = self.instances.first() {
-            let instance_size = first_instance.n_reqs().to_string();
-            let csv_filename = format!("results/{}/metaheuristic_comparison.csv", instance_size);
-            
-            let mut file = File::create(&csv_filename).expect("Failed to create metaheuristic comparison CSV file");
-            for line in csv_data {
-                writeln!(file, "{}", line).expect("Failed to write to comparison CSV file");
+        println!("Benchmark results written to: {}", writer.path());
+
+        if results.algorithms.len() >= 2 {
+            match results.comparison() {
+                Ok(report) => println!("\n{}", report),
+                Err(e) => eprintln!("No statistical comparison: {}", e),
             }
-            println!("Metaheuristic comparison results written to: {}", csv_filename);
         }
-        
-        results
+
+        Ok(results)
+    }
+
+    /// Constructive heuristics and local search
+    pub fn run_comparison(&self) -> Result<BenchmarkResults, ResultsError> {
+        let benchmark = Benchmark::new()
+            .with_algorithm("deterministic", |instance, _seed| {
+                Box::new(DeterministicConstruction::new(instance))
+            })
+            .with_algorithm("random", |instance, seed| {
+                Box::new(RandomConstruction::new(instance, false).with_seed(seed))
+            })
+            .with_algorithm("cheapest_insertion", |instance, _seed| {
+                Box::new(InsertionConstruction::cheapest(instance))
+            })
+            .with_algorithm("regret3_insertion", |instance, _seed| {
+                Box::new(InsertionConstruction::regret(instance, 3))
+            })
+            .with_algorithm("savings", |instance, _seed| {
+                Box::new(SavingsConstruction::new(instance))
+            })
+            .with_algorithm("cluster_first_sweep", |instance, _seed| {
+                Box::new(ClusterFirstConstruction::new(instance, ClusteringMethod::Sweep))
+            })
+            .with_algorithm("beam_search", |instance, _seed| {
+                Box::new(BeamSearch::new(instance.clone()).with_beam_width(20).with_max_depth(150))
+            })
+            .with_algorithm("local_search", |instance, _seed| {
+                Box::new(LocalSearch::new(instance, LocalSearchConfig::default()))
+            });
+
+        self.run_benchmark("comparison", &benchmark)
+    }
+
+    /// Extended comparison including all metaheuristics
+    pub fn run_metaheuristic_comparison(&self) -> Result<BenchmarkResults, ResultsError> {
+        let benchmark = Benchmark::new()
+            .with_algorithm("vnd", |instance, seed| {
+                Box::new(VND::new(instance, Neighborhood::vnd_default().to_vec())
+                    .with_max_iterations(100)
+                    .with_seed(seed))
+            })
+            .with_algorithm("grasp", |instance, seed| {
+                Box::new(GRASP::new(instance, GRASPConfig::default()).with_seed(seed))
+            })
+            .with_algorithm("simulated_annealing", |instance, seed| {
+                Box::new(SimulatedAnnealing::new(instance, SimulatedAnnealingConfig::default()).with_seed(seed))
+            })
+            .with_algorithm("local_search", |instance, _seed| {
+                Box::new(LocalSearch::new(instance, LocalSearchConfig::default()))
+            });
+
+        self.run_benchmark("metaheuristic_comparison", &benchmark)
     }
-}
\ No newline at end of file
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::instance::tests::from_text;
+
+    #[test]
+    fn invalid_solutions_have_no_gap_to_the_best_known() {
+        let instance = from_text("runtime_gap", "\
+2 1 10 2 0
+# demands
+1 1
+# request locations
+0 0
+0 1
+0 3
+0 2
+0 4
+");
+        let dir = std::env::temp_dir().join(format!("scf_pdp_runtime_gap_{}", std::process::id()));
+        let registry = BestKnownRegistry::load(&dir.join("best_known.csv").to_string_lossy()).unwrap();
+        let runtime = SolverRuntime::new(vec![instance.clone()]).with_registry(Some(registry));
+
+        let valid = Solution::new(instance.clone(), vec![vec![1, 3, 2, 4]]);
+        assert_eq!(runtime.record_best_known(&valid, "a"), Some(0.0));
+        // Serves one of two requests with a shorter route
+        let invalid = Solution::new(instance, vec![vec![1, 3]]);
+        assert_eq!(runtime.record_best_known(&invalid, "b"), None);
+        let _ = fs::remove_dir_all(&dir);
+    }
+}
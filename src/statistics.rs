@@ -0,0 +1,388 @@
+/// Descriptive statistics of a sample
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct Summary {
+    pub count: usize,
+    pub mean: f64,
+    pub std: f64,
+    pub min: f64,
+    pub q1: f64,
+    pub median: f64,
+    pub q3: f64,
+    pub max: f64,
+}
+
+impl Summary {
+    /// Summarize a sample, None if it is empty
+    pub fn of(values: &[f64]) -> Option<Self> {
+        if values.is_empty() {
+            return None;
+        }
+
+        let mut sorted = values.to_vec();
+        sorted.sort_by(|a, b| a.partial_cmp(b).unwrap_or(std::cmp::Ordering::Equal));
+
+        let count = sorted.len();
+        let mean = sorted.iter().sum::<f64>() / count as f64;
+        // Sample standard deviation, like pandas in the analysis notebook
+        let std = if count > 1 {
+            (sorted.iter().map(|x| (x - mean).powi(2)).sum::<f64>() / (count - 1) as f64).sqrt()
+        } else {
+            0.0
+        };
+
+        Some(Self {
+            count,
+            mean,
+            std,
+            min: sorted[0],
+            q1: quantile(&sorted, 0.25),
+            median: quantile(&sorted, 0.5),
+            q3: quantile(&sorted, 0.75),
+            max: sorted[count - 1],
+        })
+    }
+}
+
+/// Linearly interpolated quantile of an already sorted, non-empty sample
+pub fn quantile(sorted: &[f64], q: f64) -> f64 {
+    let pos = q.clamp(0.0, 1.0) * (sorted.len() - 1) as f64;
+    let lower = pos.floor() as usize;
+    let upper = pos.ceil() as usize;
+    let frac = pos - lower as f64;
+    sorted[lower] + (sorted[upper] - sorted[lower]) * frac
+}
+
+/// Average ranks (1-based) of the values, ties share the mean of their ranks
+pub fn average_ranks(values: &[f64]) -> Vec<f64> {
+    let mut order: Vec<usize> = (0..values.len()).collect();
+    order.sort_by(|&a, &b| values[a].partial_cmp(&values[b]).unwrap_or(std::cmp::Ordering::Equal));
+
+    let mut ranks = vec![0.0; values.len()];
+    let mut i = 0;
+    while i < order.len() {
+        let mut j = i;
+        while j + 1 < order.len() && values[order[j + 1]] == values[order[i]] {
+            j += 1;
+        }
+        let rank = (i + j) as f64 / 2.0 + 1.0;
+        for &idx in &order[i..=j] {
+            ranks[idx] = rank;
+        }
+        i = j + 1;
+    }
+    ranks
+}
+
+/// Result of a two-sided Wilcoxon signed-rank test
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct WilcoxonResult {
+    /// Number of pairs with a non-zero difference
+    pub n: usize,
+    /// Rank sum of pairs where the first sample is larger
+    pub w_plus: f64,
+    /// Rank sum of pairs where the second sample is larger
+    pub w_minus: f64,
+    pub z: f64,
+    pub p_value: f64,
+}
+
+/// Two-sided Wilcoxon signed-rank test on paired samples, using the normal
+/// approximation with tie and continuity correction. Zero differences are
+/// dropped; None if no pair differs.
+pub fn wilcoxon_signed_rank(a: &[f64], b: &[f64]) -> Option<WilcoxonResult> {
+    let diffs: Vec<f64> = a.iter()
+        .zip(b)
+        .map(|(x, y)| x - y)
+        .filter(|d| d.abs() > 1e-9)
+        .collect();
+    let n = diffs.len();
+    if n == 0 {
+        return None;
+    }
+
+    let abs_diffs: Vec<f64> = diffs.iter().map(|d| d.abs()).collect();
+    let ranks = average_ranks(&abs_diffs);
+
+    let w_plus: f64 = diffs.iter().zip(&ranks).filter(|(d, _)| **d > 0.0).map(|(_, r)| r).sum();
+    let w_minus: f64 = diffs.iter().zip(&ranks).filter(|(d, _)| **d < 0.0).map(|(_, r)| r).sum();
+
+    let nf = n as f64;
+    let mean = nf * (nf + 1.0) / 4.0;
+
+    // Tie correction: Σ(t³ - t) / 48 over groups of equal |d|
+    let mut sorted = abs_diffs.clone();
+    sorted.sort_by(|x, y| x.partial_cmp(y).unwrap_or(std::cmp::Ordering::Equal));
+    let mut tie_correction = 0.0;
+    let mut i = 0;
+    while i < sorted.len() {
+        let mut j = i;
+        while j + 1 < sorted.len() && sorted[j + 1] == sorted[i] {
+            j += 1;
+        }
+        let t = (j - i + 1) as f64;
+        tie_correction += (t * t * t - t) / 48.0;
+        i = j + 1;
+    }
+    let variance = nf * (nf + 1.0) * (2.0 * nf + 1.0) / 24.0 - tie_correction;
+
+    let w = w_plus.min(w_minus);
+    let z = if variance > 0.0 {
+        ((w - mean).abs() - 0.5).max(0.0) / variance.sqrt()
+    } else {
+        0.0
+    };
+    let p_value = (2.0 * (1.0 - normal_cdf(z))).min(1.0);
+
+    Some(WilcoxonResult { n, w_plus, w_minus, z, p_value })
+}
+
+/// Result of a Friedman test over k algorithms on N instances
+#[derive(Debug, Clone, PartialEq)]
+pub struct FriedmanResult {
+    pub n_instances: usize,
+    pub chi_square: f64,
+    pub degrees_of_freedom: usize,
+    pub p_value: f64,
+    /// Mean rank of each algorithm, 1 is best (lowest value)
+    pub mean_ranks: Vec<f64>,
+}
+
+/// Friedman test; `data[i][j]` is the value of algorithm j on instance i,
+/// lower is better. None with fewer than two algorithms or no instances.
+pub fn friedman_test(data: &[Vec<f64>]) -> Option<FriedmanResult> {
+    let n = data.len();
+    let k = data.first()?.len();
+    if n == 0 || k < 2 || data.iter().any(|row| row.len() != k) {
+        return None;
+    }
+
+    let mut rank_sums = vec![0.0; k];
+    for row in data {
+        for (j, r) in average_ranks(row).into_iter().enumerate() {
+            rank_sums[j] += r;
+        }
+    }
+    let mean_ranks: Vec<f64> = rank_sums.iter().map(|s| s / n as f64).collect();
+
+    let nf = n as f64;
+    let kf = k as f64;
+    let chi_square = 12.0 * nf / (kf * (kf + 1.0))
+        * mean_ranks.iter().map(|r| r * r).sum::<f64>()
+        - 3.0 * nf * (kf + 1.0);
+    let degrees_of_freedom = k - 1;
+    let p_value = chi_square_sf(chi_square.max(0.0), degrees_of_freedom as f64);
+
+    Some(FriedmanResult { n_instances: n, chi_square, degrees_of_freedom, p_value, mean_ranks })
+}
+
+/// Nemenyi critical difference of mean ranks at alpha = 0.05 for k algorithms
+/// on n instances (Demšar, 2006). None for k outside 2..=10.
+pub fn nemenyi_critical_difference(k: usize, n: usize) -> Option<f64> {
+    const Q_005: [f64; 9] = [1.960, 2.343, 2.569, 2.728, 2.850, 2.949, 3.031, 3.102, 3.164];
+    if !(2..=10).contains(&k) || n == 0 {
+        return None;
+    }
+    let kf = k as f64;
+    Some(Q_005[k - 2] * (kf * (kf + 1.0) / (6.0 * n as f64)).sqrt())
+}
+
+/// Standard normal cumulative distribution function
+pub fn normal_cdf(x: f64) -> f64 {
+    0.5 * (1.0 + erf(x / std::f64::consts::SQRT_2))
+}
+
+/// Error function, Abramowitz and Stegun 7.1.26 (absolute error < 1.5e-7)
+fn erf(x: f64) -> f64 {
+    let sign = if x < 0.0 { -1.0 } else { 1.0 };
+    let x = x.abs();
+    let t = 1.0 / (1.0 + 0.3275911 * x);
+    let poly = t * (0.254829592
+        + t * (-0.284496736 + t * (1.421413741 + t * (-1.453152027 + t * 1.061405429))));
+    sign * (1.0 - poly * (-x * x).exp())
+}
+
+/// Survival function P(X > x) of the chi-square distribution
+pub fn chi_square_sf(x: f64, degrees_of_freedom: f64) -> f64 {
+    if x <= 0.0 {
+        return 1.0;
+    }
+    1.0 - regularized_lower_gamma(degrees_of_freedom / 2.0, x / 2.0)
+}
+
+/// Regularized lower incomplete gamma function P(a, x)
+fn regularized_lower_gamma(a: f64, x: f64) -> f64 {
+    let eps = 1e-14;
+    if x < a + 1.0 {
+        // Series representation
+        let mut sum = 1.0 / a;
+        let mut term = sum;
+        let mut ap = a;
+        for _ in 0..1000 {
+            ap += 1.0;
+            term *= x / ap;
+            sum += term;
+            if term.abs() < sum.abs() * eps {
+                break;
+            }
+        }
+        sum * (-x + a * x.ln() - ln_gamma(a)).exp()
+    } else {
+        // Continued fraction for Q(a, x) (modified Lentz)
+        let tiny = 1e-300;
+        let mut b = x + 1.0 - a;
+        let mut c = 1.0 / tiny;
+        let mut d = 1.0 / b;
+        let mut h = d;
+        for i in 1..1000 {
+            let an = -(i as f64) * (i as f64 - a);
+            b += 2.0;
+            d = an * d + b;
+            if d.abs() < tiny {
+                d = tiny;
+            }
+            c = b + an / c;
+            if c.abs() < tiny {
+                c = tiny;
+            }
+            d = 1.0 / d;
+            let delta = d * c;
+            h *= delta;
+            if (delta - 1.0).abs() < eps {
+                break;
+            }
+        }
+        1.0 - (-x + a * x.ln() - ln_gamma(a)).exp() * h
+    }
+}
+
+/// Natural logarithm of the gamma function (Lanczos approximation)
+fn ln_gamma(x: f64) -> f64 {
+    const COEFFICIENTS: [f64; 6] = [
+        76.18009172947146,
+        -86.50532032941677,
+        24.01409824083091,
+        -1.231739572450155,
+        0.1208650973866179e-2,
+        -0.5395239384953e-5,
+    ];
+    let mut y = x;
+    let tmp = x + 5.5;
+    let tmp = tmp - (x + 0.5) * tmp.ln();
+    let mut series = 1.000000000190015;
+    for c in COEFFICIENTS {
+        y += 1.0;
+        series += c / y;
+    }
+    -tmp + (2.5066282746310005 * series / x).ln()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn assert_close(actual: f64, expected: f64, tolerance: f64) {
+        assert!((actual - expected).abs() <= tolerance, "{} is not within {} of {}", actual, tolerance, expected);
+    }
+
+    #[test]
+    fn average_ranks_share_ties() {
+        assert_eq!(average_ranks(&[3.0, 1.0, 3.0, 2.0]), vec![3.5, 1.0, 3.5, 2.0]);
+    }
+
+    #[test]
+    fn wilcoxon_with_tied_differences() {
+        // Wikipedia example: one zero difference is dropped, |d| = 5 appears twice
+        let a = [125.0, 115.0, 130.0, 140.0, 140.0, 115.0, 140.0, 125.0, 140.0, 135.0];
+        let b = [110.0, 122.0, 125.0, 120.0, 140.0, 124.0, 123.0, 137.0, 135.0, 145.0];
+        let result = wilcoxon_signed_rank(&a, &b).unwrap();
+        assert_eq!(result.n, 9);
+        assert_eq!(result.w_plus, 27.0);
+        assert_eq!(result.w_minus, 18.0);
+        // Variance 9·10·19/24 - (2³ - 2)/48 = 71.125, continuity corrected |18 - 22.5| - 0.5
+        assert_close(result.z, 4.0 / 71.125f64.sqrt(), 1e-12);
+        assert_close(result.p_value, 0.635289, 1e-5);
+    }
+
+    #[test]
+    fn wilcoxon_without_differences() {
+        assert!(wilcoxon_signed_rank(&[1.0, 2.0], &[1.0, 2.0]).is_none());
+    }
+
+    #[test]
+    fn friedman_consistent_ranking() {
+        // Same order on every instance: chi² = 12·4/(3·4)·(1 + 4 + 9) - 3·4·4 = 8
+        let data = vec![vec![1.0, 2.0, 3.0]; 4];
+        let result = friedman_test(&data).unwrap();
+        assert_eq!(result.mean_ranks, vec![1.0, 2.0, 3.0]);
+        assert_close(result.chi_square, 8.0, 1e-12);
+        assert_eq!(result.degrees_of_freedom, 2);
+        // With two degrees of freedom the survival function is e^(-x/2)
+        assert_close(result.p_value, (-4.0f64).exp(), 1e-10);
+    }
+
+    #[test]
+    fn friedman_with_swapped_ranks() {
+        let data = vec![
+            vec![10.0, 20.0, 30.0],
+            vec![10.0, 20.0, 30.0],
+            vec![10.0, 20.0, 30.0],
+            vec![20.0, 10.0, 30.0],
+        ];
+        let result = friedman_test(&data).unwrap();
+        assert_eq!(result.mean_ranks, vec![1.25, 1.75, 3.0]);
+        assert_close(result.chi_square, 6.5, 1e-12);
+        assert_close(result.p_value, (-3.25f64).exp(), 1e-10);
+    }
+
+    #[test]
+    fn friedman_needs_two_algorithms() {
+        assert!(friedman_test(&[vec![1.0], vec![2.0]]).is_none());
+        assert!(friedman_test(&[]).is_none());
+    }
+
+    #[test]
+    fn nemenyi_critical_difference_from_table() {
+        // Demšar (2006): q_0.05 = 2.343 for three algorithms
+        assert_close(nemenyi_critical_difference(3, 4).unwrap(), 2.343 * 0.5f64.sqrt(), 1e-12);
+        assert!(nemenyi_critical_difference(11, 4).is_none());
+        assert!(nemenyi_critical_difference(3, 0).is_none());
+    }
+
+    #[test]
+    fn incomplete_gamma_closed_forms() {
+        // P(1, x) = 1 - e^-x, by the series below a + 1 and the continued fraction above
+        for x in [0.5f64, 3.0, 10.0] {
+            assert_close(regularized_lower_gamma(1.0, x), 1.0 - (-x).exp(), 1e-12);
+        }
+        // P(1/2, x) = erf(√x): erf(1/√2) and erf(√2)
+        assert_close(regularized_lower_gamma(0.5, 0.5), 0.682689492, 1e-8);
+        assert_close(regularized_lower_gamma(0.5, 2.0), 0.954499736, 1e-8);
+        assert_close(ln_gamma(5.0), 24.0f64.ln(), 1e-10);
+        assert_close(ln_gamma(0.5), std::f64::consts::PI.sqrt().ln(), 1e-10);
+    }
+
+    #[test]
+    fn chi_square_critical_values() {
+        // Upper 5% points of the chi-square table
+        assert_close(chi_square_sf(3.841459, 1.0), 0.05, 1e-6);
+        assert_close(chi_square_sf(5.991465, 2.0), 0.05, 1e-6);
+        assert_close(chi_square_sf(11.070498, 5.0), 0.05, 1e-6);
+        assert_eq!(chi_square_sf(0.0, 3.0), 1.0);
+    }
+
+    #[test]
+    fn normal_cdf_values() {
+        assert_close(normal_cdf(0.0), 0.5, 1e-7);
+        assert_close(normal_cdf(1.959964), 0.975, 1e-6);
+    }
+
+    #[test]
+    fn summary_quartiles() {
+        let summary = Summary::of(&[4.0, 1.0, 3.0, 2.0]).unwrap();
+        assert_eq!(summary.median, 2.5);
+        assert_eq!(summary.q1, 1.75);
+        assert_eq!(summary.q3, 3.25);
+        assert_close(summary.std, (5.0f64 / 3.0).sqrt(), 1e-12);
+        assert!(Summary::of(&[]).is_none());
+    }
+}
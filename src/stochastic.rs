@@ -0,0 +1,322 @@
+use std::fmt;
+
+use rand::prelude::*;
+
+use crate::{Instance, Solution};
+
+/// Distribution of the load a request actually brings, around its booked demand
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum DemandDistribution {
+    Normal { mean: f64, variance: f64 },
+    /// Uniform on the interval with the given mean and variance
+    Uniform { mean: f64, variance: f64 },
+    Poisson { mean: f64 },
+}
+
+impl DemandDistribution {
+    pub fn mean(&self) -> f64 {
+        match *self {
+            DemandDistribution::Normal { mean, .. }
+            | DemandDistribution::Uniform { mean, .. }
+            | DemandDistribution::Poisson { mean } => mean,
+        }
+    }
+
+    pub fn variance(&self) -> f64 {
+        match *self {
+            DemandDistribution::Normal { variance, .. } | DemandDistribution::Uniform { variance, .. } => variance,
+            DemandDistribution::Poisson { mean } => mean,
+        }
+    }
+
+    /// Draw a load, rounded to whole units and never negative
+    pub fn sample(&self, rng: &mut StdRng) -> usize {
+        let value = match *self {
+            DemandDistribution::Normal { mean, variance } => mean + variance.sqrt() * standard_normal(rng),
+            DemandDistribution::Uniform { mean, variance } => {
+                let half_width = (3.0 * variance).sqrt();
+                mean + half_width * (2.0 * rng.gen::<f64>() - 1.0)
+            }
+            DemandDistribution::Poisson { mean } if mean < 30.0 => {
+                // Knuth: count uniform draws until their product drops below e^-mean
+                let limit = (-mean).exp();
+                let mut product = rng.gen::<f64>();
+                let mut count = 0.0;
+                while product > limit {
+                    product *= rng.gen::<f64>();
+                    count += 1.0;
+                }
+                count
+            }
+            DemandDistribution::Poisson { mean } => mean + mean.sqrt() * standard_normal(rng),
+        };
+        value.round().max(0.0) as usize
+    }
+
+    /// Parse `normal mean variance`, `uniform mean variance` or `poisson mean`
+    fn parse(line: &str, req_id: usize) -> Result<Self, Box<dyn std::error::Error>> {
+        let parts: Vec<&str> = line.split_whitespace().collect();
+        let values: Vec<f64> = parts.iter()
+            .skip(1)
+            .map(|s| s.parse())
+            .collect::<Result<_, _>>()?;
+        let distribution = match (parts.first().copied(), values.as_slice()) {
+            (Some("normal"), &[mean, variance]) => DemandDistribution::Normal { mean, variance },
+            (Some("uniform"), &[mean, variance]) => DemandDistribution::Uniform { mean, variance },
+            (Some("poisson"), &[mean]) => DemandDistribution::Poisson { mean },
+            _ => return Err(format!("Invalid demand distribution of request {}: {}", req_id, line).into()),
+        };
+        if distribution.mean() < 0.0 || distribution.variance() < 0.0 {
+            return Err(format!("Negative mean or variance for request {}", req_id).into());
+        }
+        Ok(distribution)
+    }
+}
+
+/// Standard normal draw by the Box-Muller transform
+fn standard_normal(rng: &mut StdRng) -> f64 {
+    let u1: f64 = 1.0 - rng.gen::<f64>();
+    let u2: f64 = rng.gen();
+    (-2.0 * u1.ln()).sqrt() * (2.0 * std::f64::consts::PI * u2).cos()
+}
+
+/// Demand distribution of every request. Solvers plan with the booked
+/// demands; the distributions are only used to evaluate solutions.
+#[derive(Debug, Clone, PartialEq)]
+pub struct StochasticDemands {
+    pub distributions: Vec<DemandDistribution>,
+}
+
+impl StochasticDemands {
+    /// Parse the lines following `# stochastic demands`, one distribution per request
+    pub(crate) fn parse<I>(lines: &mut I, n_reqs: usize) -> Result<Self, Box<dyn std::error::Error>>
+    where
+        I: Iterator<Item = std::io::Result<String>>,
+    {
+        let distributions = (0..n_reqs)
+            .map(|i| {
+                let line = lines.next().ok_or(format!("Missing demand distribution {}", i))??;
+                DemandDistribution::parse(&line, i)
+            })
+            .collect::<Result<_, _>>()?;
+        Ok(Self { distributions })
+    }
+
+    /// Loads spread uniformly within `spread` of the booked demands, e.g. 0.2 for ±20%
+    pub fn relative(demands: &[usize], spread: f64) -> Self {
+        let distributions = demands.iter()
+            .map(|&demand| {
+                let width = 2.0 * spread * demand as f64;
+                DemandDistribution::Uniform { mean: demand as f64, variance: width * width / 12.0 }
+            })
+            .collect();
+        Self { distributions }
+    }
+
+    /// One realisation of all demands
+    pub fn sample(&self, rng: &mut StdRng) -> Vec<usize> {
+        self.distributions.iter().map(|d| d.sample(rng)).collect()
+    }
+}
+
+impl fmt::Display for StochasticDemands {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        let mean: f64 = self.distributions.iter().map(|d| d.mean()).sum();
+        let variance: f64 = self.distributions.iter().map(|d| d.variance()).sum();
+        write!(f, "total mean {:.2}, total variance {:.2}", mean, variance)
+    }
+}
+
+/// Monte Carlo estimates for a solution under stochastic demands
+#[derive(Debug, Clone, PartialEq)]
+pub struct StochasticEvaluation {
+    pub samples: usize,
+    /// Share of scenarios in which at least one vehicle is overloaded
+    pub violation_probability: f64,
+    /// Share of scenarios in which each vehicle is overloaded
+    pub route_violation_probabilities: Vec<f64>,
+    /// Mean distance of the recourse trips delivering the loads that did not fit
+    pub expected_recourse_distance: f64,
+}
+
+impl fmt::Display for StochasticEvaluation {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        writeln!(f, "Stochastic evaluation ({} samples):", self.samples)?;
+        writeln!(f, "  Capacity violation probability: {:.4}", self.violation_probability)?;
+        writeln!(f, "  Expected recourse distance: {:.2}", self.expected_recourse_distance)?;
+        for (i, probability) in self.route_violation_probabilities.iter().enumerate() {
+            writeln!(f, "  Vehicle {}: violation probability {:.4}", i + 1, probability)?;
+        }
+        Ok(())
+    }
+}
+
+/*
+Monte Carlo evaluation over a fixed set of sampled demand scenarios, so all
+solutions are compared on the same realisations. When the realised load of a
+pickup does not fit, the vehicle leaves with what fits and the excess waits at
+the pickup; recourse trips from the vehicle's home depot collect it, deliver it
+at the dropoff and return to the depot, as many as the excess needs full
+vehicles, ceil(excess / capacity). Without stochastic demands the only
+scenario is the booked demands.
+*/
+pub struct MonteCarloEvaluator<'a> {
+    instance: &'a Instance,
+    scenarios: Vec<Vec<usize>>,
+}
+
+impl<'a> MonteCarloEvaluator<'a> {
+    pub fn new(instance: &'a Instance, samples: usize, seed: u64) -> Self {
+        let scenarios = match instance.stochastic_demands() {
+            Some(demands) => {
+                let mut rng = StdRng::seed_from_u64(seed);
+                (0..samples.max(1)).map(|_| demands.sample(&mut rng)).collect()
+            }
+            None => vec![instance.demands().clone()],
+        };
+        Self { instance, scenarios }
+    }
+
+    pub fn samples(&self) -> usize {
+        self.scenarios.len()
+    }
+
+    /// Whether the vehicle is overloaded in one scenario, and the distance of its recourse trips
+    fn recourse(&self, vehicle_id: usize, route: &[usize], demands: &[usize]) -> (bool, f64) {
+        let n_reqs = self.instance.n_reqs();
+        let capacity = self.instance.vehicle(vehicle_id).capacity;
+        let depot = self.instance.home_node(vehicle_id);
+        let mut load = 0usize;
+        // Part of each request's load on board, the rest is left for a recourse trip
+        let mut carried = vec![0usize; n_reqs];
+        let mut violated = false;
+        let mut distance = 0.0;
+        for &node in route {
+            if node >= 1 && node <= n_reqs {
+                let req_id = node - 1;
+                carried[req_id] = demands[req_id].min(capacity.saturating_sub(load));
+                load += carried[req_id];
+                if carried[req_id] < demands[req_id] {
+                    violated = true;
+                    let dropoff = node + n_reqs;
+                    let trips = (demands[req_id] - carried[req_id]).div_ceil(capacity.max(1));
+                    let trip = self.instance.distance(depot, node)
+                        + self.instance.distance(node, dropoff)
+                        + self.instance.distance(dropoff, depot);
+                    distance += (trips * trip) as f64;
+                }
+            } else if node > n_reqs && node <= 2 * n_reqs {
+                load -= carried[node - n_reqs - 1];
+            }
+        }
+        (violated, distance)
+    }
+
+    /// Share of scenarios in which the route overloads the vehicle
+    pub fn route_violation_probability(&self, vehicle_id: usize, route: &[usize]) -> f64 {
+        let violations = self.scenarios.iter()
+            .filter(|demands| self.recourse(vehicle_id, route, demands).0)
+            .count();
+        violations as f64 / self.scenarios.len() as f64
+    }
+
+    /// Whether no vehicle is overloaded with a probability above `max_probability`
+    pub fn is_robust(&self, solution: &Solution, max_probability: f64) -> bool {
+        solution.routes.iter()
+            .enumerate()
+            .all(|(k, route)| self.route_violation_probability(k, route) <= max_probability)
+    }
+
+    pub fn evaluate(&self, solution: &Solution) -> StochasticEvaluation {
+        let mut violations = 0;
+        let mut route_violations = vec![0; solution.routes.len()];
+        let mut recourse_distance = 0.0;
+        for demands in &self.scenarios {
+            let mut any_violated = false;
+            for (k, route) in solution.routes.iter().enumerate() {
+                let (violated, distance) = self.recourse(k, route, demands);
+                if violated {
+                    route_violations[k] += 1;
+                    any_violated = true;
+                }
+                recourse_distance += distance;
+            }
+            if any_violated {
+                violations += 1;
+            }
+        }
+
+        let samples = self.scenarios.len();
+        StochasticEvaluation {
+            samples,
+            violation_probability: violations as f64 / samples as f64,
+            route_violation_probabilities: route_violations.iter()
+                .map(|&count| count as f64 / samples as f64)
+                .collect(),
+            expected_recourse_distance: recourse_distance / samples as f64,
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::instance::tests::from_text;
+
+    // One request of booked demand 1 from x = 1 to x = 3, capacity 2
+    const SINGLE: &str = "\
+1 1 2 1 0
+# demands
+1
+# request locations
+0 0
+1 0
+3 0
+";
+
+    #[test]
+    fn booked_demands_are_the_only_scenario_without_distributions() {
+        let instance = from_text("stochastic_booked", SINGLE);
+        let evaluator = MonteCarloEvaluator::new(&instance, 100, 0);
+        assert_eq!(evaluator.samples(), 1);
+        let evaluation = evaluator.evaluate(&Solution::new(instance.clone(), vec![vec![1, 2]]));
+        assert_eq!(evaluation.violation_probability, 0.0);
+        assert_eq!(evaluation.expected_recourse_distance, 0.0);
+    }
+
+    #[test]
+    fn excess_load_takes_as_many_recourse_trips_as_it_fills_vehicles() {
+        // 5 realised: 2 on board, 3 left for two trips of 1 + 2 + 3
+        let instance = from_text("stochastic_recourse", &format!("{}# stochastic demands\nuniform 5 0\n", SINGLE));
+        let evaluator = MonteCarloEvaluator::new(&instance, 10, 0);
+        let solution = Solution::new(instance.clone(), vec![vec![1, 2]]);
+        let evaluation = evaluator.evaluate(&solution);
+        assert_eq!(evaluation.violation_probability, 1.0);
+        assert_eq!(evaluation.route_violation_probabilities, vec![1.0]);
+        assert_eq!(evaluation.expected_recourse_distance, 12.0);
+        assert!(!evaluator.is_robust(&solution, 0.5));
+    }
+
+    #[test]
+    fn same_seed_gives_the_same_evaluation() {
+        let instance = from_text("stochastic_seed", &format!("{}# stochastic demands\npoisson 2\n", SINGLE));
+        let solution = Solution::new(instance.clone(), vec![vec![1, 2]]);
+        let first = MonteCarloEvaluator::new(&instance, 200, 7).evaluate(&solution);
+        let second = MonteCarloEvaluator::new(&instance, 200, 7).evaluate(&solution);
+        assert_eq!(first, second);
+        // Poisson(2) exceeds the capacity of 2 about a third of the time
+        assert!(first.violation_probability > 0.1 && first.violation_probability < 0.6);
+    }
+
+    #[test]
+    fn relative_spread_keeps_the_booked_mean() {
+        let demands = StochasticDemands::relative(&[10, 0], 0.2);
+        assert_eq!(demands.distributions[0].mean(), 10.0);
+        let mut rng = StdRng::seed_from_u64(0);
+        for _ in 0..100 {
+            let sample = demands.sample(&mut rng);
+            assert!((8..=12).contains(&sample[0]));
+            assert_eq!(sample[1], 0);
+        }
+    }
+}
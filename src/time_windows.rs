@@ -0,0 +1,86 @@
+use std::fmt;
+
+/// When a location can be served and how long the service takes
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct TimeWindow {
+    pub earliest: f64,
+    pub latest: f64,
+    pub service: f64,
+}
+
+impl TimeWindow {
+    /// Always open, no service time
+    pub const OPEN: TimeWindow = TimeWindow { earliest: 0.0, latest: f64::INFINITY, service: 0.0 };
+
+    /// Parse `earliest latest [service]`
+    fn parse(line: &str, what: &str) -> Result<Self, Box<dyn std::error::Error>> {
+        let parts: Vec<f64> = line
+            .split_whitespace()
+            .map(|s| s.parse())
+            .collect::<Result<_, _>>()?;
+        let window = match parts.as_slice() {
+            [earliest, latest] => TimeWindow { earliest: *earliest, latest: *latest, service: 0.0 },
+            [earliest, latest, service] => TimeWindow { earliest: *earliest, latest: *latest, service: *service },
+            _ => return Err(format!("Time window of {} should have 2 or 3 values", what).into()),
+        };
+        if window.earliest > window.latest || window.service < 0.0 {
+            return Err(format!("Invalid time window of {}: {}", what, line).into());
+        }
+        Ok(window)
+    }
+}
+
+/// Time windows of all locations. Travel times equal the distances between
+/// locations; a vehicle arriving early waits until the window opens.
+#[derive(Debug, Clone, PartialEq)]
+pub struct TimeWindows {
+    /// Vehicles leave the depot at `earliest` and must be back by `latest`
+    pub depot: TimeWindow,
+    pub pickups: Vec<TimeWindow>,
+    pub dropoffs: Vec<TimeWindow>,
+}
+
+impl TimeWindows {
+    /// Parse the lines following `# time windows`: the depot horizon, then one
+    /// line per pickup and one per dropoff
+    pub(crate) fn parse<I>(lines: &mut I, n_reqs: usize) -> Result<Self, Box<dyn std::error::Error>>
+    where
+        I: Iterator<Item = std::io::Result<String>>,
+    {
+        let depot_line = lines.next().ok_or("Missing depot time window")??;
+        let depot = TimeWindow::parse(&depot_line, "the depot")?;
+
+        let mut read = |what: &str| -> Result<Vec<TimeWindow>, Box<dyn std::error::Error>> {
+            let mut windows = Vec::with_capacity(n_reqs);
+            for i in 0..n_reqs {
+                let line = lines.next().ok_or(format!("Missing {} time window {}", what, i))??;
+                windows.push(TimeWindow::parse(&line, &format!("{} {}", what, i))?);
+            }
+            Ok(windows)
+        };
+        let pickups = read("pickup")?;
+        let dropoffs = read("drop-off")?;
+
+        Ok(Self { depot, pickups, dropoffs })
+    }
+
+    /// Window of a node index: 0 is the depot, then pickups, dropoffs and
+    /// additional depots, which share the depot's window
+    pub fn window(&self, node: usize) -> TimeWindow {
+        let n_reqs = self.pickups.len();
+        if node == 0 || node > 2 * n_reqs {
+            // Passing a depot within a route takes no service time
+            TimeWindow { service: 0.0, ..self.depot }
+        } else if node <= n_reqs {
+            self.pickups[node - 1]
+        } else {
+            self.dropoffs[node - n_reqs - 1]
+        }
+    }
+}
+
+impl fmt::Display for TimeWindows {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        write!(f, "horizon [{}, {}]", self.depot.earliest, self.depot.latest)
+    }
+}
@@ -1,3 +1,7 @@
+use std::cell::{Cell, RefCell};
+use std::time::Instant;
+use rand::prelude::*;
+use crate::solver::SearchStats;
 use crate::{Instance, LocalSearch, LocalSearchConfig, Neighborhood, RandomConstruction, Solution, Solver, StepFunction, AcceptanceCriterion};
 
 /// Variable Neighborhood Descent solver
@@ -8,6 +12,8 @@ pub struct VND<'a> {
     neighborhoods: Vec<Neighborhood>,
     max_iterations: usize,
     biased_construction: bool,
+    rng: RefCell<StdRng>,
+    stats: Cell<SearchStats>,
 }
 
 impl<'a> VND<'a> {
@@ -17,6 +23,8 @@ impl<'a> VND<'a> {
             neighborhoods,
             max_iterations: 1000,
             biased_construction: true,
+            rng: RefCell::new(StdRng::from_entropy()),
+            stats: Cell::new(SearchStats::default()),
         }
     }
 
@@ -30,8 +38,16 @@ impl<'a> VND<'a> {
         self
     }
 
+    /// Make the random initial solution reproducible
+    pub fn with_seed(self, seed: u64) -> Self {
+        self.rng.replace(StdRng::seed_from_u64(seed));
+        self
+    }
+
     fn construct_initial_solution(&self) -> Solution {
-        let random_construction = RandomConstruction::new(self.instance, self.biased_construction);
+        let seed = self.rng.borrow_mut().gen();
+        let random_construction = RandomConstruction::new(self.instance, self.biased_construction)
+            .with_seed(seed);
         random_construction.solve()
     }
 
@@ -50,6 +66,10 @@ impl<'a> VND<'a> {
         
         // Generate all neighbors in this neighborhood
         let neighbors = local_search.generate_neighbors(current);
+
+        let mut stats = self.stats.get();
+        stats.evaluations += neighbors.len();
+        self.stats.set(stats);
         
         if neighbors.is_empty() {
             return None;
@@ -74,10 +94,14 @@ impl<'a> VND<'a> {
 
 impl<'a> Solver for VND<'a> {
     fn solve(&self) -> Solution {
+        let start_time = Instant::now();
+        self.stats.set(SearchStats::default());
+
         // Start with an initial solution
         let mut current = self.construct_initial_solution();
         let mut best_solution = current.clone();
         let mut best_obj = current.objective_function_value();
+        let mut time_to_best = start_time.elapsed().as_secs_f64();
 
         let mut iterations = 0;
 
@@ -97,6 +121,7 @@ impl<'a> Solver for VND<'a> {
                     if better_obj < best_obj {
                         best_solution = current.clone();
                         best_obj = better_obj;
+                        time_to_best = start_time.elapsed().as_secs_f64();
                     }
                     
                     improved = true;
@@ -113,6 +138,22 @@ impl<'a> Solver for VND<'a> {
             iterations += 1;
         }
 
+        let mut stats = self.stats.get();
+        stats.iterations = iterations;
+        stats.time_to_best_seconds = Some(time_to_best);
+        self.stats.set(stats);
+
         best_solution
     }
+
+    fn stats(&self) -> SearchStats {
+        self.stats.get()
+    }
+
+    fn config_description(&self) -> String {
+        format!(
+            "neighborhoods={:?} max_iterations={} biased_construction={}",
+            self.neighborhoods, self.max_iterations, self.biased_construction
+        )
+    }
 }
\ No newline at end of file